@@ -259,6 +259,19 @@ function getChatCompletionResponse() {
   };
 }
 
+// Anthropic Messages response
+function getAnthropicMessageResponse() {
+  return {
+    id: "msg_mock_" + Date.now(),
+    type: "message",
+    role: "assistant",
+    model: "mock-model",
+    content: [{ type: "text", text: "Mock response - quota activated" }],
+    stop_reason: "end_turn",
+    usage: { input_tokens: 10, output_tokens: 5 }
+  };
+}
+
 const server = http.createServer((req, res) => {
   const parsedUrl = url.parse(req.url, true);
   const path = parsedUrl.pathname;
@@ -320,7 +333,7 @@ const server = http.createServer((req, res) => {
   }
 
   // All other endpoints need auth → per-key state
-  const authHeader = req.headers['authorization'] || '';
+  const authHeader = req.headers['authorization'] || req.headers['x-api-key'] || '';
   const state = getKeyState(authHeader);
   state.requestCount++;
 
@@ -347,6 +360,13 @@ const server = http.createServer((req, res) => {
     return;
   }
 
+  if (path.endsWith('/v1/messages')) {
+    performWake(state);
+    res.writeHead(200, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify(getAnthropicMessageResponse()));
+    return;
+  }
+
   if (path.includes('/wake') || path.includes('/warmup')) {
     performWake(state);
     res.writeHead(200, { 'Content-Type': 'application/json' });
@@ -426,7 +446,7 @@ server.listen(config.port, () => {
   console.log('');
   console.log('  Behavior:');
   console.log('    - Each key starts COLD (no nextResetTime)');
-  console.log('    - POST /chat/completions → WAKE that key (starts timer, generates data)');
+  console.log('    - POST /chat/completions or /v1/messages → WAKE that key (starts timer, generates data)');
   console.log('    - GET requests return stable data for that key until next wake/expiry');
   console.log(`    - After ${config.expiryMinutes}min → that key goes COLD again`);
   console.log('');
@@ -435,6 +455,7 @@ server.listen(config.port, () => {
   console.log('    GET  /api/monitor/usage/model-usage       - Model call/token stats');
   console.log('    GET  /api/monitor/usage/tool-usage        - Tool usage stats');
   console.log('    POST /api/coding/paas/v4/chat/completions - Wake');
+  console.log('    POST /api/anthropic/v1/messages           - Wake (Anthropic)');
   console.log('    GET  /health                              - Health check (all keys)');
   console.log('');
 });
@@ -1,3 +1,6 @@
 fn main() {
-    tauri_build::build()
+    // The headless daemon build (`--no-default-features`) has no Tauri context.
+    if std::env::var_os("CARGO_FEATURE_GUI").is_some() {
+        tauri_build::build()
+    }
 }
@@ -0,0 +1,219 @@
+//! Scriptable fault injection. Rules are checked in the order they were
+//! added; the first one matching the endpoint applies and uses up one of
+//! its `times`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Endpoint {
+    Quota,
+    ModelUsage,
+    ToolUsage,
+    ChatCompletions,
+    /// Anthropic-compatible `…/v1/messages`.
+    Messages,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 5] = [
+        Endpoint::Quota,
+        Endpoint::ModelUsage,
+        Endpoint::ToolUsage,
+        Endpoint::ChatCompletions,
+        Endpoint::Messages,
+    ];
+
+    /// Endpoint served at `path`; matched on the suffix like the Node mock,
+    /// so Z.ai and BigModel path prefixes both work.
+    pub fn for_path(path: &str) -> Option<Self> {
+        if path.contains("/quota/limit") {
+            Some(Self::Quota)
+        } else if path.contains("/model-usage") {
+            Some(Self::ModelUsage)
+        } else if path.contains("/tool-usage") {
+            Some(Self::ToolUsage)
+        } else if path.contains("/chat/completions") {
+            Some(Self::ChatCompletions)
+        } else if path.ends_with("/v1/messages") {
+            Some(Self::Messages)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a `POST` here wakes the key.
+    pub fn is_wake(&self) -> bool {
+        matches!(self, Self::ChatCompletions | Self::Messages)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Quota => "quota",
+            Self::ModelUsage => "model-usage",
+            Self::ToolUsage => "tool-usage",
+            Self::ChatCompletions => "chat-completions",
+            Self::Messages => "messages",
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Fault {
+    /// Answer with this HTTP status and a JSON error body.
+    Http { status: u16 },
+    /// HTTP 200 with `code` set to this value instead of 200.
+    ApiCode { code: i64 },
+    /// HTTP 200 with a body that is not valid JSON.
+    MalformedJson,
+    /// Answer normally after waiting this long.
+    Delay { ms: u64 },
+    /// Quota only: a warm key reports no `nextResetTime`.
+    MissingResetTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultRule {
+    /// `None` matches every endpoint.
+    #[serde(default)]
+    pub endpoint: Option<Endpoint>,
+    pub fault: Fault,
+    /// Requests left to affect; `None` means every request until cleared.
+    #[serde(default)]
+    pub times: Option<u32>,
+}
+
+impl FaultRule {
+    pub fn once(endpoint: Endpoint, fault: Fault) -> Self {
+        Self::times(endpoint, fault, 1)
+    }
+
+    pub fn times(endpoint: Endpoint, fault: Fault, times: u32) -> Self {
+        Self {
+            endpoint: Some(endpoint),
+            fault,
+            times: Some(times),
+        }
+    }
+
+    pub fn always(endpoint: Endpoint, fault: Fault) -> Self {
+        Self {
+            endpoint: Some(endpoint),
+            fault,
+            times: None,
+        }
+    }
+
+    fn matches(&self, endpoint: Endpoint) -> bool {
+        self.endpoint.is_none_or(|e| e == endpoint) && self.times != Some(0)
+    }
+}
+
+/// Apply the first rule matching `endpoint`, dropping rules that ran out.
+pub(crate) fn take(rules: &mut Vec<FaultRule>, endpoint: Endpoint) -> Option<Fault> {
+    let index = rules.iter().position(|rule| rule.matches(endpoint))?;
+    let rule = &mut rules[index];
+    let fault = rule.fault.clone();
+    if let Some(times) = rule.times.as_mut() {
+        *times -= 1;
+        if *times == 0 {
+            rules.remove(index);
+        }
+    }
+    Some(fault)
+}
+
+/// `<endpoint>:<fault>[*<times>]`, e.g. `quota:http=500*2`,
+/// `chat-completions:code=1113`, `any:delay=3000`, `quota:no-reset`,
+/// `model-usage:malformed`. Without `*<times>` the rule never runs out.
+impl FromStr for FaultRule {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (endpoint, rest) = spec
+            .split_once(':')
+            .ok_or_else(|| format!("invalid fault '{spec}': expected <endpoint>:<fault>"))?;
+        let endpoint = match endpoint {
+            "any" | "*" => None,
+            name => Some(
+                Endpoint::ALL
+                    .into_iter()
+                    .find(|e| e.as_str() == name || (name == "chat" && *e == Endpoint::ChatCompletions))
+                    .ok_or_else(|| format!("unknown endpoint '{name}'"))?,
+            ),
+        };
+        let (fault, times) = match rest.split_once('*') {
+            Some((fault, times)) => (
+                fault,
+                Some(
+                    times
+                        .parse::<u32>()
+                        .map_err(|err| format!("invalid fault count '{times}': {err}"))?,
+                ),
+            ),
+            None => (rest, None),
+        };
+        let number = |value: &str| {
+            value
+                .parse::<i64>()
+                .map_err(|err| format!("invalid fault value '{value}': {err}"))
+        };
+        let fault = match fault.split_once('=') {
+            Some(("http", status)) => Fault::Http {
+                status: u16::try_from(number(status)?).map_err(|err| format!("invalid status: {err}"))?,
+            },
+            Some(("code", code)) => Fault::ApiCode { code: number(code)? },
+            Some(("delay", ms)) => Fault::Delay {
+                ms: u64::try_from(number(ms)?).map_err(|err| format!("invalid delay: {err}"))?,
+            },
+            None if fault == "malformed" => Fault::MalformedJson,
+            None if fault == "no-reset" => Fault::MissingResetTime,
+            _ => return Err(format!("unknown fault '{fault}'")),
+        };
+        Ok(Self { endpoint, fault, times })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fault_rules() {
+        assert_eq!(
+            "quota:http=500*2".parse::<FaultRule>().unwrap(),
+            FaultRule::times(Endpoint::Quota, Fault::Http { status: 500 }, 2)
+        );
+        assert_eq!(
+            "chat:code=1113".parse::<FaultRule>().unwrap(),
+            FaultRule::always(Endpoint::ChatCompletions, Fault::ApiCode { code: 1113 })
+        );
+        let any = "any:delay=250".parse::<FaultRule>().unwrap();
+        assert_eq!((any.endpoint, any.fault), (None, Fault::Delay { ms: 250 }));
+        assert!("quota:teapot".parse::<FaultRule>().is_err());
+        assert!("nowhere:malformed".parse::<FaultRule>().is_err());
+    }
+
+    #[test]
+    fn test_take_uses_up_rules_in_order() {
+        let mut rules = vec![
+            FaultRule::once(Endpoint::Quota, Fault::MalformedJson),
+            FaultRule::always(Endpoint::Quota, Fault::MissingResetTime),
+        ];
+        assert_eq!(take(&mut rules, Endpoint::ToolUsage), None);
+        assert_eq!(take(&mut rules, Endpoint::Quota), Some(Fault::MalformedJson));
+        assert_eq!(take(&mut rules, Endpoint::Quota), Some(Fault::MissingResetTime));
+        assert_eq!(take(&mut rules, Endpoint::Quota), Some(Fault::MissingResetTime));
+        assert_eq!(rules.len(), 1);
+    }
+}
@@ -0,0 +1,301 @@
+//! Mock Z.ai / BigModel API for GLM Tray, usable from `cargo test` and as
+//! the `glm-mock-server` binary. It mirrors `docs/mock-server.cjs`: each API
+//! key gets its own COLD/WARM state, and `POST …/chat/completions` (or the
+//! Anthropic-style `POST …/v1/messages`) wakes it for `expiry`. Faults (HTTP errors, `code != 200`, malformed JSON, slow
+//! responses, a missing `nextResetTime`) can be scripted per endpoint, from
+//! code via `MockServer::inject` or over HTTP at `/_mock/faults`.
+
+mod fault;
+mod state;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::NaiveDateTime;
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+pub use fault::{Endpoint, Fault, FaultRule};
+pub use state::KeySnapshot;
+
+use state::KeyState;
+
+/// Current time in epoch ms. Tests pass their fake clock's time here so
+/// the mock's reset windows move with it.
+pub type NowFn = Arc<dyn Fn() -> i64 + Send + Sync>;
+
+/// Queries spanning more than this get the 24h usage totals.
+const SHORT_WINDOW_MS: i64 = 5 * 3_600_000 + 60_000;
+
+#[derive(Clone)]
+pub struct MockConfig {
+    /// Port on 127.0.0.1; 0 picks a free one.
+    pub port: u16,
+    /// How long a key stays WARM after a wake.
+    pub expiry: Duration,
+    pub now: NowFn,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            port: 0,
+            expiry: Duration::from_secs(3 * 60),
+            now: Arc::new(|| chrono::Utc::now().timestamp_millis()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    keys: HashMap<String, KeyState>,
+    faults: Vec<FaultRule>,
+    requests: HashMap<Endpoint, u64>,
+    total_requests: u64,
+}
+
+struct Shared {
+    config: MockConfig,
+    inner: Mutex<Inner>,
+}
+
+impl Shared {
+    fn now_ms(&self) -> i64 {
+        (self.config.now)()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A running mock server; stops when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(config: MockConfig) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", config.port)).await?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            config,
+            inner: Mutex::default(),
+        });
+        let app = Router::new()
+            .route("/", get(health))
+            .route("/health", get(health))
+            .route("/_mock/faults", get(list_faults).post(add_fault).delete(clear_faults))
+            .fallback(api)
+            .with_state(shared.clone());
+        let handle = tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                log::warn!("mock server stopped: {err}");
+            }
+        });
+        Ok(Self { addr, shared, handle })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL to use as GLM Tray's `mock_url`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn inject(&self, rule: FaultRule) {
+        self.shared.lock().faults.push(rule);
+    }
+
+    pub fn clear_faults(&self) {
+        self.shared.lock().faults.clear();
+    }
+
+    /// State of the key sent as `api_key` (with or without `Bearer `), if
+    /// it has made a request.
+    pub fn key(&self, api_key: &str) -> Option<KeySnapshot> {
+        let now_ms = self.shared.now_ms();
+        self.shared
+            .lock()
+            .keys
+            .get(token(api_key))
+            .map(|state| state.snapshot(now_ms))
+    }
+
+    /// Requests served for `endpoint`, faults included.
+    pub fn requests(&self, endpoint: Endpoint) -> u64 {
+        self.shared.lock().requests.get(&endpoint).copied().unwrap_or(0)
+    }
+
+    /// Resolves when the server task ends (it only does on error or abort).
+    pub async fn wait(mut self) {
+        let _ = (&mut self.handle).await;
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn token(authorization: &str) -> &str {
+    let value = authorization.trim();
+    value
+        .strip_prefix("Bearer ")
+        .or_else(|| value.strip_prefix("bearer "))
+        .unwrap_or(value)
+        .trim()
+}
+
+async fn health(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    let now_ms = shared.now_ms();
+    let inner = shared.lock();
+    let keys: Vec<KeySnapshot> = inner.keys.values().map(|state| state.snapshot(now_ms)).collect();
+    Json(json!({
+        "status": "ok",
+        "message": "Mock server running",
+        "totalRequests": inner.total_requests,
+        "keys": keys,
+        "faults": inner.faults,
+        "config": { "expiryMinutes": shared.config.expiry.as_secs_f64() / 60.0 },
+    }))
+}
+
+async fn list_faults(State(shared): State<Arc<Shared>>) -> Json<Vec<FaultRule>> {
+    Json(shared.lock().faults.clone())
+}
+
+async fn add_fault(State(shared): State<Arc<Shared>>, Json(rule): Json<FaultRule>) -> StatusCode {
+    log::info!("fault added: {rule:?}");
+    shared.lock().faults.push(rule);
+    StatusCode::NO_CONTENT
+}
+
+async fn clear_faults(State(shared): State<Arc<Shared>>) -> StatusCode {
+    shared.lock().faults.clear();
+    StatusCode::NO_CONTENT
+}
+
+async fn api(
+    State(shared): State<Arc<Shared>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let Some(endpoint) = Endpoint::for_path(uri.path()) else {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "Not found", "path": uri.path() }))).into_response();
+    };
+    if endpoint.is_wake() != (method == Method::POST) {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+    let token = token(
+        [header::AUTHORIZATION.as_str(), "x-api-key"]
+            .into_iter()
+            .find_map(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+            .unwrap_or_default(),
+    );
+    let token = if token.is_empty() { "__default__" } else { token }.to_string();
+
+    let fault = {
+        let mut inner = shared.lock();
+        inner.total_requests += 1;
+        *inner.requests.entry(endpoint).or_default() += 1;
+        let state = inner
+            .keys
+            .entry(token.clone())
+            .or_insert_with(|| KeyState::new(&token));
+        state.request_count += 1;
+        log::info!("{method} {} [{}]", uri.path(), state.key_id());
+        fault::take(&mut inner.faults, endpoint)
+    };
+
+    match fault {
+        Some(Fault::Http { status }) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            return (status, Json(json!({ "error": { "code": status.as_str(), "message": "injected fault" } })))
+                .into_response();
+        }
+        Some(Fault::ApiCode { code }) => {
+            return Json(json!({ "code": code, "msg": "injected fault", "success": false })).into_response();
+        }
+        Some(Fault::MalformedJson) => {
+            return ([(header::CONTENT_TYPE, "application/json")], "{\"code\": 200, \"data\": ").into_response();
+        }
+        Some(Fault::Delay { ms }) => tokio::time::sleep(Duration::from_millis(ms)).await,
+        Some(Fault::MissingResetTime) | None => {}
+    }
+
+    let now_ms = shared.now_ms();
+    let expiry_ms = shared.config.expiry.as_millis() as i64;
+    let mut inner = shared.lock();
+    let Some(state) = inner.keys.get_mut(&token) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let data = match endpoint {
+        Endpoint::Quota => state.quota(now_ms, fault != Some(Fault::MissingResetTime)),
+        Endpoint::ModelUsage => state.model_usage(now_ms, is_long_window(&query)),
+        Endpoint::ToolUsage => state.tool_usage(now_ms),
+        Endpoint::ChatCompletions => {
+            state.wake(now_ms, expiry_ms);
+            return Json(chat_completion(now_ms)).into_response();
+        }
+        Endpoint::Messages => {
+            state.wake(now_ms, expiry_ms);
+            return Json(anthropic_message(now_ms)).into_response();
+        }
+    };
+    Json(json!({ "code": 200, "data": data, "msg": "Operation successful", "success": true })).into_response()
+}
+
+/// Whether `startTime`..`endTime` covers more than the 5h window.
+fn is_long_window(query: &HashMap<String, String>) -> bool {
+    let parse = |name: &str| {
+        query
+            .get(name)
+            .and_then(|value| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+    };
+    match (parse("startTime"), parse("endTime")) {
+        (Some(start), Some(end)) => (end - start).num_milliseconds() > SHORT_WINDOW_MS,
+        _ => true,
+    }
+}
+
+fn chat_completion(now_ms: i64) -> Value {
+    json!({
+        "id": format!("mock-{now_ms}"),
+        "object": "chat.completion",
+        "created": now_ms / 1_000,
+        "model": "mock-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": "Mock response - quota activated" },
+            "finish_reason": "stop",
+        }],
+        "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 },
+    })
+}
+
+fn anthropic_message(now_ms: i64) -> Value {
+    json!({
+        "id": format!("msg_mock_{now_ms}"),
+        "type": "message",
+        "role": "assistant",
+        "model": "mock-model",
+        "content": [{ "type": "text", "text": "Mock response - quota activated" }],
+        "stop_reason": "end_turn",
+        "usage": { "input_tokens": 10, "output_tokens": 5 },
+    })
+}
@@ -0,0 +1,109 @@
+//! `glm-mock-server [--port=PORT] [--expiry=MINUTES] [--fault=SPEC]...`
+//!
+//! Same options and environment variables (`MOCK_PORT`, `MOCK_EXPIRY`) as
+//! `docs/mock-server.cjs`. Each `--fault` adds a rule such as
+//! `quota:http=500*2`; see `FaultRule`'s `FromStr` for the syntax.
+
+use std::time::Duration;
+
+use glm_mock_server::{MockConfig, MockServer};
+
+const DEFAULT_PORT: u16 = 3456;
+const DEFAULT_EXPIRY_MINUTES: f64 = 3.0;
+
+struct Args {
+    port: u16,
+    expiry_minutes: f64,
+    faults: Vec<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let mut args = Args {
+        port: env("MOCK_PORT").map_or(Ok(DEFAULT_PORT), |v| parse(&v, "MOCK_PORT"))?,
+        expiry_minutes: env("MOCK_EXPIRY").map_or(Ok(DEFAULT_EXPIRY_MINUTES), |v| parse(&v, "MOCK_EXPIRY"))?,
+        faults: Vec::new(),
+    };
+
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| argv.next())
+                .ok_or_else(|| format!("{flag} needs a value"))
+        };
+        match flag.as_str() {
+            "--port" => args.port = parse(&value()?, "--port")?,
+            "--expiry" => args.expiry_minutes = parse(&value()?, "--expiry")?,
+            "--fault" => args.faults.push(value()?),
+            "-h" | "--help" => {
+                println!("usage: glm-mock-server [--port=PORT] [--expiry=MINUTES] [--fault=ENDPOINT:FAULT[*TIMES]]...");
+                std::process::exit(0);
+            }
+            other if !other.starts_with('-') => args.port = parse(other, "port")?,
+            other => return Err(format!("unknown option '{other}'")),
+        }
+    }
+    Ok(args)
+}
+
+fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|err| format!("invalid {name} '{value}': {err}"))
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("glm-mock-server: {err}");
+            std::process::exit(2);
+        }
+    };
+    let config = MockConfig {
+        port: args.port,
+        expiry: Duration::from_secs_f64(args.expiry_minutes.max(0.0) * 60.0),
+        ..MockConfig::default()
+    };
+    let server = match MockServer::start(config).await {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("glm-mock-server: failed to bind port {}: {err}", args.port);
+            std::process::exit(1);
+        }
+    };
+    for spec in &args.faults {
+        match spec.parse() {
+            Ok(rule) => server.inject(rule),
+            Err(err) => {
+                eprintln!("glm-mock-server: {err}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    println!("Mock API server running on {}", server.url());
+    println!("  quota expiry after wake: {} minutes", args.expiry_minutes);
+    println!("  GET  /api/monitor/usage/quota/limit        quota & timer state");
+    println!("  GET  /api/monitor/usage/model-usage        model call/token stats");
+    println!("  GET  /api/monitor/usage/tool-usage         tool usage stats");
+    println!("  POST /api/coding/paas/v4/chat/completions  wake");
+    println!("  POST /api/anthropic/v1/messages            wake (Anthropic)");
+    println!("  GET  /health                               state of all keys");
+    println!("  GET|POST|DELETE /_mock/faults              list, add or clear faults");
+
+    tokio::select! {
+        _ = server.wait() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
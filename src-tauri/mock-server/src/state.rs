@@ -0,0 +1,235 @@
+//! Per-key COLD/WARM state. A key starts COLD (no `nextResetTime`); a chat
+//! completion wakes it for the configured expiry, and each wake picks new
+//! usage numbers from the key's profile.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+struct Profile {
+    label: &'static str,
+    percentage: (u64, u64),
+    calls: (u64, u64),
+    tokens: (u64, u64),
+}
+
+/// Same profiles as `docs/mock-server.cjs`, so keys look distinct.
+const PROFILES: [Profile; 4] = [
+    Profile { label: "Alpha", percentage: (5, 15), calls: (50, 150), tokens: (500_000, 2_000_000) },
+    Profile { label: "Beta", percentage: (25, 45), calls: (200, 500), tokens: (3_000_000, 8_000_000) },
+    Profile { label: "Gamma", percentage: (50, 70), calls: (400, 800), tokens: (8_000_000, 20_000_000) },
+    Profile { label: "Delta", percentage: (10, 30), calls: (100, 300), tokens: (1_000_000, 5_000_000) },
+];
+
+#[derive(Debug, Clone, Default)]
+struct Usage {
+    percentage: u64,
+    time_limit_usage: u64,
+    search_prime: u64,
+    web_reader: u64,
+    zread: u64,
+    model_calls_5h: u64,
+    tokens_5h: u64,
+    model_calls_24h: u64,
+    tokens_24h: u64,
+    tool_network_search: u64,
+    tool_web_read: u64,
+    tool_zread: u64,
+    tool_search_mcp: u64,
+}
+
+pub(crate) struct KeyState {
+    key_id: String,
+    profile: usize,
+    seed: u64,
+    wake_at_ms: Option<i64>,
+    expires_at_ms: Option<i64>,
+    wake_count: u64,
+    pub(crate) request_count: u64,
+    usage: Usage,
+}
+
+/// A key's state as seen by tests and `/health`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeySnapshot {
+    pub key_id: String,
+    pub profile: String,
+    pub warm: bool,
+    pub wake_at_ms: Option<i64>,
+    pub next_reset_ms: Option<i64>,
+    pub percentage: u64,
+    pub wake_count: u64,
+    pub request_count: u64,
+}
+
+impl KeyState {
+    pub(crate) fn new(token: &str) -> Self {
+        // FNV-1a: stable across runs, so a key always gets the same profile.
+        let hash = token
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3));
+        Self {
+            key_id: key_id(token),
+            profile: (hash % PROFILES.len() as u64) as usize,
+            seed: hash,
+            wake_at_ms: None,
+            expires_at_ms: None,
+            wake_count: 0,
+            request_count: 0,
+            usage: Usage::default(),
+        }
+    }
+
+    pub(crate) fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub(crate) fn is_warm(&self, now_ms: i64) -> bool {
+        self.expires_at_ms.is_some_and(|expires| now_ms < expires)
+    }
+
+    pub(crate) fn wake(&mut self, now_ms: i64, expiry_ms: i64) {
+        self.wake_at_ms = Some(now_ms);
+        self.expires_at_ms = Some(now_ms + expiry_ms);
+        self.wake_count += 1;
+
+        let profile = &PROFILES[self.profile];
+        let mut rng = Rng(self.seed ^ self.wake_count.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let model_calls_5h = rng.range(profile.calls);
+        let tokens_5h = rng.range(profile.tokens);
+        self.usage = Usage {
+            percentage: rng.range(profile.percentage),
+            time_limit_usage: rng.range((10, 80)),
+            search_prime: rng.range((1, 15)),
+            web_reader: rng.range((1, 10)),
+            zread: rng.range((2, 30)),
+            model_calls_5h,
+            tokens_5h,
+            model_calls_24h: model_calls_5h + rng.range((100, 400)),
+            tokens_24h: tokens_5h + rng.range(profile.tokens),
+            tool_network_search: rng.range((5, 60)),
+            tool_web_read: rng.range((2, 40)),
+            tool_zread: rng.range((10, 120)),
+            tool_search_mcp: rng.range((5, 50)),
+        };
+        log::info!(
+            "[{}] wake #{}: warm until {} ms, {}%",
+            self.key_id,
+            self.wake_count,
+            now_ms + expiry_ms,
+            self.usage.percentage
+        );
+    }
+
+    pub(crate) fn snapshot(&self, now_ms: i64) -> KeySnapshot {
+        let warm = self.is_warm(now_ms);
+        KeySnapshot {
+            key_id: self.key_id.clone(),
+            profile: PROFILES[self.profile].label.to_string(),
+            warm,
+            wake_at_ms: self.wake_at_ms,
+            next_reset_ms: self.expires_at_ms.filter(|_| warm),
+            percentage: if warm { self.usage.percentage } else { 0 },
+            wake_count: self.wake_count,
+            request_count: self.request_count,
+        }
+    }
+
+    pub(crate) fn quota(&self, now_ms: i64, with_reset_time: bool) -> Value {
+        let warm = self.is_warm(now_ms);
+        let usage = &self.usage;
+        let mut tokens_limit = json!({
+            "type": "TOKENS_LIMIT",
+            "unit": 3,
+            "number": 5,
+            "percentage": if warm { usage.percentage } else { 0 },
+        });
+        if warm && with_reset_time {
+            tokens_limit["nextResetTime"] = json!(self.expires_at_ms);
+        }
+        let details = if warm {
+            json!([
+                { "modelCode": "search-prime", "usage": usage.search_prime },
+                { "modelCode": "web-reader", "usage": usage.web_reader },
+                { "modelCode": "zread", "usage": usage.zread },
+            ])
+        } else {
+            json!([])
+        };
+        let used = if warm { usage.time_limit_usage } else { 0 };
+        json!({
+            "limits": [
+                {
+                    "type": "TIME_LIMIT",
+                    "unit": 5,
+                    "number": 1,
+                    "usage": 1000,
+                    "currentValue": used,
+                    "remaining": 1000 - used,
+                    "percentage": used / 10,
+                    "nextResetTime": null,
+                    "usageDetails": details,
+                },
+                tokens_limit,
+            ],
+            "level": "pro",
+        })
+    }
+
+    /// Totals for the 5h window, or the 24h one when `long_window` is set.
+    pub(crate) fn model_usage(&self, now_ms: i64, long_window: bool) -> Value {
+        let (calls, tokens) = match (self.is_warm(now_ms), long_window) {
+            (false, _) => (0, 0),
+            (true, false) => (self.usage.model_calls_5h, self.usage.tokens_5h),
+            (true, true) => (self.usage.model_calls_24h, self.usage.tokens_24h),
+        };
+        json!({
+            "totalUsage": {
+                "totalModelCallCount": calls,
+                "totalTokensUsage": tokens,
+            }
+        })
+    }
+
+    pub(crate) fn tool_usage(&self, now_ms: i64) -> Value {
+        let warm = self.is_warm(now_ms);
+        let count = |value: u64| if warm { value } else { 0 };
+        json!({
+            "totalUsage": {
+                "totalNetworkSearchCount": count(self.usage.tool_network_search),
+                "totalWebReadMcpCount": count(self.usage.tool_web_read),
+                "totalZreadMcpCount": count(self.usage.tool_zread),
+                "totalSearchMcpCount": count(self.usage.tool_search_mcp),
+            }
+        })
+    }
+}
+
+/// `abcd…wxyz` for logs; short tokens are shown as-is.
+fn key_id(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() < 8 {
+        return if token.is_empty() { "no-key".to_string() } else { token.to_string() };
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}…{tail}")
+}
+
+/// xorshift64*: deterministic usage numbers without a `rand` dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0.max(1);
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn range(&mut self, (min, max): (u64, u64)) -> u64 {
+        min + self.next() % (max - min + 1)
+    }
+}
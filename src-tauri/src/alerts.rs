@@ -0,0 +1,365 @@
+//! Threshold alerts. The scheduler reports quota snapshots and wake outcomes
+//! as they arrive; rules from `AppConfig::alerts` that match become desktop
+//! notifications, subject to per-rule quiet periods and a global debounce.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Local;
+use log::info;
+use serde_json::json;
+use tokio::sync::Mutex;
+use tokio::time::{self, Duration};
+
+use crate::clock::ClockHandle;
+use crate::file_logger;
+use crate::forecast::RESET_JITTER_MS;
+use crate::host::HostHandle;
+use crate::models::{AlertKind, AlertRule, AlertSettings, KeySlotConfig};
+
+const NOTIFICATION_TITLE: &str = "GLM Tray";
+
+/// Quota state before and after one successful poll.
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaTransition {
+    pub previous_percentage: Option<u8>,
+    pub percentage: u8,
+    pub previous_timer_active: Option<bool>,
+    pub timer_active: bool,
+    pub previous_reset_ms: Option<i64>,
+    pub reset_ms: Option<i64>,
+}
+
+impl QuotaTransition {
+    pub fn window_started(&self) -> bool {
+        match self.previous_timer_active {
+            Some(false) => self.timer_active,
+            Some(true) => {
+                self.timer_active
+                    && matches!(
+                        (self.previous_reset_ms, self.reset_ms),
+                        (Some(prev), Some(next)) if next - prev > RESET_JITTER_MS
+                    )
+            }
+            None => false,
+        }
+    }
+
+    pub fn window_ended(&self) -> bool {
+        self.previous_timer_active == Some(true) && !self.timer_active
+    }
+}
+
+/// Something the scheduler observed for a slot.
+#[derive(Debug, Clone)]
+pub enum AlertEvent {
+    Quota(QuotaTransition),
+    WakeConfirmed,
+    WakeFailed(String),
+    WakeAutoDisabled,
+    ForecastExhaustion { at_ms: i64 },
+}
+
+#[derive(Default)]
+struct EngineState {
+    /// `rule key:slot` → epoch ms of the last notification for it.
+    last_fired: HashMap<String, i64>,
+    last_notified_ms: Option<i64>,
+    /// Messages waiting for the debounce window to pass.
+    pending: Vec<String>,
+    flush_scheduled: bool,
+}
+
+/// Shared by all slot tasks of one scheduler.
+pub struct AlertEngine {
+    state: Mutex<EngineState>,
+    clock: ClockHandle,
+}
+
+impl AlertEngine {
+    /// Quiet periods and the debounce window are measured on `clock`.
+    pub fn new(clock: ClockHandle) -> Self {
+        Self {
+            state: Mutex::default(),
+            clock,
+        }
+    }
+
+    /// Check `event` against every enabled rule for this slot and notify for
+    /// those that match and are outside their quiet period.
+    pub async fn evaluate(
+        self: &Arc<Self>,
+        host: &HostHandle,
+        settings: &AlertSettings,
+        slot_cfg: &KeySlotConfig,
+        event: &AlertEvent,
+    ) {
+        if !settings.enabled {
+            return;
+        }
+        let now_ms = self.clock.now_ms();
+        let slot_name = if slot_cfg.name.is_empty() {
+            format!("Key {}", slot_cfg.slot)
+        } else {
+            slot_cfg.name.clone()
+        };
+
+        for rule in &settings.rules {
+            if !rule.enabled || (!rule.slots.is_empty() && !rule.slots.contains(&slot_cfg.slot)) {
+                continue;
+            }
+            let Some(message) = describe(rule, event) else {
+                continue;
+            };
+
+            let key = format!("{:?}:{}:{}", rule.kind, rule.threshold, slot_cfg.slot);
+            {
+                let mut state = self.state.lock().await;
+                let quiet_until = state
+                    .last_fired
+                    .get(&key)
+                    .map_or(i64::MIN, |last| last + rule.quiet_minutes as i64 * 60_000);
+                if now_ms < quiet_until {
+                    continue;
+                }
+                state.last_fired.insert(key, now_ms);
+            }
+
+            info!("slot {} alert: {}", slot_cfg.slot, message);
+            host.emit(
+                "alert-fired",
+                json!({ "slot": slot_cfg.slot, "kind": rule.kind, "message": message }),
+            );
+            if slot_cfg.logging {
+                let _ = file_logger::append(
+                    host,
+                    file_logger::event_entry(
+                        slot_cfg.slot,
+                        "alert.fired",
+                        Some(json!({ "kind": rule.kind, "threshold": rule.threshold, "message": message })),
+                    ),
+                )
+                .await;
+            }
+            self.notify(host, settings.debounce_seconds, format!("{slot_name}: {message}"))
+                .await;
+        }
+    }
+
+    /// Show the notification now, or queue it until the debounce window
+    /// since the last one has passed.
+    async fn notify(self: &Arc<Self>, host: &HostHandle, debounce_seconds: u64, line: String) {
+        let now_ms = self.clock.now_ms();
+        let mut state = self.state.lock().await;
+        let due_ms = state
+            .last_notified_ms
+            .map_or(now_ms, |last| last + debounce_seconds as i64 * 1_000);
+
+        if now_ms >= due_ms && state.pending.is_empty() {
+            state.last_notified_ms = Some(now_ms);
+            drop(state);
+            host.notify(NOTIFICATION_TITLE, &line);
+            return;
+        }
+
+        state.pending.push(line);
+        if !state.flush_scheduled {
+            state.flush_scheduled = true;
+            let engine = self.clone();
+            let host = host.clone();
+            let wait_ms = (due_ms - now_ms).max(0) as u64;
+            tokio::spawn(async move {
+                time::sleep(Duration::from_millis(wait_ms)).await;
+                engine.flush(&host).await;
+            });
+        }
+    }
+
+    async fn flush(&self, host: &HostHandle) {
+        let lines = {
+            let mut state = self.state.lock().await;
+            state.flush_scheduled = false;
+            state.last_notified_ms = Some(self.clock.now_ms());
+            std::mem::take(&mut state.pending)
+        };
+        match lines.as_slice() {
+            [] => {}
+            [line] => host.notify(NOTIFICATION_TITLE, line),
+            _ => host.notify(
+                &format!("{NOTIFICATION_TITLE}: {} alerts", lines.len()),
+                &lines.join("\n"),
+            ),
+        }
+    }
+}
+
+/// Notification text when `event` matches `rule`.
+fn describe(rule: &AlertRule, event: &AlertEvent) -> Option<String> {
+    match (rule.kind, event) {
+        (AlertKind::QuotaThreshold, AlertEvent::Quota(q)) => {
+            let previous = q.previous_percentage?;
+            (previous < rule.threshold && q.percentage >= rule.threshold)
+                .then(|| format!("quota at {}% (crossed {}%)", q.percentage, rule.threshold))
+        }
+        (AlertKind::ResetStarted, AlertEvent::Quota(q)) => q
+            .window_started()
+            .then(|| format!("reset window started, resets at {}", format_time(q.reset_ms))),
+        (AlertKind::ResetEnded, AlertEvent::Quota(q)) => {
+            q.window_ended().then(|| "reset window ended".to_string())
+        }
+        (AlertKind::WakeConfirmed, AlertEvent::WakeConfirmed) => Some("wake confirmed".to_string()),
+        (AlertKind::WakeFailed, AlertEvent::WakeFailed(err)) => Some(format!("wake failed: {err}")),
+        (AlertKind::WakeAutoDisabled, AlertEvent::WakeAutoDisabled) => {
+            Some("wake paused after repeated errors, probing periodically".to_string())
+        }
+        (AlertKind::ForecastExhaustion, AlertEvent::ForecastExhaustion { at_ms }) => Some(format!(
+            "on track to hit 100% at {} (before reset)",
+            format_time(Some(*at_ms))
+        )),
+        _ => None,
+    }
+}
+
+fn format_time(epoch_ms: Option<i64>) -> String {
+    epoch_ms
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|ts| ts.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_else(|| "--:--".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::host::Host;
+    use crate::models::{AppConfig, RuntimeStatus};
+    use chrono::{TimeZone, Utc};
+    use serde_json::Value;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    /// Host that records fired alerts and notifications, over a config
+    /// directory removed on drop.
+    struct RecordingHost {
+        dir: TempDir,
+        fired: std::sync::Mutex<Vec<(usize, String)>>,
+        notified: std::sync::Mutex<Vec<(String, String)>>,
+    }
+
+    impl Default for RecordingHost {
+        fn default() -> Self {
+            Self {
+                dir: tempfile::tempdir().unwrap(),
+                fired: Default::default(),
+                notified: Default::default(),
+            }
+        }
+    }
+
+    impl Host for RecordingHost {
+        fn config_dir(&self) -> Result<std::path::PathBuf, String> {
+            Ok(self.dir.path().to_path_buf())
+        }
+
+        fn shared_config(&self) -> Option<Arc<RwLock<AppConfig>>> {
+            None
+        }
+
+        fn emit(&self, event: &str, payload: Value) {
+            if event == "alert-fired" {
+                let slot = payload["slot"].as_u64().unwrap_or(0) as usize;
+                let message = payload["message"].as_str().unwrap_or_default().to_string();
+                self.fired.lock().unwrap().push((slot, message));
+            }
+        }
+
+        fn refresh_status(&self, _runtime: RuntimeStatus, _has_ready_slot: bool) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn notify(&self, title: &str, body: &str) {
+            self.notified.lock().unwrap().push((title.to_string(), body.to_string()));
+        }
+    }
+
+    /// Engine on a fake clock at Monday 2026-03-02 08:00 UTC.
+    fn engine() -> (Arc<AlertEngine>, Arc<FakeClock>, Arc<RecordingHost>, HostHandle) {
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap());
+        let recorder = Arc::new(RecordingHost::default());
+        let host: HostHandle = recorder.clone();
+        (Arc::new(AlertEngine::new(clock.clone())), clock, recorder, host)
+    }
+
+    fn settings(debounce_seconds: u64, rules: &[(AlertKind, u64)]) -> AlertSettings {
+        AlertSettings {
+            enabled: true,
+            debounce_seconds,
+            rules: rules
+                .iter()
+                .map(|&(kind, quiet_minutes)| AlertRule {
+                    kind,
+                    quiet_minutes,
+                    ..AlertRule::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn slot(id: usize) -> KeySlotConfig {
+        KeySlotConfig {
+            slot: id,
+            logging: false,
+            ..KeySlotConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_stay_quiet_per_slot_after_firing() {
+        let (engine, clock, recorder, host) = engine();
+        let settings = settings(0, &[(AlertKind::WakeFailed, 30)]);
+        let failed = AlertEvent::WakeFailed("HTTP 503".to_string());
+
+        engine.evaluate(&host, &settings, &slot(1), &failed).await;
+        clock.advance(29 * MINUTE);
+        engine.evaluate(&host, &settings, &slot(1), &failed).await;
+        // The quiet period is per slot.
+        engine.evaluate(&host, &settings, &slot(2), &failed).await;
+        clock.advance(MINUTE);
+        engine.evaluate(&host, &settings, &slot(1), &failed).await;
+
+        let fired: Vec<usize> = recorder.fired.lock().unwrap().iter().map(|(slot, _)| *slot).collect();
+        assert_eq!(fired, [1, 2, 1]);
+        assert_eq!(recorder.notified.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn alerts_within_the_debounce_window_are_batched() {
+        let (engine, clock, recorder, host) = engine();
+        let settings = settings(30, &[(AlertKind::WakeFailed, 0), (AlertKind::WakeConfirmed, 0)]);
+        let failed = AlertEvent::WakeFailed("HTTP 503".to_string());
+
+        engine.evaluate(&host, &settings, &slot(1), &failed).await;
+        clock.advance(Duration::from_secs(10));
+        engine.evaluate(&host, &settings, &slot(1), &AlertEvent::WakeConfirmed).await;
+        engine.evaluate(&host, &settings, &slot(2), &failed).await;
+        assert_eq!(recorder.fired.lock().unwrap().len(), 3);
+        assert_eq!(recorder.notified.lock().unwrap().len(), 1);
+
+        // The rest of the window passes and the queued alerts go out together.
+        time::sleep(Duration::from_secs(21)).await;
+        assert_eq!(
+            recorder.notified.lock().unwrap()[1],
+            (
+                "GLM Tray: 2 alerts".to_string(),
+                "Key 1: wake confirmed\nKey 2: wake failed: HTTP 503".to_string()
+            )
+        );
+
+        // A full window after that batch, alerts show right away again.
+        clock.advance(Duration::from_secs(30));
+        engine.evaluate(&host, &settings, &slot(2), &failed).await;
+        assert_eq!(recorder.notified.lock().unwrap().len(), 3);
+    }
+}
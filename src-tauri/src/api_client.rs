@@ -1,13 +1,19 @@
-use chrono::{Local, TimeZone};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use log::{debug, info, warn};
-use reqwest::header::{ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE};
 use std::time::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
-use serde_json::json;
+use serde_json::{json, Value};
 
+use crate::clock::{self, Clock, ClockHandle};
 use crate::file_logger;
-use crate::models::{KeySlotConfig, QuotaApiResponse, QuotaApiResponseFull, QuotaSnapshot,
-    ModelUsageApiResponse, ToolUsageApiResponse, SlotStats, LimitInfo, UsageDetailInfo};
+use crate::host::HostHandle;
+use crate::network;
+use crate::models::{
+    ApiProtocol, KeySlotConfig, NetworkConfig, QuotaSnapshot, RetryPolicy, SlotStats, LimitInfo, UsageDetailInfo,
+};
+use crate::provider::{self, Provider, UsageKind};
+use crate::retry;
+use crate::slot_zone::SlotZone;
 
 static FLOW_SEQUENCE: AtomicU64 = AtomicU64::new(1);
 
@@ -27,7 +33,7 @@ fn mock_base_url(config_mock_url: Option<&str>) -> String {
 }
 
 /// Override URL to mock server if debug mode is enabled
-fn debug_url(url: &str, config_debug: Option<bool>, config_mock_url: Option<&str>) -> String {
+pub(crate) fn debug_url(url: &str, config_debug: Option<bool>, config_mock_url: Option<&str>) -> String {
     if !is_debug_mode(config_debug) {
         return url.to_string();
     }
@@ -35,27 +41,103 @@ fn debug_url(url: &str, config_debug: Option<bool>, config_mock_url: Option<&str
     // Replace the base URL with mock server
     let base = mock_base_url(config_mock_url);
 
-    // Extract the path from the original URL
+    // Extract the path (and query) from the original URL
     if let Ok(parsed) = reqwest::Url::parse(url) {
-        let path = parsed.path();
-        format!("{}{}", base, path)
+        match parsed.query() {
+            Some(query) => format!("{}{}?{}", base, parsed.path(), query),
+            None => format!("{}{}", base, parsed.path()),
+        }
     } else {
         warn!("debug mode: failed to parse URL '{}', using as-is", url);
         url.to_string()
     }
 }
 
+/// Request body for a wake or warmup of `cfg`: its configured model and
+/// messages (placeholders filled in), plus the optional fields, shaped for
+/// the slot's protocol. Anthropic takes system prompts as a top-level
+/// `system` string rather than as messages.
+pub(crate) fn wake_request_body(cfg: &KeySlotConfig, now: DateTime<Local>) -> Value {
+    let wake = &cfg.wake_request;
+    let timestamp = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    let fill = |content: &str| {
+        content
+            .replace("{{slot}}", &cfg.slot.to_string())
+            .replace("{{slot_name}}", &cfg.name)
+            .replace("{{timestamp}}", &timestamp)
+    };
+
+    let mut body = wake.extra.clone();
+    body.insert("model".into(), json!(wake.model));
+    match cfg.protocol {
+        ApiProtocol::OpenAi => {
+            let messages: Vec<Value> = wake
+                .messages
+                .iter()
+                .map(|message| json!({ "role": message.role, "content": fill(&message.content) }))
+                .collect();
+            body.insert("messages".into(), Value::Array(messages));
+        }
+        ApiProtocol::Anthropic => {
+            let (system, turns): (Vec<_>, Vec<_>) = wake.messages.iter().partition(|message| message.role == "system");
+            if !system.is_empty() {
+                let system: Vec<String> = system.iter().map(|message| fill(&message.content)).collect();
+                body.insert("system".into(), json!(system.join("\n\n")));
+            }
+            let messages: Vec<Value> = turns
+                .iter()
+                .map(|message| json!({ "role": message.role, "content": fill(&message.content) }))
+                .collect();
+            body.insert("messages".into(), Value::Array(messages));
+        }
+    }
+    if let Some(max_tokens) = wake.max_tokens {
+        body.insert("max_tokens".into(), json!(max_tokens));
+    }
+    if wake.disable_thinking {
+        body.insert("thinking".into(), json!({ "type": "disabled" }));
+    }
+    Value::Object(body)
+}
+
+/// Tokens a chat reply reports having used: `total_tokens` for OpenAI,
+/// input plus output for Anthropic.
+fn usage_total(protocol: ApiProtocol, usage: &Value) -> Option<u64> {
+    match protocol {
+        ApiProtocol::OpenAi => usage.get("total_tokens").and_then(Value::as_u64),
+        ApiProtocol::Anthropic => {
+            let input = usage.get("input_tokens").and_then(Value::as_u64)?;
+            let output = usage.get("output_tokens").and_then(Value::as_u64).unwrap_or(0);
+            Some(input + output)
+        }
+    }
+}
+
+/// Flow ID tying together the log entries of one request/response exchange.
+pub(crate) fn next_flow_id(clock: &dyn Clock, slot: usize, action: &str) -> String {
+    let seq = FLOW_SEQUENCE.fetch_add(1, Ordering::SeqCst);
+    let ts = clock.now_ms();
+    format!("{ts}_{action}_slot{slot}_{seq}")
+}
+
 #[derive(Clone)]
 pub struct ApiClient {
     client: reqwest::Client,
-    app: Option<tauri::AppHandle>,
+    host: Option<HostHandle>,
     debug: bool,
     mock_url: Option<String>,
+    clock: ClockHandle,
+    retry: RetryPolicy,
 }
 
 impl ApiClient {
-    pub fn new(app: Option<tauri::AppHandle>, debug: bool, mock_url: Option<String>) -> Result<Self, String> {
-        let mut builder = reqwest::Client::builder()
+    pub fn new(
+        host: Option<HostHandle>,
+        debug: bool,
+        mock_url: Option<String>,
+        network: &NetworkConfig,
+    ) -> Result<Self, String> {
+        let mut builder = network::client_builder(network)?
             .connect_timeout(std::time::Duration::from_secs(5))
             .timeout(std::time::Duration::from_secs(15));
 
@@ -74,160 +156,169 @@ impl ApiClient {
             info!("API client initialized in debug mode");
         }
 
-        Ok(Self { client, app, debug, mock_url })
+        Ok(Self {
+            client,
+            host,
+            debug,
+            mock_url,
+            clock: clock::system(),
+            retry: RetryPolicy::default(),
+        })
     }
 
-    fn auth_header(api_key: &str) -> String {
-        if api_key.trim_start().starts_with("Bearer ") {
-            api_key.trim().to_string()
-        } else {
-            format!("Bearer {}", api_key.trim())
+    /// Use `clock` for flow IDs, wake bodies and the time ranges of usage
+    /// queries.
+    pub fn with_clock(mut self, clock: ClockHandle) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Retry failed requests per `retry`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Switch to `retry` for the requests that follow.
+    pub fn set_retry(&mut self, retry: &RetryPolicy) {
+        if self.retry != *retry {
+            self.retry = retry.clone();
         }
     }
 
-    /// Log to JSONL file if logging is enabled and an app handle is available.
+    /// Log to JSONL file if logging is enabled and a host is available.
     async fn log(&self, cfg: &KeySlotConfig, entry: file_logger::LogEntry) {
         if !cfg.logging {
             return;
         }
-        if let Some(app) = &self.app {
-            let _ = file_logger::append(app, entry).await;
+        if let Some(host) = &self.host {
+            let _ = file_logger::append(host, entry).await;
         }
     }
 
     fn next_flow_id(&self, cfg: &KeySlotConfig, action: &str) -> String {
-        let seq = FLOW_SEQUENCE.fetch_add(1, Ordering::SeqCst);
-        let ts = Local::now().timestamp_millis();
-        format!("{ts}_{action}_slot{}_{}", cfg.slot, seq)
+        next_flow_id(&*self.clock, cfg.slot, action)
     }
 
-    pub async fn warmup_key(&self, cfg: &KeySlotConfig) -> Result<(), String> {
-        let Some(original_url) = cfg.request_url.clone() else {
-            return Err("no request URL configured".to_string());
-        };
-
-        // Apply debug URL transformation if enabled
-        let url = debug_url(&original_url, Some(self.debug), self.mock_url.as_deref());
-
-        let body = json!({
-            "model": "glm-5",
-            "messages": [
-                { "role": "system", "content": "You are a helpful assistant." },
-                { "role": "user", "content": "ping" }
-            ]
-        });
-
-        info!("slot {}: sending warmup request to {}", cfg.slot, url);
-        let flow_id = self.next_flow_id(cfg, "manual-warmup");
-        let start = Instant::now();
-
-        let response = match self
-            .client
-            .post(&url)
-            .header(AUTHORIZATION, Self::auth_header(&cfg.api_key))
-            .header(ACCEPT_LANGUAGE, "en-US")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-        {
-            Ok(response) => response,
-            Err(err) => {
-                let msg = format!("warmup request failed: {err}");
-                self.log(
-                    cfg,
-                    file_logger::error_entry_with_id(
-                        cfg.slot,
-                        "manual-warmup",
-                        "POST",
-                        &url,
-                        &msg,
-                        flow_id,
-                    ),
-                )
-                .await;
-                return Err(msg);
+    /// Send `request`, trying again per the retry policy after a transport
+    /// error or a retryable status. A `POST` answered with 429 is not
+    /// retried: the key is rate limited or out of quota (1113/1308), and each
+    /// wake try would count against it. Every retried failure is logged
+    /// under `flow_id`; only the outcome of the last try is returned.
+    async fn send(
+        &self,
+        cfg: &KeySlotConfig,
+        action: &str,
+        method: &str,
+        url: &str,
+        flow_id: &str,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let Some(this_try) = request.try_clone() else {
+                return request.send().await;
+            };
+            let result = this_try.send().await;
+            attempt += 1;
+            if attempt >= self.retry.max_attempts {
+                return result;
             }
-        };
-
-        let status = response.status();
-        let elapsed = start.elapsed().as_millis() as u64;
-        self.log(
-            cfg,
-            file_logger::response_entry_with_timing_and_id(
+            let (status, error, retry_after) = match &result {
+                Ok(response) if !self.retry.retries_status(response.status().as_u16()) => return result,
+                Ok(response) if method == "POST" && response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    return result
+                }
+                Ok(response) => (
+                    Some(response.status().as_u16()),
+                    format!("HTTP error: {}", response.status()),
+                    retry::retry_after(response.headers(), self.clock.now()),
+                ),
+                Err(err) if err.is_builder() => return result,
+                Err(err) => (None, err.to_string(), None),
+            };
+            let Some(delay) = self.retry.delay(attempt, retry_after) else {
+                return result;
+            };
+
+            warn!(
+                "slot {}: {} attempt {}/{} failed ({}), retrying in {} ms",
                 cfg.slot,
-                "manual-warmup",
-                "POST",
-                &url,
-                status.as_u16(),
-                None,
-                elapsed,
-                flow_id.clone(),
-            ),
-        )
-        .await;
-
-        if !status.is_success() {
-            let msg = format!("warmup HTTP error: {}", status);
+                action,
+                attempt,
+                self.retry.max_attempts,
+                error,
+                delay.as_millis()
+            );
             self.log(
                 cfg,
-                file_logger::error_entry_with_id(
+                file_logger::retry_entry(
                     cfg.slot,
-                    "manual-warmup",
-                    "POST",
-                    &url,
-                    &msg,
-                    flow_id,
+                    action,
+                    method,
+                    url,
+                    &error,
+                    json!({
+                        "status": status,
+                        "attempt": attempt,
+                        "max_attempts": self.retry.max_attempts,
+                        "delay_ms": delay.as_millis() as u64,
+                        "retry_after": retry_after.is_some(),
+                    }),
+                    flow_id.to_string(),
                 ),
             )
             .await;
-            return Err(msg);
+            tokio::time::sleep(delay).await;
         }
+    }
 
-        info!("slot {}: warmup request succeeded", cfg.slot);
-        Ok(())
+    pub async fn warmup_key(&self, cfg: &KeySlotConfig) -> Result<(), String> {
+        if cfg.request_url.is_none() {
+            return Err("no request URL configured".to_string());
+        }
+        self.send_chat(cfg, "manual-warmup", "warmup").await
     }
 
     pub async fn send_wake_request(&self, cfg: &KeySlotConfig) -> Result<(), String> {
-        let Some(original_url) = cfg.request_url.clone() else {
+        if cfg.request_url.is_none() {
             return Ok(());
-        };
+        }
+        self.send_chat(cfg, "scheduled-wake", "wake").await
+    }
 
+    /// The slot's wake request, addressed and authorized for its protocol,
+    /// and the URL it goes to.
+    fn chat_request(&self, cfg: &KeySlotConfig) -> (String, reqwest::RequestBuilder) {
+        let provider = provider::for_slot(cfg);
         // Apply debug URL transformation if enabled
-        let url = debug_url(&original_url, Some(self.debug), self.mock_url.as_deref());
-
-        let body = json!({
-            "model": "glm-5",
-            "messages": [
-                { "role": "system", "content": "You are a helpful assistant." },
-                { "role": "user", "content": "ping" }
-            ]
-        });
-
-        info!("slot {}: sending scheduled wake request to {}", cfg.slot, url);
-        let flow_id = self.next_flow_id(cfg, "scheduled-wake");
+        let url = debug_url(&provider.request_url(cfg.protocol), Some(self.debug), self.mock_url.as_deref());
+        let body = wake_request_body(cfg, self.clock.now_local());
+        let request = provider.authorize(self.client.post(&url), &cfg.api_key, cfg.protocol).json(&body);
+        (url, request)
+    }
+
+    /// POST the slot's wake request body and log the tokens it used.
+    async fn send_chat(&self, cfg: &KeySlotConfig, action: &str, kind: &str) -> Result<(), String> {
+        let (url, request) = self.chat_request(cfg);
+
+        info!("slot {}: sending {} request to {}", cfg.slot, kind, url);
+        let flow_id = self.next_flow_id(cfg, action);
         let start = Instant::now();
 
-        let response = match self
-            .client
-            .post(&url)
-            .header(AUTHORIZATION, Self::auth_header(&cfg.api_key))
-            .header(ACCEPT_LANGUAGE, "en-US")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-        {
+        let response = match self.send(cfg, action, "POST", &url, &flow_id, request).await {
             Ok(response) => response,
             Err(err) => {
-                let msg = format!("wake request failed: {err}");
-                let elapsed = start.elapsed().as_millis() as u64;
-                let _ = elapsed;
+                let msg = format!("{kind} request failed: {err}");
                 self.log(
                     cfg,
                     file_logger::error_entry_with_id(
                         cfg.slot,
-                        "scheduled-wake",
+                        action,
                         "POST",
                         &url,
                         &msg,
@@ -245,7 +336,7 @@ impl ApiClient {
             cfg,
             file_logger::response_entry_with_timing_and_id(
                 cfg.slot,
-                "scheduled-wake",
+                action,
                 "POST",
                 &url,
                 status.as_u16(),
@@ -257,12 +348,12 @@ impl ApiClient {
         .await;
 
         if !status.is_success() {
-            let msg = format!("wake HTTP error: {}", status);
+            let msg = format!("{kind} HTTP error: {}", status);
             self.log(
                 cfg,
                 file_logger::error_entry_with_id(
                     cfg.slot,
-                    "scheduled-wake",
+                    action,
                     "POST",
                     &url,
                     &msg,
@@ -273,26 +364,46 @@ impl ApiClient {
             return Err(msg);
         }
 
-        info!("slot {}: wake request succeeded", cfg.slot);
+        let usage = response
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|reply| reply.get("usage").cloned());
+        match usage.as_ref().and_then(|usage| usage_total(cfg.protocol, usage)) {
+            Some(total) => info!(
+                "slot {}: {} request succeeded ({}, {} tokens)",
+                cfg.slot, kind, cfg.wake_request.model, total
+            ),
+            None => info!("slot {}: {} request succeeded", cfg.slot, kind),
+        }
+        self.log(
+            cfg,
+            file_logger::event_entry(
+                cfg.slot,
+                &format!("{action}.usage"),
+                Some(json!({
+                    "flow_id": flow_id,
+                    "model": cfg.wake_request.model,
+                    "usage": usage,
+                })),
+            ),
+        )
+        .await;
         Ok(())
     }
 
     pub async fn fetch_quota(&self, cfg: &KeySlotConfig, caller: &str) -> Result<QuotaSnapshot, String> {
+        let provider = provider::for_slot(cfg);
         // Apply debug URL transformation if enabled
-        let url = debug_url(&cfg.quota_url, Some(self.debug), self.mock_url.as_deref());
+        let url = debug_url(&provider.quota_url(), Some(self.debug), self.mock_url.as_deref());
 
         debug!("slot {}: fetching quota from {} (caller: {})", cfg.slot, url, caller);
         let flow_id = self.next_flow_id(cfg, caller);
         let start = Instant::now();
 
-        let req = self
-            .client
-            .get(&url)
-            .header(AUTHORIZATION, Self::auth_header(&cfg.api_key))
-            .header(ACCEPT_LANGUAGE, "en-US")
-            .header(CONTENT_TYPE, "application/json");
+        let req = provider.authorize(self.client.get(&url), &cfg.api_key, ApiProtocol::OpenAi);
 
-        let response = match req.send().await {
+        let response = match self.send(cfg, caller, "GET", &url, &flow_id, req).await {
             Ok(response) => response,
             Err(err) => {
                 let msg = format!("quota request failed: {err}");
@@ -353,10 +464,9 @@ impl ApiClient {
         )
         .await;
 
-        let payload: QuotaApiResponse = match serde_json::from_str(&raw_text) {
-            Ok(payload) => payload,
-            Err(err) => {
-                let msg = format!("invalid quota JSON response: {err}");
+        let limits = match provider.parse_quota(&raw_text) {
+            Ok(data) => data.limits,
+            Err(msg) => {
                 self.log(
                     cfg,
                     file_logger::error_entry_with_id(
@@ -373,60 +483,6 @@ impl ApiClient {
             }
         };
 
-        if payload.code != 200 {
-            let msg = format!("quota API code {}", payload.code);
-            self.log(
-                cfg,
-                file_logger::error_entry_with_id(
-                    cfg.slot,
-                    caller,
-                    "GET",
-                    &url,
-                    &msg,
-                    flow_id.clone(),
-                ),
-            )
-            .await;
-            return Err(msg);
-        }
-
-        let limits = match payload.data {
-            Some(value) => value.limits,
-            None => {
-                let msg = "quota response missing data".to_string();
-                self.log(
-                    cfg,
-                    file_logger::error_entry_with_id(
-                        cfg.slot,
-                        caller,
-                        "GET",
-                        &url,
-                        &msg,
-                        flow_id.clone(),
-                    ),
-                )
-                .await;
-                return Err(msg);
-            }
-        };
-
-        if limits.is_empty() {
-            let msg = "quota limits missing".to_string();
-            self.log(
-                cfg,
-                file_logger::error_entry_with_id(
-                    cfg.slot,
-                    caller,
-                    "GET",
-                    &url,
-                    &msg,
-                    flow_id.clone(),
-                ),
-            )
-            .await;
-            return Err(msg);
-        }
-
         let selected = limits
             .iter()
             .find(|limit| limit.r#type == "TOKENS_LIMIT")
@@ -436,13 +492,7 @@ impl ApiClient {
         let timer_active = selected.next_reset_time.is_some();
 
         let (hms, epoch) = match selected.next_reset_time {
-            Some(ts) if ts > 0 => {
-                let h = Local
-                    .timestamp_millis_opt(ts)
-                    .single()
-                    .map(|dt| dt.format("%H:%M:%S").to_string());
-                (h, Some(ts))
-            }
+            Some(ts) if ts > 0 => (SlotZone::for_slot(cfg).format(ts, "%H:%M:%S"), Some(ts)),
             _ => (None, None),
         };
 
@@ -469,146 +519,53 @@ impl ApiClient {
         cfg: &KeySlotConfig,
         next_reset_epoch_ms: Option<i64>,
     ) -> (u64, u64) {
-        let auth = Self::auth_header(&cfg.api_key);
-        let base_url = debug_url(&cfg.quota_url, Some(self.debug), self.mock_url.as_deref());
-        let base = base_url.trim_end_matches("/quota/limit");
-        let now = Local::now();
-
-        let (start_5h, end_5h) = if let Some(reset_ts) = next_reset_epoch_ms {
-            if reset_ts > 0 {
-                let reset_dt = Local.timestamp_millis_opt(reset_ts).single().unwrap_or(now);
-                let start = (reset_dt - chrono::Duration::hours(5))
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string();
-                let end = reset_dt.format("%Y-%m-%d %H:%M:%S").to_string();
-                (start, end)
-            } else {
-                (
-                    (now - chrono::Duration::hours(5))
-                        .format("%Y-%m-%d %H:%M:%S")
-                        .to_string(),
-                    now.format("%Y-%m-%d %H:%M:%S").to_string(),
-                )
-            }
-        } else {
-            (
-                (now - chrono::Duration::hours(5))
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string(),
-                now.format("%Y-%m-%d %H:%M:%S").to_string(),
-            )
-        };
-
-        let url = format!(
-            "{}/model-usage?startTime={}&endTime={}",
-            base,
-            urlencoding::encode(&start_5h),
-            urlencoding::encode(&end_5h)
+        let provider = provider::for_slot(cfg);
+        let (start_5h, end_5h) = window_5h(self.clock.now(), next_reset_epoch_ms);
+        let url = debug_url(
+            &provider.usage_url(UsageKind::Model, start_5h, end_5h),
+            Some(self.debug),
+            self.mock_url.as_deref(),
         );
 
-        match self
-            .client
-            .get(&url)
-            .header(AUTHORIZATION, auth)
-            .header(ACCEPT_LANGUAGE, "en-US")
-            .header(CONTENT_TYPE, "application/json")
-            .send()
+        self.fetch_usage(cfg, provider.as_ref(), "poll-model-usage-5h", &url)
             .await
-        {
-            Ok(resp) if resp.status().is_success() => {
-                let status = resp.status().as_u16();
-                let text = resp.text().await.unwrap_or_default();
-                let resp_json: Option<serde_json::Value> =
-                    serde_json::from_str(&text).ok();
-                self.log(
-                    cfg,
-                    file_logger::response_entry(
-                        cfg.slot,
-                        "poll-model-usage-5h",
-                        "GET",
-                        &url,
-                        status,
-                        resp_json,
-                    ),
-                )
-                .await;
-                let parsed: Result<ModelUsageApiResponse, _> =
-                    serde_json::from_str(&text);
-                match parsed {
-                    Ok(r) if r.code == 200 => {
-                        let t = r.data.and_then(|d| d.total_usage);
-                        (
-                            t.as_ref().map_or(0, |u| u.total_model_call_count),
-                            t.as_ref().map_or(0, |u| u.total_tokens_usage),
-                        )
-                    }
-                    _ => (0, 0),
-                }
-            }
-            Ok(resp) => {
-                let msg = format!("poll-model-usage-5h HTTP error: {}", resp.status());
-                self.log(
-                    cfg,
-                    file_logger::error_entry(
-                        cfg.slot,
-                        "poll-model-usage-5h",
-                        "GET",
-                        &url,
-                        &msg,
-                    ),
-                )
-                .await;
-                (0, 0)
-            }
-            Err(e) => {
-                self.log(
-                    cfg,
-                    file_logger::error_entry(
-                        cfg.slot,
-                        "poll-model-usage-5h",
-                        "GET",
-                        &url,
-                        &e.to_string(),
-                    ),
-                )
-                .await;
-                (0, 0)
-            }
-        }
+            .and_then(|text| provider.parse_model_usage(&text))
+            .map_or((0, 0), |u| (u.total_model_call_count, u.total_tokens_usage))
     }
 
     pub async fn fetch_slot_stats(&self, cfg: &KeySlotConfig) -> Result<SlotStats, String> {
-        let auth = Self::auth_header(&cfg.api_key);
+        let provider = provider::for_slot(cfg);
 
         // Apply debug URL transformation if enabled
-        let quota_url = debug_url(&cfg.quota_url, Some(self.debug), self.mock_url.as_deref());
+        let quota_url = debug_url(&provider.quota_url(), Some(self.debug), self.mock_url.as_deref());
 
         // 1. Fetch full quota/limit
 
+        let action = "manual-stats-request";
+        let flow_id = self.next_flow_id(cfg, action);
+        let start = Instant::now();
+        let request = provider.authorize(self.client.get(&quota_url), &cfg.api_key, ApiProtocol::OpenAi);
         let quota_resp = self
-            .client
-            .get(&quota_url)
-            .header(AUTHORIZATION, auth.clone())
-            .header(ACCEPT_LANGUAGE, "en-US")
-            .header(CONTENT_TYPE, "application/json")
-            .send()
+            .send(cfg, action, "GET", &quota_url, &flow_id, request)
             .await
             .map_err(|e| format!("quota request failed: {e}"))?;
 
         if !quota_resp.status().is_success() {
             let msg = format!("quota HTTP error: {}", quota_resp.status());
-            self.log(cfg, file_logger::error_entry(cfg.slot, "manual-stats-request", "GET", &quota_url, &msg)).await;
+            self.log(cfg, file_logger::error_entry_with_id(cfg.slot, action, "GET", &quota_url, &msg, flow_id)).await;
             return Err(msg);
         }
 
         let quota_text = quota_resp.text().await.map_err(|e| format!("read quota: {e}"))?;
         let resp_json: Option<serde_json::Value> = serde_json::from_str(&quota_text).ok();
-        self.log(cfg, file_logger::response_entry(cfg.slot, "manual-stats-request", "GET", &quota_url, 200, resp_json)).await;
-        let quota_parsed: QuotaApiResponseFull =
-            serde_json::from_str(&quota_text).map_err(|e| format!("parse quota: {e}"))?;
-
-        let quota_data = quota_parsed.data.ok_or("quota missing data")?;
-        let level = quota_data.level.unwrap_or_else(|| "unknown".into());
+        let elapsed = start.elapsed().as_millis() as u64;
+        self.log(
+            cfg,
+            file_logger::response_entry_with_timing_and_id(cfg.slot, action, "GET", &quota_url, 200, resp_json, elapsed, flow_id),
+        )
+        .await;
+        let quota_data = provider.parse_quota(&quota_text)?;
+        let level = quota_data.level.clone().unwrap_or_else(|| "unknown".into());
 
         let limits: Vec<LimitInfo> = quota_data
             .limits
@@ -619,7 +576,7 @@ impl ApiClient {
                 // 4=days, 5=months, 6=years → "Jan 31" date format
                 let reset_display = l.next_reset_time.and_then(|ts| {
                     if ts > 0 {
-                        Local.timestamp_millis_opt(ts).single().map(|dt| {
+                        SlotZone::for_slot(cfg).at(ts).map(|dt| {
                             let unit = l.unit.unwrap_or(3);
                             if unit <= 3 {
                                 // Hours or less → show time
@@ -650,145 +607,51 @@ impl ApiClient {
             })
             .collect();
 
-        // 2. Derive base URL for model-usage / tool-usage and calculate time ranges
-        let base = quota_url.trim_end_matches("/quota/limit");
-        let now = Local::now();
-        let start_24h = (now - chrono::Duration::hours(24)).format("%Y-%m-%d %H:%M:%S").to_string();
-        let end = now.format("%Y-%m-%d %H:%M:%S").to_string();
+        // 2. Calculate time ranges for model-usage / tool-usage
+        let now = self.clock.now();
+        let start_24h = now - chrono::Duration::hours(24);
 
         // For 5h window, use the TOKENS_LIMIT reset time if available
         // The 5h window is the 5 hours leading up to the next reset
         let tokens_limit = quota_data.limits.iter().find(|l| l.r#type == "TOKENS_LIMIT");
-        let reset_time = tokens_limit.and_then(|l| l.next_reset_time);
-        let (start_5h, end_5h) = if let Some(reset_ts) = reset_time {
-            if reset_ts > 0 {
-                let reset_dt = Local.timestamp_millis_opt(reset_ts).single().unwrap_or(now);
-                let start = (reset_dt - chrono::Duration::hours(5)).format("%Y-%m-%d %H:%M:%S").to_string();
-                let end = reset_dt.format("%Y-%m-%d %H:%M:%S").to_string();
-                (start, end)
-            } else {
-                ((now - chrono::Duration::hours(5)).format("%Y-%m-%d %H:%M:%S").to_string(),
-                 now.format("%Y-%m-%d %H:%M:%S").to_string())
-            }
-        } else {
-            ((now - chrono::Duration::hours(5)).format("%Y-%m-%d %H:%M:%S").to_string(),
-             now.format("%Y-%m-%d %H:%M:%S").to_string())
-        };
+        let (start_5h, end_5h) = window_5h(now, tokens_limit.and_then(|l| l.next_reset_time));
 
         // 3. Fetch model-usage for 24h window (best effort)
         let (total_model_calls_24h, total_tokens_24h) = {
-            let url = format!("{}/model-usage?startTime={}&endTime={}", base,
-                urlencoding::encode(&start_24h), urlencoding::encode(&end));
-
-            match self.client.get(&url)
-                .header(AUTHORIZATION, auth.clone())
-                .header(ACCEPT_LANGUAGE, "en-US")
-                .header(CONTENT_TYPE, "application/json")
-                .send().await
-            {
-                Ok(resp) if resp.status().is_success() => {
-                    let status = resp.status().as_u16();
-                    let text = resp.text().await.unwrap_or_default();
-                    let resp_json: Option<serde_json::Value> = serde_json::from_str(&text).ok();
-                    self.log(cfg, file_logger::response_entry(cfg.slot, "manual-model-usage-24h", "GET", &url, status, resp_json)).await;
-                    let parsed: Result<ModelUsageApiResponse, _> = serde_json::from_str(&text);
-                    match parsed {
-                        Ok(r) if r.code == 200 => {
-                            let t = r.data.and_then(|d| d.total_usage);
-                            (t.as_ref().map_or(0, |u| u.total_model_call_count),
-                             t.as_ref().map_or(0, |u| u.total_tokens_usage))
-                        }
-                        _ => (0, 0),
-                    }
-                }
-                Ok(resp) => {
-                    let msg = format!("model-usage-24h HTTP error: {}", resp.status());
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-model-usage-24h", "GET", &url, &msg)).await;
-                    (0, 0)
-                }
-                Err(e) => {
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-model-usage-24h", "GET", &url, &e.to_string())).await;
-                    (0, 0)
-                }
-            }
+            let url = debug_url(&provider.usage_url(UsageKind::Model, start_24h, now),
+                Some(self.debug), self.mock_url.as_deref());
+
+            self.fetch_usage(cfg, provider.as_ref(), "manual-model-usage-24h", &url)
+                .await
+                .and_then(|text| provider.parse_model_usage(&text))
+                .map_or((0, 0), |u| (u.total_model_call_count, u.total_tokens_usage))
         };
 
         // 3b. Fetch model-usage for 5h window (best effort)
         let (total_model_calls_5h, total_tokens_5h) = {
-            let url = format!("{}/model-usage?startTime={}&endTime={}", base,
-                urlencoding::encode(&start_5h), urlencoding::encode(&end_5h));
-
-            match self.client.get(&url)
-                .header(AUTHORIZATION, auth.clone())
-                .header(ACCEPT_LANGUAGE, "en-US")
-                .header(CONTENT_TYPE, "application/json")
-                .send().await
-            {
-                Ok(resp) if resp.status().is_success() => {
-                    let status = resp.status().as_u16();
-                    let text = resp.text().await.unwrap_or_default();
-                    let resp_json: Option<serde_json::Value> = serde_json::from_str(&text).ok();
-                    self.log(cfg, file_logger::response_entry(cfg.slot, "manual-model-usage-5h", "GET", &url, status, resp_json)).await;
-                    let parsed: Result<ModelUsageApiResponse, _> = serde_json::from_str(&text);
-                    match parsed {
-                        Ok(r) if r.code == 200 => {
-                            let t = r.data.and_then(|d| d.total_usage);
-                            (t.as_ref().map_or(0, |u| u.total_model_call_count),
-                             t.as_ref().map_or(0, |u| u.total_tokens_usage))
-                        }
-                        _ => (0, 0),
-                    }
-                }
-                Ok(resp) => {
-                    let msg = format!("model-usage-5h HTTP error: {}", resp.status());
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-model-usage-5h", "GET", &url, &msg)).await;
-                    (0, 0)
-                }
-                Err(e) => {
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-model-usage-5h", "GET", &url, &e.to_string())).await;
-                    (0, 0)
-                }
-            }
+            let url = debug_url(&provider.usage_url(UsageKind::Model, start_5h, end_5h),
+                Some(self.debug), self.mock_url.as_deref());
+
+            self.fetch_usage(cfg, provider.as_ref(), "manual-model-usage-5h", &url)
+                .await
+                .and_then(|text| provider.parse_model_usage(&text))
+                .map_or((0, 0), |u| (u.total_model_call_count, u.total_tokens_usage))
         };
 
         // 4. Fetch tool-usage (best effort)
         let (net_search, web_read, zread, search_mcp) = {
-            let url = format!("{}/tool-usage?startTime={}&endTime={}", base,
-                urlencoding::encode(&start_24h), urlencoding::encode(&end));
-
-            match self.client.get(&url)
-                .header(AUTHORIZATION, auth.clone())
-                .header(ACCEPT_LANGUAGE, "en-US")
-                .header(CONTENT_TYPE, "application/json")
-                .send().await
-            {
-                Ok(resp) if resp.status().is_success() => {
-                    let status = resp.status().as_u16();
-                    let text = resp.text().await.unwrap_or_default();
-                    let resp_json: Option<serde_json::Value> = serde_json::from_str(&text).ok();
-                    self.log(cfg, file_logger::response_entry(cfg.slot, "manual-tool-usage", "GET", &url, status, resp_json)).await;
-                    let parsed: Result<ToolUsageApiResponse, _> = serde_json::from_str(&text);
-                    match parsed {
-                        Ok(r) if r.code == 200 => {
-                            let t = r.data.and_then(|d| d.total_usage);
-                            (t.as_ref().map_or(0, |u| u.total_network_search_count),
-                             t.as_ref().map_or(0, |u| u.total_web_read_mcp_count),
-                             t.as_ref().map_or(0, |u| u.total_zread_mcp_count),
-                             t.as_ref().map_or(0, |u| u.total_search_mcp_count))
-                        }
-                        _ => (0, 0, 0, 0),
-                    }
-                }
-                Ok(resp) => {
-                    let msg = format!("tool-usage HTTP error: {}", resp.status());
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-tool-usage", "GET", &url, &msg)).await;
-                    (0, 0, 0, 0)
-                }
-                Err(e) => {
-                    self.log(cfg, file_logger::error_entry(cfg.slot, "manual-tool-usage", "GET", &url, &e.to_string())).await;
-                    (0, 0, 0, 0)
-                }
-            }
+            let url = debug_url(&provider.usage_url(UsageKind::Tool, start_24h, now),
+                Some(self.debug), self.mock_url.as_deref());
+
+            self.fetch_usage(cfg, provider.as_ref(), "manual-tool-usage", &url)
+                .await
+                .and_then(|text| provider.parse_tool_usage(&text))
+                .map_or((0, 0, 0, 0), |u| {
+                    (u.total_network_search_count,
+                     u.total_web_read_mcp_count,
+                     u.total_zread_mcp_count,
+                     u.total_search_mcp_count)
+                })
         };
 
         Ok(SlotStats {
@@ -804,4 +667,157 @@ impl ApiClient {
             total_search_mcp_24h: search_mcp,
         })
     }
+
+    /// GET a best-effort usage endpoint and log the exchange; the body when
+    /// the request succeeded.
+    async fn fetch_usage(&self, cfg: &KeySlotConfig, provider: &dyn Provider, action: &str, url: &str) -> Option<String> {
+        let flow_id = self.next_flow_id(cfg, action);
+        let start = Instant::now();
+        let request = provider.authorize(self.client.get(url), &cfg.api_key, ApiProtocol::OpenAi);
+        match self.send(cfg, action, "GET", url, &flow_id, request).await {
+            Ok(resp) if resp.status().is_success() => {
+                let status = resp.status().as_u16();
+                let text = resp.text().await.unwrap_or_default();
+                let resp_json: Option<serde_json::Value> = serde_json::from_str(&text).ok();
+                let elapsed = start.elapsed().as_millis() as u64;
+                self.log(
+                    cfg,
+                    file_logger::response_entry_with_timing_and_id(cfg.slot, action, "GET", url, status, resp_json, elapsed, flow_id),
+                )
+                .await;
+                Some(text)
+            }
+            Ok(resp) => {
+                let msg = format!("{action} HTTP error: {}", resp.status());
+                self.log(cfg, file_logger::error_entry_with_id(cfg.slot, action, "GET", url, &msg, flow_id)).await;
+                None
+            }
+            Err(e) => {
+                self.log(cfg, file_logger::error_entry_with_id(cfg.slot, action, "GET", url, &e.to_string(), flow_id)).await;
+                None
+            }
+        }
+    }
+}
+
+/// The 5 hours leading up to the next reset, or the last 5 hours when the
+/// reset time is unknown.
+fn window_5h(now: DateTime<Utc>, next_reset_epoch_ms: Option<i64>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let end = next_reset_epoch_ms
+        .filter(|ts| *ts > 0)
+        .and_then(|ts| Utc.timestamp_millis_opt(ts).single())
+        .unwrap_or(now);
+    (end - chrono::Duration::hours(5), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FakeClock};
+    use crate::models::{ProviderKind, WakeMessage, WakeRequestConfig};
+    use crate::provider::{ANTHROPIC_MESSAGES_PATH, ANTHROPIC_VERSION, OPENAI_CHAT_PATH};
+
+    fn message(role: &str, content: &str) -> WakeMessage {
+        WakeMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn slot(protocol: ApiProtocol) -> KeySlotConfig {
+        let mut extra = serde_json::Map::new();
+        extra.insert("temperature".into(), json!(0.2));
+        KeySlotConfig {
+            slot: 3,
+            name: "Work".to_string(),
+            api_key: "Bearer sk-test".to_string(),
+            provider: ProviderKind::Zai,
+            protocol,
+            wake_request: WakeRequestConfig {
+                model: "glm-4.5-air".to_string(),
+                messages: vec![
+                    message("system", "Keep it short."),
+                    message("system", "Slot {{slot}}."),
+                    message("user", "ping from {{slot_name}} at {{timestamp}}"),
+                ],
+                max_tokens: Some(16),
+                disable_thinking: true,
+                extra,
+            },
+            ..KeySlotConfig::default()
+        }
+    }
+
+    /// The wake request `cfg` sends at 2026-03-02 08:00 UTC, and that time
+    /// as it appears in `{{timestamp}}`.
+    fn built(cfg: &KeySlotConfig) -> (reqwest::Request, Value, String) {
+        let clock = FakeClock::new(Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap());
+        let timestamp = clock.now_local().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        let client = ApiClient::new(None, false, None, &NetworkConfig::default())
+            .unwrap()
+            .with_clock(clock);
+        let request = client.chat_request(cfg).1.build().unwrap();
+        let body = serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        (request, body, timestamp)
+    }
+
+    fn header<'a>(request: &'a reqwest::Request, name: &str) -> Option<&'a str> {
+        request.headers().get(name).and_then(|value| value.to_str().ok())
+    }
+
+    #[test]
+    fn openai_wake_sends_messages_with_bearer_auth() {
+        let (request, body, timestamp) = built(&slot(ApiProtocol::OpenAi));
+        assert!(request.url().as_str().ends_with(OPENAI_CHAT_PATH));
+        assert_eq!(header(&request, "authorization"), Some("Bearer sk-test"));
+        assert_eq!(header(&request, "x-api-key"), None);
+        assert_eq!(header(&request, "content-type"), Some("application/json"));
+        assert_eq!(
+            body,
+            json!({
+                "model": "glm-4.5-air",
+                "messages": [
+                    { "role": "system", "content": "Keep it short." },
+                    { "role": "system", "content": "Slot 3." },
+                    { "role": "user", "content": format!("ping from Work at {timestamp}") },
+                ],
+                "max_tokens": 16,
+                "thinking": { "type": "disabled" },
+                "temperature": 0.2,
+            })
+        );
+    }
+
+    #[test]
+    fn anthropic_wake_lifts_system_prompts_and_uses_api_key_headers() {
+        let (request, body, timestamp) = built(&slot(ApiProtocol::Anthropic));
+        assert!(request.url().as_str().ends_with(ANTHROPIC_MESSAGES_PATH));
+        assert_eq!(header(&request, "x-api-key"), Some("sk-test"));
+        assert_eq!(header(&request, "anthropic-version"), Some(ANTHROPIC_VERSION));
+        assert_eq!(header(&request, "authorization"), None);
+        assert_eq!(
+            body,
+            json!({
+                "model": "glm-4.5-air",
+                "system": "Keep it short.\n\nSlot 3.",
+                "messages": [{ "role": "user", "content": format!("ping from Work at {timestamp}") }],
+                "max_tokens": 16,
+                "thinking": { "type": "disabled" },
+                "temperature": 0.2,
+            })
+        );
+    }
+
+    #[test]
+    fn extra_fields_never_replace_the_wake_fields() {
+        let mut cfg = slot(ApiProtocol::OpenAi);
+        cfg.wake_request.disable_thinking = false;
+        cfg.wake_request.max_tokens = None;
+        cfg.wake_request.extra.insert("model".into(), json!("other-model"));
+        cfg.wake_request.extra.insert("messages".into(), json!([]));
+        let (_, body, _) = built(&cfg);
+        assert_eq!(body["model"], "glm-4.5-air");
+        assert_eq!(body["messages"].as_array().map(Vec::len), Some(3));
+        assert!(body.get("thinking").is_none() && body.get("max_tokens").is_none());
+    }
 }
@@ -0,0 +1,3 @@
+fn main() {
+    glm_tray_lib::daemon::run()
+}
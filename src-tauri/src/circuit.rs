@@ -0,0 +1,74 @@
+//! Circuit breaker transitions for a slot's quota and wake endpoints.
+//! `max_consecutive_errors` failures in a row open the circuit; once
+//! `circuit_probe_minutes` pass, one probe goes out half-open and either
+//! closes it or opens it again for another interval.
+
+use crate::models::{CircuitState, CircuitStatus};
+
+const MINUTE_MS: i64 = 60_000;
+
+impl CircuitStatus {
+    pub fn is_closed(&self) -> bool {
+        self.state == CircuitState::Closed
+    }
+
+    /// Open the circuit, or reopen it after a failed probe, with the next
+    /// probe `probe_minutes` from `now_ms`.
+    pub fn open(&mut self, now_ms: i64, probe_minutes: u64) {
+        match self.state {
+            CircuitState::Closed => {
+                self.opened_epoch_ms = Some(now_ms);
+                self.failed_probes = 0;
+            }
+            CircuitState::HalfOpen => self.failed_probes = self.failed_probes.saturating_add(1),
+            CircuitState::Open => {}
+        }
+        self.state = CircuitState::Open;
+        let probe_ms = i64::try_from(probe_minutes.max(1)).unwrap_or(i64::MAX / MINUTE_MS) * MINUTE_MS;
+        self.next_probe_epoch_ms = Some(now_ms.saturating_add(probe_ms));
+    }
+
+    /// Whether an open circuit is due for its probe at `now_ms`.
+    pub fn probe_due(&self, now_ms: i64) -> bool {
+        self.state == CircuitState::Open && self.next_probe_epoch_ms.is_none_or(|at| at <= now_ms)
+    }
+
+    /// Let one probe through.
+    pub fn half_open(&mut self) {
+        self.state = CircuitState::HalfOpen;
+        self.next_probe_epoch_ms = None;
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_reopen_until_one_succeeds() {
+        let mut circuit = CircuitStatus::default();
+        assert!(circuit.is_closed() && !circuit.probe_due(0));
+
+        circuit.open(1_000, 15);
+        assert_eq!(circuit.state, CircuitState::Open);
+        assert_eq!(circuit.next_probe_epoch_ms, Some(1_000 + 15 * MINUTE_MS));
+        assert!(!circuit.probe_due(1_000 + 15 * MINUTE_MS - 1));
+        assert!(circuit.probe_due(1_000 + 15 * MINUTE_MS));
+
+        circuit.half_open();
+        assert!(!circuit.probe_due(i64::MAX));
+        circuit.open(2_000_000, 15);
+        assert_eq!(
+            (circuit.opened_epoch_ms, circuit.failed_probes, circuit.next_probe_epoch_ms),
+            (Some(1_000), 1, Some(2_000_000 + 15 * MINUTE_MS))
+        );
+
+        circuit.half_open();
+        circuit.close();
+        assert_eq!(circuit, CircuitStatus::default());
+    }
+}
@@ -0,0 +1,578 @@
+//! `glm-tray <command>`: query and control a running tray app or `glm-trayd`
+//! over the control socket (see `ipc`).
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::cron_schedule::CronFireTime;
+use crate::file_logger::{LogEntry, LogPage, LogQuery};
+use crate::history::{ResetCycle, UsageSample};
+use crate::host;
+use crate::ipc::{self, Request};
+use crate::models::{CircuitState, CircuitStatus, RuntimeStatus, SlotStats};
+use crate::simulation::{self, QuotaStep, SimulationInput, SimulationReport};
+
+const USAGE: &str = "\
+Usage: glm-tray [--config-dir <DIR>] <command>
+
+Commands:
+  status [--json]          Monitoring state and per-slot quota
+  start                    Start monitoring
+  stop                     Stop monitoring
+  warmup <slot> | --all    Send a warmup request now
+  stats <slot> [--json]    Fetch detailed quota and usage stats
+  logs tail [-n <N>]       Print the newest JSONL log entries (default 20)
+  logs query [--slot <N>] [--hours <N>] [--action <A>] [--phase <P>]
+             [--flow <ID>] [--limit <N>] [--offset <N>] [--json]
+                           Search the logs, newest first (default: last
+                           24 hours, 100 entries); --action takes a name
+                           or a prefix such as 'wake.*'
+  logs follow [--slot <N>] [--action <A>] [--phase <P>] [--flow <ID>] [--json]
+                           Print matching entries as they are written
+  history <slot> [--hours <N>] [--bucket <MIN>] [--json]
+                           Recorded usage samples (default: last 24 hours)
+  cycles <slot> [--days <N>] [--json]
+                           Past quota windows (default: last 7 days)
+  cron-preview <slot> | <expr>... [--count <N>] [--json]
+                           Next fire times of a slot's cron schedule or of
+                           the given expressions (default: 10)
+  simulate <slot> [--hours <N>] [--quota <FILE>] [--json]
+                           Dry-run the slot's schedule without sending
+                           requests (default: 24 hours); FILE is a JSON
+                           array of quota steps
+
+Without a command, the tray app starts.";
+
+const COMMANDS: &[&str] = &[
+    "status", "start", "stop", "warmup", "stats", "logs", "history", "cycles", "cron-preview",
+    "simulate", "help",
+];
+
+const DEFAULT_TAIL_LINES: usize = 20;
+
+const HOUR_MS: i64 = 3_600_000;
+
+const DEFAULT_CRON_PREVIEW: u64 = 10;
+
+struct Invocation {
+    config_dir: Option<PathBuf>,
+    request: Request,
+    json: bool,
+}
+
+/// Whether these arguments (without the program name) are meant for the CLI
+/// rather than for launching the tray app.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.first().is_some_and(|first| {
+        COMMANDS.contains(&first.as_str()) || matches!(first.as_str(), "-h" | "--help" | "--config-dir")
+    })
+}
+
+/// Run one CLI command and return the process exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    let invocation = match parse_args(args) {
+        Ok(Some(invocation)) => invocation,
+        Ok(None) => {
+            println!("{USAGE}");
+            return 0;
+        }
+        Err(err) => {
+            eprintln!("glm-tray: {err}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    let config_dir = match invocation.config_dir.map_or_else(host::default_config_dir, Ok) {
+        Ok(dir) => dir,
+        Err(err) => {
+            eprintln!("glm-tray: {err}");
+            return 1;
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("glm-tray: failed to start async runtime: {err}");
+            return 1;
+        }
+    };
+
+    let result = match &invocation.request {
+        Request::LogsFollow(_) => runtime
+            .block_on(ipc::follow(&config_dir, &invocation.request, |entry| {
+                print_log_entry(&entry, invocation.json)
+            })),
+        request => runtime
+            .block_on(ipc::request(&config_dir, request))
+            .and_then(|data| print_response(request, data, invocation.json)),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("glm-tray: {err}");
+            1
+        }
+    }
+}
+
+/// Returns `Ok(None)` when help was requested.
+fn parse_args(args: Vec<String>) -> Result<Option<Invocation>, String> {
+    let mut config_dir = None;
+    let mut json = false;
+    let mut positional = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" | "help" => return Ok(None),
+            "--config-dir" => {
+                let dir = args.next().ok_or("--config-dir needs a value")?;
+                config_dir = Some(PathBuf::from(dir));
+            }
+            "--json" => json = true,
+            "-n" | "--lines" | "--hours" | "--bucket" | "--days" | "--count" | "--quota" | "--slot" | "--action"
+            | "--phase" | "--flow" | "--limit" | "--offset" | "--all" => {
+                positional.push(arg.clone());
+                if arg != "--all" {
+                    positional.push(args.next().ok_or_else(|| format!("{arg} needs a value"))?);
+                }
+            }
+            other if other.starts_with('-') => return Err(format!("unknown option '{other}'")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+    let request = match positional.as_slice() {
+        [] => return Ok(None),
+        ["status"] => Request::Status,
+        ["start"] => Request::Start,
+        ["stop"] => Request::Stop,
+        ["warmup", "--all"] => Request::WarmupAll,
+        ["warmup", slot] => Request::Warmup { slot: parse_slot(slot)? },
+        ["stats", slot] => Request::Stats { slot: parse_slot(slot)? },
+        ["logs", "tail"] => Request::LogsTail { lines: DEFAULT_TAIL_LINES },
+        ["logs", "tail", "-n" | "--lines", n] => Request::LogsTail {
+            lines: n.parse().map_err(|_| format!("invalid line count '{n}'"))?,
+        },
+        ["logs", "query", opts @ ..] => {
+            let hours = option_value(opts, "--hours")?.unwrap_or(24);
+            Request::LogsQuery(LogQuery {
+                from_ms: Some(ago_ms("--hours", hours, HOUR_MS)?),
+                offset: option_value(opts, "--offset")?.unwrap_or(0) as usize,
+                limit: option_value(opts, "--limit")?.map(|limit| limit as usize),
+                ..log_filters(opts)?
+            })
+        }
+        ["logs", "follow", opts @ ..] => {
+            if let Some(flag) = ["--hours", "--limit", "--offset"].into_iter().find(|flag| opts.contains(flag)) {
+                return Err(format!("{flag} does not apply to 'logs follow'"));
+            }
+            Request::LogsFollow(log_filters(opts)?)
+        }
+        ["history", slot, opts @ ..] => {
+            let hours = option_value(opts, "--hours")?.unwrap_or(24);
+            Request::History {
+                slot: parse_slot(slot)?,
+                from_ms: Some(ago_ms("--hours", hours, HOUR_MS)?),
+                to_ms: None,
+                bucket_minutes: option_value(opts, "--bucket")?,
+            }
+        }
+        ["cycles", slot, opts @ ..] => {
+            let days = option_value(opts, "--days")?.unwrap_or(7);
+            Request::ResetCycles {
+                slot: parse_slot(slot)?,
+                from_ms: Some(ago_ms("--days", days, 24 * HOUR_MS)?),
+            }
+        }
+        ["cron-preview", args @ ..] if !args.is_empty() => {
+            let split = args.iter().position(|arg| arg.starts_with("--")).unwrap_or(args.len());
+            let (targets, opts) = args.split_at(split);
+            let count = option_value(opts, "--count")?.unwrap_or(DEFAULT_CRON_PREVIEW) as usize;
+            match targets {
+                [slot] if slot.parse::<usize>().is_ok() => Request::CronPreview {
+                    slot: Some(parse_slot(slot)?),
+                    expressions: Vec::new(),
+                    timezone: None,
+                    count,
+                },
+                [] => return Err("cron-preview needs a slot id or cron expressions".into()),
+                exprs => Request::CronPreview {
+                    slot: None,
+                    expressions: exprs.iter().map(|expr| expr.to_string()).collect(),
+                    timezone: None,
+                    count,
+                },
+            }
+        }
+        ["simulate", slot, opts @ ..] => {
+            let hours = option_value(opts, "--hours")?.unwrap_or(u64::from(simulation::DEFAULT_HOURS));
+            let quota = match option_text(opts, "--quota") {
+                Some(path) => read_quota_steps(path)?,
+                None => Vec::new(),
+            };
+            Request::Simulate {
+                slot: Some(parse_slot(slot)?),
+                config: None,
+                input: SimulationInput {
+                    hours: u32::try_from(hours).unwrap_or(u32::MAX),
+                    quota,
+                    ..SimulationInput::default()
+                },
+            }
+        }
+        ["cron-preview"] => return Err("cron-preview needs a slot id or cron expressions".into()),
+        ["warmup"] | ["stats"] | ["history"] | ["cycles"] | ["simulate"] => {
+            return Err(format!("{} needs a slot id", positional[0]))
+        }
+        ["logs", ..] => return Err("expected 'logs tail', 'logs query' or 'logs follow'".into()),
+        [command, ..] if !COMMANDS.contains(command) => return Err(format!("unknown command '{command}'")),
+        [command, ..] => return Err(format!("unexpected arguments for '{command}'")),
+    };
+
+    Ok(Some(Invocation { config_dir, request, json }))
+}
+
+/// Slot, action, phase and flow filters shared by `logs query` and `logs follow`.
+fn log_filters(opts: &[&str]) -> Result<LogQuery, String> {
+    Ok(LogQuery {
+        slot: option_text(opts, "--slot").map(parse_slot).transpose()?,
+        action: option_text(opts, "--action").map(str::to_string),
+        phase: option_text(opts, "--phase").map(str::to_string),
+        flow_id: option_text(opts, "--flow").map(str::to_string),
+        ..LogQuery::default()
+    })
+}
+
+fn parse_slot(raw: &str) -> Result<usize, String> {
+    raw.parse().map_err(|_| format!("invalid slot id '{raw}'"))
+}
+
+/// Value of `--flag <N>` among trailing options; any other token is an error.
+fn option_value(opts: &[&str], flag: &str) -> Result<Option<u64>, String> {
+    let mut value = None;
+    for pair in opts.chunks(2) {
+        match pair {
+            [name, raw] if *name == flag => {
+                value = Some(raw.parse().map_err(|_| format!("invalid value '{raw}' for {flag}"))?);
+            }
+            [name, _] if name.starts_with("--") => {}
+            _ => return Err(format!("unexpected argument '{}'", pair[0])),
+        }
+    }
+    Ok(value)
+}
+
+/// Epoch ms `amount` units of `unit_ms` ago, for `--hours`/`--days`.
+fn ago_ms(flag: &str, amount: u64, unit_ms: i64) -> Result<i64, String> {
+    i64::try_from(amount)
+        .ok()
+        .and_then(|amount| amount.checked_mul(unit_ms))
+        .and_then(|span| now_ms().checked_sub(span))
+        .ok_or_else(|| format!("value {amount} for {flag} is too large"))
+}
+
+/// Raw value of `--flag <VALUE>` among trailing options.
+fn option_text<'a>(opts: &[&'a str], flag: &str) -> Option<&'a str> {
+    opts.chunks(2).find_map(|pair| match pair {
+        [name, value] if *name == flag => Some(*value),
+        _ => None,
+    })
+}
+
+fn read_quota_steps(path: &str) -> Result<Vec<QuotaStep>, String> {
+    let raw = std::fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+    serde_json::from_str(&raw).map_err(|err| format!("invalid quota steps in {path}: {err}"))
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn print_response(request: &Request, data: Value, json: bool) -> Result<(), String> {
+    if json {
+        let out = serde_json::to_string_pretty(&data).map_err(|err| format!("serialize: {err}"))?;
+        println!("{out}");
+        return Ok(());
+    }
+
+    match request {
+        Request::Status => {
+            let status: RuntimeStatus =
+                serde_json::from_value(data).map_err(|err| format!("invalid status: {err}"))?;
+            print_status(&status);
+        }
+        Request::Start => {
+            if data.as_bool() == Some(true) {
+                println!("monitoring started");
+            } else {
+                println!("monitoring not started: no enabled slots with an API key");
+            }
+        }
+        Request::Stop => println!("monitoring stopped"),
+        Request::Warmup { slot } => println!("warmup sent for slot {slot}"),
+        Request::WarmupAll => println!("warmup sent for all ready slots"),
+        Request::Stats { slot } => {
+            let stats: SlotStats =
+                serde_json::from_value(data).map_err(|err| format!("invalid stats: {err}"))?;
+            print_stats(*slot, &stats);
+        }
+        Request::History { .. } => {
+            let samples: Vec<UsageSample> =
+                serde_json::from_value(data).map_err(|err| format!("invalid history: {err}"))?;
+            print_history(&samples);
+        }
+        Request::ResetCycles { .. } => {
+            let cycles: Vec<ResetCycle> =
+                serde_json::from_value(data).map_err(|err| format!("invalid cycles: {err}"))?;
+            print_cycles(&cycles);
+        }
+        Request::CronPreview { .. } => {
+            let times: Vec<CronFireTime> =
+                serde_json::from_value(data).map_err(|err| format!("invalid preview: {err}"))?;
+            if times.is_empty() {
+                println!("no upcoming fire times");
+            }
+            for time in &times {
+                println!("{}  {}", time.local, time.expression);
+            }
+        }
+        Request::Simulate { .. } => {
+            let report: SimulationReport =
+                serde_json::from_value(data).map_err(|err| format!("invalid simulation: {err}"))?;
+            print_simulation(&report);
+        }
+        Request::LogsQuery(_) => {
+            let page: LogPage = serde_json::from_value(data).map_err(|err| format!("invalid logs: {err}"))?;
+            print_log_page(&page);
+        }
+        Request::LogsFollow(_) => {}
+        Request::LogsTail { .. } => {
+            // One compact JSON object per line, same as the log files.
+            for entry in data.as_array().into_iter().flatten() {
+                println!("{entry}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Oldest first, like `logs tail`, with a hint for the next page.
+fn print_log_page(page: &LogPage) {
+    if page.entries.is_empty() {
+        println!("no matching log entries");
+        return;
+    }
+    for entry in page.entries.iter().rev() {
+        println!("{}", format_log_entry(entry));
+    }
+    match page.next_offset {
+        Some(next) => println!("{} of {} entries; older ones with --offset {next}", page.entries.len(), page.total),
+        None => println!("{} of {} entries", page.entries.len(), page.total),
+    }
+}
+
+fn print_log_entry(data: &Value, json: bool) {
+    match serde_json::from_value::<LogEntry>(data.clone()) {
+        Ok(entry) if !json => println!("{}", format_log_entry(&entry)),
+        _ => println!("{data}"),
+    }
+}
+
+fn format_log_entry(entry: &LogEntry) -> String {
+    let ts = chrono::DateTime::parse_from_rfc3339(&entry.ts)
+        .map(|ts| ts.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|_| entry.ts.clone());
+    let outcome = match (&entry.error, entry.status) {
+        (Some(error), _) => error.clone(),
+        (None, Some(status)) => status.to_string(),
+        (None, None) => String::new(),
+    };
+    format!(
+        "{ts}  {:<4} {:<8} {:<36} {:<10} {outcome}",
+        entry.slot,
+        entry.phase.as_deref().unwrap_or("-"),
+        entry.action,
+        entry.flow_id.as_deref().unwrap_or("-"),
+    )
+}
+
+fn print_status(status: &RuntimeStatus) {
+    println!("monitoring: {}", if status.monitoring { "on" } else { "off" });
+    if status.slots.is_empty() {
+        println!("no slots configured");
+        return;
+    }
+
+    println!("{:<5} {:<20} {:>5}  {:<8}  STATE", "SLOT", "NAME", "QUOTA", "RESET");
+    for slot in &status.slots {
+        let name = if slot.name.is_empty() {
+            format!("Key {}", slot.slot)
+        } else {
+            slot.name.clone()
+        };
+        let quota = slot
+            .percentage
+            .map(|p| format!("{p}%"))
+            .unwrap_or_else(|| "-".into());
+        let reset = slot.next_reset_hms.as_deref().unwrap_or("-");
+        let state = if !slot.quota_circuit.is_closed() {
+            describe_circuit("quota", &slot.quota_circuit)
+        } else if !slot.wake_circuit.is_closed() {
+            describe_circuit("wake", &slot.wake_circuit)
+        } else if let Some(err) = &slot.last_error {
+            format!("error: {err}")
+        } else if slot.wake_pending {
+            "wake pending".to_string()
+        } else if slot.enabled {
+            "ok".to_string()
+        } else {
+            "idle".to_string()
+        };
+        println!("{:<5} {:<20} {:>5}  {:<8}  {}", slot.slot, name, quota, reset, state);
+    }
+}
+
+fn describe_circuit(endpoint: &str, circuit: &CircuitStatus) -> String {
+    match (circuit.state, circuit.next_probe_epoch_ms) {
+        (CircuitState::HalfOpen, _) => format!("{endpoint} circuit half-open, probing"),
+        (_, Some(at)) => format!("{endpoint} circuit open, next probe {}", format_ts(at)),
+        _ => format!("{endpoint} circuit open, probe due"),
+    }
+}
+
+fn print_simulation(report: &SimulationReport) {
+    println!(
+        "slot {}: {} hours simulated",
+        report.slot,
+        (report.end_epoch_ms - report.start_epoch_ms) / HOUR_MS
+    );
+    for event in &report.events {
+        println!("{:<9}  {:<18}  {}", event.local, event.kind.as_str(), event.detail);
+    }
+    println!(
+        "{} wake(s), {} confirmed, {} poll(s){}{}",
+        report.wakes,
+        report.confirmed,
+        report.polls,
+        if report.wake_auto_disabled { "; wake circuit opened" } else { "" },
+        if report.quota_auto_disabled { "; quota circuit opened" } else { "" }
+    );
+}
+
+fn print_stats(slot: usize, stats: &SlotStats) {
+    println!("slot {slot} ({})", stats.level);
+    println!("  5h:  {} calls, {} tokens", stats.total_model_calls_5h, stats.total_tokens_5h);
+    println!("  24h: {} calls, {} tokens", stats.total_model_calls_24h, stats.total_tokens_24h);
+    println!(
+        "  tools (24h): search {}, web read {}, zread {}, search mcp {}",
+        stats.total_network_search_24h,
+        stats.total_web_read_24h,
+        stats.total_zread_24h,
+        stats.total_search_mcp_24h
+    );
+    for limit in &stats.limits {
+        let reset = limit.next_reset_hms.as_deref().unwrap_or("-");
+        println!("  {:<16} {:>3}%  reset {}", limit.type_name, limit.percentage, reset);
+    }
+}
+
+fn format_ts(epoch_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(epoch_ms)
+        .map(|ts| ts.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".into())
+}
+
+fn print_history(samples: &[UsageSample]) {
+    if samples.is_empty() {
+        println!("no samples recorded in this range");
+        return;
+    }
+    println!("{:<16}  {:>5}  {:>10}  {:>12}  SOURCE", "TIME", "QUOTA", "CALLS (5h)", "TOKENS (5h)");
+    for sample in samples {
+        let quota = sample
+            .percentage
+            .map(|p| format!("{p}%"))
+            .unwrap_or_else(|| "-".into());
+        let calls = sample.model_calls_5h.map_or_else(|| "-".into(), |v| v.to_string());
+        let tokens = sample.tokens_5h.map_or_else(|| "-".into(), |v| v.to_string());
+        println!(
+            "{:<16}  {:>5}  {:>10}  {:>12}  {}",
+            format_ts(sample.ts),
+            quota,
+            calls,
+            tokens,
+            sample.source
+        );
+    }
+}
+
+fn print_cycles(cycles: &[ResetCycle]) {
+    if cycles.is_empty() {
+        println!("no quota windows recorded in this range");
+        return;
+    }
+    println!("{:<16}  {:>5}  {:>10}  {:>12}  SAMPLES", "RESET", "PEAK", "CALLS (5h)", "TOKENS (5h)");
+    for cycle in cycles {
+        println!(
+            "{:<16}  {:>4}%  {:>10}  {:>12}  {}",
+            format_ts(cycle.reset_epoch_ms),
+            cycle.peak_percentage,
+            cycle.peak_model_calls_5h,
+            cycle.peak_tokens_5h,
+            cycle.samples
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Invocation>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn oversized_time_ranges_are_usage_errors() {
+        let from_ms = |args: &[&str]| match parse(args).unwrap().unwrap().request {
+            Request::History { from_ms, .. } | Request::ResetCycles { from_ms, .. } => from_ms.unwrap(),
+            _ => unreachable!(),
+        };
+        let hours_ago = (now_ms() - from_ms(&["history", "1", "--hours", "2"])) / HOUR_MS;
+        assert_eq!(hours_ago, 2);
+        assert_eq!((now_ms() - from_ms(&["cycles", "1", "--days", "3"])) / HOUR_MS, 72);
+
+        let huge = u64::MAX.to_string();
+        let just_over = (i64::MAX as u64 / HOUR_MS as u64 + 1).to_string();
+        for args in [
+            ["history", "1", "--hours", huge.as_str()],
+            ["history", "1", "--hours", just_over.as_str()],
+            ["cycles", "1", "--days", just_over.as_str()],
+        ] {
+            let err = parse(&args).err().unwrap();
+            assert!(err.contains("is too large"), "{err}");
+        }
+    }
+
+    #[test]
+    fn log_filters_become_queries() {
+        let request = parse(&["logs", "query", "--slot", "3", "--action", "wake.*", "--hours", "1", "--limit", "5"])
+            .unwrap()
+            .unwrap()
+            .request;
+        let Request::LogsQuery(query) = request else { unreachable!() };
+        assert_eq!((query.slot, query.action.as_deref(), query.limit, query.offset), (Some(3), Some("wake.*"), Some(5), 0));
+        assert_eq!((now_ms() - query.from_ms.unwrap()) / 60_000, 60);
+
+        let Request::LogsFollow(query) = parse(&["logs", "follow", "--flow", "f-9"]).unwrap().unwrap().request else {
+            unreachable!()
+        };
+        assert_eq!((query.flow_id.as_deref(), query.from_ms), (Some("f-9"), None));
+        assert!(parse(&["logs", "follow", "--hours", "2"]).is_err());
+        assert!(parse(&["logs", "query", "--slot", "x"]).is_err());
+    }
+}
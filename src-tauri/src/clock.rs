@@ -0,0 +1,92 @@
+//! Time source for the scheduler and API client. Everything that decides
+//! when to wake or poll reads the time through `Clock`, so tests and the
+//! schedule simulation can swap the system clock for a `FakeClock` and let
+//! hours pass instantly.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+use tokio::time::Instant;
+
+pub trait Clock: Send + Sync {
+    /// Wall-clock time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Monotonic time, for intervals and deadlines that must not jump with
+    /// the wall clock.
+    fn instant(&self) -> Instant;
+
+    fn now_ms(&self) -> i64 {
+        self.now().timestamp_millis()
+    }
+
+    fn now_local(&self) -> DateTime<Local> {
+        self.now().with_timezone(&Local)
+    }
+}
+
+pub type ClockHandle = Arc<dyn Clock>;
+
+/// The real clocks.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+pub fn system() -> ClockHandle {
+    Arc::new(SystemClock)
+}
+
+/// Clock that only moves when told to. `advance` moves both clocks, as time
+/// passing normally does; `suspend` moves only the wall clock, as a system
+/// sleep does.
+pub struct FakeClock {
+    base: Instant,
+    state: std::sync::Mutex<FakeTime>,
+}
+
+struct FakeTime {
+    wall: DateTime<Utc>,
+    elapsed: std::time::Duration,
+}
+
+impl FakeClock {
+    pub fn new(start: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self {
+            base: Instant::now(),
+            state: std::sync::Mutex::new(FakeTime {
+                wall: start,
+                elapsed: std::time::Duration::ZERO,
+            }),
+        })
+    }
+
+    pub fn advance(&self, by: std::time::Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.wall += chrono::Duration::from_std(by).unwrap();
+        state.elapsed += by;
+    }
+
+    #[cfg(test)]
+    pub fn suspend(&self, by: std::time::Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.wall += chrono::Duration::from_std(by).unwrap();
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.state.lock().unwrap().wall
+    }
+
+    fn instant(&self) -> Instant {
+        self.base + self.state.lock().unwrap().elapsed
+    }
+}
@@ -1,11 +1,18 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use log::{debug, info, warn};
 use serde::Deserialize;
-use tauri::{AppHandle, Manager};
+use serde_json::Value;
 use tokio::fs;
 
-use crate::models::{AppConfig, KeySlotConfig, CURRENT_CONFIG_VERSION, MAX_SLOTS};
+use crate::cron_schedule;
+use crate::slot_zone::{self, SlotZone};
+use crate::host::HostHandle;
+use crate::network;
+use crate::secrets::{self, KeyVault};
+use crate::provider::{ANTHROPIC_MESSAGES_PATH, OPENAI_CHAT_PATH};
+use crate::models::{AlertKind, ApiProtocol, AppConfig, KeySlotConfig, ProviderKind, WakeRequestConfig, CURRENT_CONFIG_VERSION, DEFAULT_WAKE_MODEL};
 
 const CONFIG_FILE_NAME: &str = "settings.json";
 
@@ -46,16 +53,25 @@ impl From<SlotConfigV2> for KeySlotConfig {
             name: old.name,
             enabled: old.enabled,
             api_key: old.api_key,
+            provider: ProviderKind::detect(&old.quota_url, old.request_url.as_deref(), ApiProtocol::default()),
             quota_url: old.quota_url,
             request_url: old.request_url,
+            protocol: ApiProtocol::default(),
+            proxy_url: None,
             schedule_interval_enabled: old.wake_interval_enabled,
             schedule_times_enabled: old.wake_times_enabled,
             schedule_after_reset_enabled: old.wake_after_reset_enabled,
             schedule_interval_minutes: old.wake_interval_minutes,
             schedule_times: old.wake_times,
             schedule_after_reset_minutes: old.wake_after_reset_minutes,
+            schedule_cron_enabled: false,
+            schedule_cron: Vec::new(),
+            schedule_timezone: String::new(),
+            schedule_days: slot_zone::ALL_DAYS,
+            missed_fire_policy: Default::default(),
             poll_interval_minutes: old.poll_interval_minutes,
             logging: old.logging,
+            wake_request: WakeRequestConfig::default(),
         }
     }
 }
@@ -75,6 +91,8 @@ impl From<AppConfigV2> for AppConfig {
             debug: false,
             mock_url: None,
             auto_update: true,
+            next_slot_id: 1,
+            ..Default::default()
         }
     }
 }
@@ -106,6 +124,33 @@ fn migrate(raw_json: &str) -> Result<AppConfig, String> {
         cfg.config_version = 3;
     }
 
+    // version 3 → 4: slots are an addressable collection with stable IDs.
+    // The fixed-index layout always carried four entries; drop the untouched
+    // placeholders and keep the positional IDs of the rest.
+    if from < 4 {
+        info!("migrating config v{} → v4 (stable slot IDs)", cfg.config_version);
+        cfg.slots.retain(|s| !is_blank_slot(s));
+        cfg.next_slot_id = cfg.slots.iter().map(|s| s.slot).max().unwrap_or(0) + 1;
+        cfg.config_version = 4;
+    }
+
+    // version 4 → 5: slots select a provider. Only slots on a platform's
+    // default endpoints become built-in; any other URLs are kept as custom.
+    if from < 5 {
+        info!("migrating config v{} → v5 (slot providers)", cfg.config_version);
+        for slot in cfg.slots.iter_mut() {
+            slot.provider = ProviderKind::detect(&slot.quota_url, slot.request_url.as_deref(), slot.protocol);
+        }
+        cfg.config_version = 5;
+    }
+
+    // version 5 → 6: API keys are encrypted at rest. Keys still in
+    // plaintext are sealed by the re-save in `load_config`.
+    if from < 6 {
+        info!("migrating config v{} → v6 (encrypted API keys)", cfg.config_version);
+        cfg.config_version = 6;
+    }
+
     if from != cfg.config_version && from > 0 {
         info!("config migrated from v{from} → v{}", cfg.config_version);
     }
@@ -113,6 +158,11 @@ fn migrate(raw_json: &str) -> Result<AppConfig, String> {
     Ok(cfg)
 }
 
+/// A slot that was never touched in the fixed four-slot layout.
+fn is_blank_slot(slot: &KeySlotConfig) -> bool {
+    !slot.enabled && slot.api_key.trim().is_empty() && slot.name.trim().is_empty()
+}
+
 /// Check if debug mode is enabled via environment variable
 fn is_debug_mode() -> bool {
     std::env::var("GLM_TRAY_DEBUG")
@@ -157,21 +207,126 @@ fn validate(mut cfg: AppConfig) -> AppConfig {
     }
 
     cfg.max_log_days = cfg.max_log_days.clamp(1, 365);
+    cfg.max_log_file_mb = cfg.max_log_file_mb.clamp(1, 1_024);
+    cfg.max_log_total_mb = cfg.max_log_total_mb.clamp(cfg.max_log_file_mb, 102_400);
+    cfg.history_retention_days = cfg.history_retention_days.clamp(1, 3_650);
     cfg.wake_quota_retry_window_minutes = cfg.wake_quota_retry_window_minutes.clamp(1, 1_440);
     cfg.max_consecutive_errors = cfg.max_consecutive_errors.clamp(1, 1_000);
     cfg.quota_poll_backoff_cap_minutes = cfg.quota_poll_backoff_cap_minutes.clamp(1, 1_440);
+    cfg.circuit_probe_minutes = cfg.circuit_probe_minutes.clamp(1, 1_440);
+
+    // -- proxy_port: unprivileged ports only --
+    if cfg.proxy_port < 1024 {
+        warn!("config: invalid proxy_port {}, resetting to default", cfg.proxy_port);
+        cfg.proxy_port = AppConfig::default().proxy_port;
+    }
+    cfg.proxy_token = cfg
+        .proxy_token
+        .take()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+
+    // -- alerts: sane bounds, at most 20 rules --
+    cfg.alerts.debounce_seconds = cfg.alerts.debounce_seconds.min(3_600);
+    cfg.alerts.rules.truncate(20);
+    for rule in cfg.alerts.rules.iter_mut() {
+        if rule.kind == AlertKind::QuotaThreshold {
+            rule.threshold = rule.threshold.clamp(1, 100);
+        }
+        rule.quiet_minutes = rule.quiet_minutes.min(10_080);
+        rule.slots.sort_unstable();
+        rule.slots.dedup();
+    }
+
+    // -- webhooks: http(s) URLs only, at most 10 targets --
+    cfg.webhooks.retain(|target| {
+        let url = target.url.trim();
+        let valid = url.starts_with("https://") || url.starts_with("http://");
+        if !valid {
+            warn!("config: dropping webhook '{}' with invalid url '{}'", target.name, target.url);
+        }
+        valid
+    });
+    cfg.webhooks.truncate(10);
+    for target in cfg.webhooks.iter_mut() {
+        target.url = target.url.trim().to_string();
+        target.name = target.name.trim().chars().take(32).collect();
+        target.max_retries = target.max_retries.min(10);
+        target.events.retain(|e| !e.trim().is_empty());
+        target.quota_thresholds.retain(|t| (1..=100).contains(t));
+        target.quota_thresholds.sort_unstable();
+        target.quota_thresholds.dedup();
+    }
+
+    // -- network: valid proxy URL; certificate files are read when clients are built --
+    let network = &mut cfg.network;
+    network.proxy_url = network.proxy_url.trim().to_string();
+    if !network::is_valid_proxy_url(&network.proxy_url) {
+        warn!("config: invalid proxy_url '{}', using the environment", network.proxy_url);
+        network.proxy_url.clear();
+    }
+    network.no_proxy = network.no_proxy.trim().to_string();
+    for (name, path) in [
+        ("ca_bundle_path", &mut network.ca_bundle_path),
+        ("client_cert_path", &mut network.client_cert_path),
+        ("client_key_path", &mut network.client_key_path),
+    ] {
+        *path = path.trim().to_string();
+        if !path.is_empty() && !std::path::Path::new(path.as_str()).is_file() {
+            warn!("config: {name} '{path}' does not exist");
+        }
+    }
 
-    // -- slot count --
-    if cfg.slots.len() > MAX_SLOTS {
-        warn!("config: truncating {} slots → {MAX_SLOTS}", cfg.slots.len());
-        cfg.slots.truncate(MAX_SLOTS);
+    // -- retry: at most 10 tries a minute apart; real HTTP statuses only --
+    let retry = &mut cfg.retry;
+    retry.max_attempts = retry.max_attempts.clamp(1, 10);
+    retry.max_delay_ms = retry.max_delay_ms.clamp(1, 60_000);
+    retry.base_delay_ms = retry.base_delay_ms.clamp(1, retry.max_delay_ms);
+    retry.jitter_percent = retry.jitter_percent.min(100);
+    retry.retry_statuses.retain(|status| (400..=599).contains(status));
+    retry.retry_statuses.sort_unstable();
+    retry.retry_statuses.dedup();
+
+    // -- log_redaction: trimmed rules, valid patterns only, at most 20 of each --
+    let redaction = &mut cfg.log_redaction;
+    for rules in [&mut redaction.header_names, &mut redaction.json_paths, &mut redaction.patterns] {
+        rules.iter_mut().for_each(|rule| *rule = rule.trim().to_string());
+        rules.retain(|rule| !rule.is_empty());
+        rules.truncate(20);
     }
-    while cfg.slots.len() < MAX_SLOTS {
-        cfg.slots.push(KeySlotConfig::default());
+    redaction.json_paths.retain(|path| {
+        let valid = path.split('.').all(|segment| !segment.is_empty());
+        if !valid {
+            warn!("config: dropping invalid log redaction path '{path}'");
+        }
+        valid
+    });
+    redaction.patterns.retain(|pattern| match regex::Regex::new(pattern) {
+        Ok(_) => true,
+        Err(err) => {
+            warn!("config: dropping invalid log redaction pattern '{pattern}': {err}");
+            false
+        }
+    });
+
+    // -- slot IDs: keep stable IDs, re-issue missing or duplicate ones --
+    let mut seen_ids = HashSet::new();
+    let mut needs_id = Vec::new();
+    for (idx, slot) in cfg.slots.iter().enumerate() {
+        if slot.slot == 0 || !seen_ids.insert(slot.slot) {
+            needs_id.push(idx);
+        }
     }
+    for idx in needs_id {
+        let old_id = cfg.slots[idx].slot;
+        let new_id = cfg.allocate_slot_id();
+        warn!("config: slot id {old_id} is missing or duplicated, reassigning → {new_id}");
+        cfg.slots[idx].slot = new_id;
+    }
+    let max_id = cfg.slots.iter().map(|s| s.slot).max().unwrap_or(0);
+    cfg.next_slot_id = cfg.next_slot_id.max(max_id + 1);
 
-    for (idx, slot) in cfg.slots.iter_mut().enumerate() {
-        slot.slot = idx + 1;
+    for slot in cfg.slots.iter_mut() {
 
         // -- name: trim, cap at 32 chars --
         slot.name = slot.name.trim().chars().take(32).collect();
@@ -179,6 +334,16 @@ fn validate(mut cfg: AppConfig) -> AppConfig {
         // -- api_key: trim whitespace (no length cap – keys vary by platform) --
         slot.api_key = slot.api_key.trim().to_string();
 
+        // -- provider: built-in providers fill in URLs left blank --
+        if let Some(provider) = slot.provider.builtin() {
+            if slot.quota_url.trim().is_empty() {
+                slot.quota_url = provider.quota_url();
+            }
+            if slot.request_url.as_deref().is_none_or(|url| url.trim().is_empty()) {
+                slot.request_url = Some(provider.request_url(slot.protocol));
+            }
+        }
+
         // -- URLs: must be valid (https://, or http:// in debug mode) or fall back to defaults --
         if !is_valid_url(&slot.quota_url) {
             if !slot.quota_url.trim().is_empty() {
@@ -195,6 +360,15 @@ fn validate(mut cfg: AppConfig) -> AppConfig {
             slot.request_url = Some(cfg.global_request_url.clone());
         }
 
+        // -- proxy_url: per-key override of the global proxy --
+        if let Some(url) = slot.proxy_url.as_mut() {
+            *url = url.trim().to_string();
+            if !network::is_valid_proxy_url(url) {
+                warn!("slot {}: invalid proxy_url '{}', using the global proxy", slot.slot, url);
+                slot.proxy_url = None;
+            }
+        }
+
         // -- interval bounds (min 1, max 1440 = 24 h) --
         slot.poll_interval_minutes = slot.poll_interval_minutes.clamp(1, 1440);
         slot.schedule_interval_minutes = slot.schedule_interval_minutes.clamp(1, 1440);
@@ -224,6 +398,50 @@ fn validate(mut cfg: AppConfig) -> AppConfig {
             })
             .collect();
 
+        // -- schedule_cron: trim, drop blanks and unparsable expressions, max 5 --
+        slot.schedule_cron = slot
+            .schedule_cron
+            .iter()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .filter(|v| match cron_schedule::parse(v) {
+                Ok(_) => true,
+                Err(err) => {
+                    warn!("slot {}: dropping {err}", slot.slot);
+                    false
+                }
+            })
+            .take(cron_schedule::MAX_EXPRESSIONS)
+            .collect();
+
+        // -- schedule_timezone: known IANA name or empty (system zone) --
+        slot.schedule_timezone = slot.schedule_timezone.trim().to_string();
+        if let Err(err) = SlotZone::parse(&slot.schedule_timezone) {
+            warn!("slot {}: {err}, using the system zone", slot.slot);
+            slot.schedule_timezone.clear();
+        }
+
+        // -- schedule_days: 7-bit weekday mask, at least one day --
+        slot.schedule_days &= slot_zone::ALL_DAYS;
+        if slot.schedule_days == 0 {
+            warn!("slot {}: no schedule days selected, using every day", slot.slot);
+            slot.schedule_days = slot_zone::ALL_DAYS;
+        }
+
+        // -- request_url: follow the protocol on the platform's default paths --
+        if let Some(url) = slot.request_url.as_mut() {
+            let (from, to) = match slot.protocol {
+                ApiProtocol::OpenAi => (ANTHROPIC_MESSAGES_PATH, OPENAI_CHAT_PATH),
+                ApiProtocol::Anthropic => (OPENAI_CHAT_PATH, ANTHROPIC_MESSAGES_PATH),
+            };
+            if let Some(base) = url.strip_suffix(from) {
+                info!("slot {}: request_url switched to the {:?} endpoint", slot.slot, slot.protocol);
+                *url = format!("{base}{to}");
+            }
+        }
+
+        validate_wake_request(slot.slot, slot.protocol, &mut slot.wake_request);
+
         // -- if key is blank, disable polling for safety --
         if slot.api_key.is_empty() && slot.enabled {
             warn!("slot {}: no API key, force-disabling", slot.slot);
@@ -236,17 +454,80 @@ fn validate(mut cfg: AppConfig) -> AppConfig {
     cfg
 }
 
-pub fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let mut base = app
-        .path()
-        .app_config_dir()
-        .map_err(|err| format!("failed to resolve app config dir: {err}"))?;
+/// Body fields the wake request sets itself; `extra` may not override them.
+const RESERVED_WAKE_FIELDS: [&str; 5] = ["model", "messages", "max_tokens", "thinking", "stream"];
+
+/// `max_tokens` for Anthropic wakes that do not set one; the API requires it.
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 32;
+
+fn validate_wake_request(slot_id: usize, protocol: ApiProtocol, wake: &mut WakeRequestConfig) {
+    // -- model: trimmed, default when blank --
+    wake.model = wake.model.trim().chars().take(64).collect();
+    if wake.model.is_empty() {
+        wake.model = DEFAULT_WAKE_MODEL.to_string();
+    }
+
+    // -- messages: known roles, no blank content, max 10, at least one user message --
+    wake.messages.retain_mut(|message| {
+        message.role = message.role.trim().to_ascii_lowercase();
+        let valid = matches!(message.role.as_str(), "system" | "user" | "assistant")
+            && !message.content.trim().is_empty();
+        if !valid {
+            warn!("slot {slot_id}: dropping invalid wake message with role '{}'", message.role);
+        }
+        valid
+    });
+    wake.messages.truncate(10);
+    if !wake.messages.iter().any(|message| message.role == "user") {
+        warn!("slot {slot_id}: wake request has no user message, using the default messages");
+        wake.messages = WakeRequestConfig::default_messages();
+    }
+
+    // -- Anthropic: the conversation must open with a user message --
+    if protocol == ApiProtocol::Anthropic {
+        while let Some(index) = wake.messages.iter().position(|message| message.role != "system") {
+            if wake.messages[index].role == "user" {
+                break;
+            }
+            warn!("slot {slot_id}: dropping leading assistant message, Anthropic conversations start with the user");
+            wake.messages.remove(index);
+        }
+    }
+
+    // -- max_tokens: 1..=4096, 0 means unset (Anthropic requires it) --
+    wake.max_tokens = wake.max_tokens.filter(|n| *n > 0).map(|n| n.min(4_096));
+    if protocol == ApiProtocol::Anthropic && wake.max_tokens.is_none() {
+        wake.max_tokens = Some(ANTHROPIC_DEFAULT_MAX_TOKENS);
+    }
+
+    // -- extra: may not replace the fields above --
+    for key in RESERVED_WAKE_FIELDS {
+        if wake.extra.remove(key).is_some() {
+            warn!("slot {slot_id}: dropping reserved wake request field '{key}'");
+        }
+    }
+}
+
+/// `slot` sanitised as it would be when saved into `cfg`.
+pub fn validate_slot(cfg: &AppConfig, slot: KeySlotConfig) -> KeySlotConfig {
+    let slot_id = slot.slot;
+    let mut validated = validate(AppConfig {
+        slots: vec![slot],
+        ..cfg.clone()
+    });
+    let mut slot = validated.slots.remove(0);
+    slot.slot = slot_id;
+    slot
+}
+
+pub fn config_path(host: &HostHandle) -> Result<PathBuf, String> {
+    let mut base = host.config_dir()?;
     base.push(CONFIG_FILE_NAME);
     Ok(base)
 }
 
-pub async fn load_config(app: &AppHandle) -> Result<AppConfig, String> {
-    let path = config_path(app)?;
+pub async fn load_config(host: &HostHandle) -> Result<AppConfig, String> {
+    let path = config_path(host)?;
 
     if !path.exists() {
         info!("no config file at {}, using defaults", path.display());
@@ -259,13 +540,23 @@ pub async fn load_config(app: &AppHandle) -> Result<AppConfig, String> {
         .await
         .map_err(|err| format!("failed to read config: {err}"))?;
 
-    let migrated = migrate(&content)?;
+    let vault = KeyVault::open(host).await?;
+    let mut migrated = migrate(&content)?;
+    let previous = secrets::sealed_keys(&migrated);
+    if vault.unseal_keys(&mut migrated)? {
+        info!("encrypting plaintext API keys in {}", path.display());
+    }
     let validated = validate(migrated);
-
-    // Re-save if migration or validation changed anything
-    if validated.config_version != 0 {
-        // always persist after load so the file reflects the latest schema
-        let serialized = serde_json::to_string_pretty(&validated)
+    let mut sealed = validated.clone();
+    vault.seal_keys(&mut sealed, &previous);
+
+    // Re-save only if migration or validation changed anything (plaintext
+    // keys included); the daemon loads on every SIGHUP and must not rewrite
+    // an unchanged file.
+    let on_disk = serde_json::from_str::<Value>(&content).ok();
+    if on_disk != serde_json::to_value(&sealed).ok() {
+        info!("re-saving migrated config to {}", path.display());
+        let serialized = serde_json::to_string_pretty(&sealed)
             .map_err(|err| format!("failed to serialize config: {err}"))?;
         if let Some(parent) = path.parent() {
             let _ = fs::create_dir_all(parent).await;
@@ -276,9 +567,12 @@ pub async fn load_config(app: &AppHandle) -> Result<AppConfig, String> {
     Ok(validated)
 }
 
-pub async fn save_config(app: &AppHandle, input: AppConfig) -> Result<AppConfig, String> {
+// Only the settings UI saves edits; the daemon only re-saves a file that
+// `load_config` had to migrate or repair.
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub async fn save_config(host: &HostHandle, input: AppConfig) -> Result<AppConfig, String> {
     let validated = validate(input);
-    let path = config_path(app)?;
+    let path = config_path(host)?;
 
     info!("saving config to {}", path.display());
 
@@ -288,7 +582,17 @@ pub async fn save_config(app: &AppHandle, input: AppConfig) -> Result<AppConfig,
             .map_err(|err| format!("failed to create config directory: {err}"))?;
     }
 
-    let serialized = serde_json::to_string_pretty(&validated)
+    // Keep the ciphertext of keys that did not change.
+    let previous = match fs::read_to_string(&path).await {
+        Ok(content) => serde_json::from_str::<AppConfig>(&content)
+            .map(|on_disk| secrets::sealed_keys(&on_disk))
+            .unwrap_or_default(),
+        Err(_) => Default::default(),
+    };
+    let mut sealed = validated.clone();
+    KeyVault::open(host).await?.seal_keys(&mut sealed, &previous);
+
+    let serialized = serde_json::to_string_pretty(&sealed)
         .map_err(|err| format!("failed to serialize config: {err}"))?;
 
     fs::write(path, serialized)
@@ -297,3 +601,147 @@ pub async fn save_config(app: &AppHandle, input: AppConfig) -> Result<AppConfig,
 
     Ok(validated)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::host::HeadlessHost;
+    use serde_json::json;
+
+    #[test]
+    fn v3_fixed_slots_migrate_to_stable_ids() {
+        // The v3 layout always held four positional slots; the second was
+        // never touched, and hand edits left a duplicate and a zero ID.
+        let raw = json!({
+            "config_version": 3,
+            "theme": "dark",
+            "slots": [
+                { "slot": 1, "name": "Work", "enabled": true, "api_key": "key-work" },
+                { "slot": 2, "name": "", "enabled": false, "api_key": "" },
+                { "slot": 1, "name": "Copy", "enabled": false, "api_key": "key-copy" },
+                { "slot": 0, "name": "Spare", "enabled": false, "api_key": "" }
+            ]
+        });
+
+        let migrated = migrate(&raw.to_string()).unwrap();
+        let names: Vec<_> = migrated.slots.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Work", "Copy", "Spare"]);
+        assert_eq!(migrated.next_slot_id, 2);
+
+        let cfg = validate(migrated);
+        let slots: Vec<_> = cfg.slots.iter().map(|s| (s.slot, s.name.as_str(), s.api_key.as_str())).collect();
+        assert_eq!(slots, [(1, "Work", "key-work"), (2, "Copy", "key-copy"), (3, "Spare", "")]);
+        assert_eq!(cfg.next_slot_id, 4);
+        assert_eq!(cfg.config_version, CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn v4_slots_become_built_in_only_on_default_urls() {
+        let zai_quota = "https://api.z.ai/api/monitor/usage/quota/limit";
+        let gateway = "https://api.z.ai/api/paas/v4/chat/completions";
+        let raw = json!({
+            "config_version": 4,
+            "next_slot_id": 4,
+            "slots": [
+                { "slot": 1, "api_key": "a", "quota_url": zai_quota,
+                  "request_url": "https://api.z.ai/api/coding/paas/v4/chat/completions" },
+                { "slot": 2, "api_key": "b", "quota_url": zai_quota, "request_url": gateway },
+                { "slot": 3, "api_key": "c", "protocol": "anthropic",
+                  "quota_url": "https://open.bigmodel.cn/api/monitor/usage/quota/limit",
+                  "request_url": "https://open.bigmodel.cn/api/anthropic/v1/messages" }
+            ]
+        });
+
+        let mut cfg = validate(migrate(&raw.to_string()).unwrap());
+        let providers: Vec<_> = cfg.slots.iter().map(|s| s.provider).collect();
+        assert_eq!(providers, [ProviderKind::Zai, ProviderKind::Custom, ProviderKind::BigModel]);
+        assert_eq!(cfg.slots[1].request_url.as_deref(), Some(gateway));
+
+        // Built-in slots only get their defaults where a URL is blank.
+        cfg.slots[0].request_url = Some(gateway.to_string());
+        cfg.slots[2].quota_url.clear();
+        let cfg = validate(cfg);
+        assert_eq!(cfg.slots[0].request_url.as_deref(), Some(gateway));
+        assert_eq!(cfg.slots[2].quota_url, "https://open.bigmodel.cn/api/monitor/usage/quota/limit");
+    }
+
+    #[tokio::test]
+    async fn load_rewrites_the_file_only_when_migration_changes_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::default()));
+        let path = config_path(&host).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, json!({ "config_version": 3, "slots": [{ "slot": 1, "api_key": "key" }] }).to_string())
+            .unwrap();
+
+        let first = load_config(&host).await.unwrap();
+        let migrated = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<AppConfig>(&migrated).unwrap().config_version, CURRENT_CONFIG_VERSION);
+
+        // Reloading an up-to-date file leaves it alone, even its formatting.
+        let compact = serde_json::from_str::<Value>(&migrated).unwrap().to_string();
+        std::fs::write(&path, &compact).unwrap();
+        let second = load_config(&host).await.unwrap();
+        assert_eq!(serde_json::to_value(&second).unwrap(), serde_json::to_value(&first).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), compact);
+    }
+
+    #[tokio::test]
+    async fn plaintext_keys_are_encrypted_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::default()));
+        let path = config_path(&host).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let raw = json!({ "config_version": 5, "slots": [{ "slot": 1, "enabled": true, "api_key": "sk-plaintext-secret" }] });
+        std::fs::write(&path, raw.to_string()).unwrap();
+
+        let loaded = load_config(&host).await.unwrap();
+        assert_eq!(loaded.slots[0].api_key, "sk-plaintext-secret");
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("sk-plaintext-secret"));
+        let sealed = serde_json::from_str::<AppConfig>(&on_disk).unwrap().slots[0].api_key.clone();
+        assert!(secrets::is_sealed(&sealed));
+
+        // Saving an unchanged key keeps its ciphertext; a new key gets a new one.
+        let mut edited = loaded.clone();
+        save_config(&host, edited.clone()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), on_disk);
+        edited.slots[0].api_key = "sk-replaced-secret".to_string();
+        save_config(&host, edited).await.unwrap();
+        let replaced = std::fs::read_to_string(&path).unwrap();
+        assert!(!replaced.contains("sk-replaced-secret") && !replaced.contains(&sealed));
+        assert_eq!(load_config(&host).await.unwrap().slots[0].api_key, "sk-replaced-secret");
+    }
+
+    #[test]
+    fn wake_request_drops_reserved_extra_fields() {
+        let extra = json!({
+            "model": "glm-5",
+            "messages": [],
+            "max_tokens": 9000,
+            "thinking": { "type": "enabled" },
+            "stream": true,
+            "temperature": 0.2,
+        });
+        let mut wake: WakeRequestConfig = serde_json::from_value(json!({
+            "model": "  ",
+            "messages": [
+                { "role": "assistant", "content": "earlier reply" },
+                { "role": "narrator", "content": "dropped" },
+                { "role": "User", "content": "ping" },
+            ],
+            "extra": extra,
+        }))
+        .unwrap();
+
+        validate_wake_request(1, ApiProtocol::Anthropic, &mut wake);
+
+        assert_eq!(Value::Object(wake.extra), json!({ "temperature": 0.2 }));
+        assert_eq!(wake.model, DEFAULT_WAKE_MODEL);
+        assert_eq!(wake.max_tokens, Some(ANTHROPIC_DEFAULT_MAX_TOKENS));
+        let roles: Vec<_> = wake.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user"]);
+    }
+}
@@ -0,0 +1,317 @@
+//! Host-independent operations behind the Tauri commands, the tray menu and
+//! the IPC socket, so every control surface drives the same shared state.
+
+use std::sync::Arc;
+
+use log::{info, warn};
+use tokio::sync::RwLock;
+
+use crate::clock::ClockHandle;
+use crate::cron_schedule::{self, CronFireTime};
+use crate::history::{self, ResetCycle, UsageSample};
+use crate::models::{KeySlotConfig, RuntimeStatus, SlotStats};
+use crate::simulation::{self, SimulationInput, SimulationReport};
+use crate::slot_zone::SlotZone;
+use crate::{api_client, config, has_enabled_slot_with_key, scheduler, scheduler_state, SharedState};
+
+const DAY_MS: i64 = 24 * 3_600_000;
+
+pub async fn start_monitoring(state: &SharedState) -> Result<(), String> {
+    info!("starting monitoring");
+    let settings = state.config.read().await.clone();
+    let enabled = settings
+        .slots
+        .iter()
+        .filter(|s| s.enabled && !s.api_key.trim().is_empty())
+        .count();
+
+    if enabled == 0 {
+        info!("start monitoring skipped: no enabled slots configured");
+        state.host.emit("monitoring-changed", false.into());
+        return Ok(());
+    }
+
+    info!("monitoring {enabled} enabled slot(s)");
+    let runtime_status = state.runtime_status.clone();
+    let mut scheduler = state.scheduler.lock().await;
+    scheduler.start(state.host.clone(), settings, runtime_status).await;
+    state.host.emit("monitoring-changed", true.into());
+    Ok(())
+}
+
+pub async fn stop_monitoring(state: &SharedState) -> Result<(), String> {
+    info!("stopping monitoring");
+    let config = state.config.read().await.clone();
+    let mut scheduler = state.scheduler.lock().await;
+    scheduler.stop().await;
+    // An explicit stop resets error counters and auto-disable decisions.
+    scheduler_state::clear(&state.host).await;
+    scheduler::reset_runtime(&state.runtime_status).await;
+    let snapshot = state.runtime_status.read().await.clone();
+    let has_ready_slots = has_enabled_slot_with_key(&config);
+    state.host.refresh_status(snapshot, has_ready_slots)?;
+    state.host.emit("monitoring-changed", false.into());
+    Ok(())
+}
+
+pub async fn warmup_slot(state: &SharedState, slot: usize) -> Result<(), String> {
+    info!("warmup slot {} requested", slot);
+    let clock = scheduler_clock(state).await;
+    let config = state.config.read().await.clone();
+    let slot_cfg = config.slot(slot)
+        .ok_or_else(|| format!("slot {slot} not found"))?;
+
+    if !slot_cfg.enabled || slot_cfg.api_key.trim().is_empty() {
+        return Err("slot is disabled or has no API key".into());
+    }
+
+    let client = api_client::ApiClient::new(
+        Some(state.host.clone()),
+        config.debug,
+        config.mock_url.clone(),
+        &config.network_for(slot_cfg),
+    )?
+    .with_retry(config.retry.clone())
+    .with_clock(clock);
+    if is_slot_quota_full_realtime(&client, &state.runtime_status, slot_cfg).await {
+        return Err("slot reset window is still active".into());
+    }
+    client.warmup_key(slot_cfg).await?;
+    info!("warmup slot {} succeeded", slot);
+
+    // Fetch fresh stats immediately for instant UI feedback
+    if let Ok(snapshot) = client.fetch_quota(slot_cfg, "warmup-postcheck").await {
+        let (model_calls, tokens) = client.fetch_model_usage_5h(slot_cfg, snapshot.next_reset_epoch_ms).await;
+        let now_iso = client.clock().now_local().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+
+        {
+            let mut runtime = state.runtime_status.write().await;
+            if let Some(current) = runtime.slot_mut(slot) {
+                current.timer_active = snapshot.timer_active;
+                current.percentage = Some(snapshot.percentage);
+                current.next_reset_hms = snapshot.next_reset_hms.clone();
+                current.last_updated_epoch_ms = snapshot.next_reset_epoch_ms;
+                current.total_model_calls_5h = model_calls;
+                current.total_tokens_5h = tokens;
+                current.quota_last_updated = Some(now_iso.clone());
+            }
+        }
+
+        state.host.emit("quota-updated", serde_json::json!({
+            "slot": slot,
+            "timer_active": snapshot.timer_active,
+            "percentage": snapshot.percentage,
+            "next_reset_hms": snapshot.next_reset_hms,
+            "total_model_calls_5h": model_calls,
+            "total_tokens_5h": tokens,
+            "quota_last_updated": now_iso
+        }));
+    }
+
+    Ok(())
+}
+
+pub async fn warmup_all(state: &SharedState) -> Result<(), String> {
+    info!("warmup all keys requested");
+    let clock = scheduler_clock(state).await;
+    let config = state.config.read().await.clone();
+    let runtime_status = state.runtime_status.clone();
+
+    for slot_cfg in &config.slots {
+        if !slot_cfg.enabled || slot_cfg.api_key.trim().is_empty() {
+            continue;
+        }
+        // Each key may go through its own proxy
+        let client = match api_client::ApiClient::new(
+            Some(state.host.clone()),
+            config.debug,
+            config.mock_url.clone(),
+            &config.network_for(slot_cfg),
+        ) {
+            Ok(client) => client.with_retry(config.retry.clone()).with_clock(clock.clone()),
+            Err(err) => {
+                warn!("warmup slot {} failed: {}", slot_cfg.slot, err);
+                continue;
+            }
+        };
+        if is_slot_quota_full_realtime(&client, &runtime_status, slot_cfg).await {
+            warn!(
+                "slot {} next reset window is still active, skipping warmup",
+                slot_cfg.slot
+            );
+            continue;
+        }
+        info!("warming up slot {}", slot_cfg.slot);
+        match client.warmup_key(slot_cfg).await {
+            Ok(()) => info!("warmup slot {} succeeded", slot_cfg.slot),
+            Err(err) => warn!("warmup slot {} failed: {}", slot_cfg.slot, err),
+        }
+    }
+
+    info!("warmup all keys completed");
+    Ok(())
+}
+
+pub async fn fetch_slot_stats(state: &SharedState, slot: usize) -> Result<SlotStats, String> {
+    let clock = scheduler_clock(state).await;
+    let config = state.config.read().await;
+    let slot_cfg = config.slot(slot)
+        .ok_or_else(|| format!("slot {slot} not found"))?;
+    if slot_cfg.api_key.trim().is_empty() {
+        return Err("no API key configured".into());
+    }
+    let client = api_client::ApiClient::new(
+        Some(state.host.clone()),
+        config.debug,
+        config.mock_url.clone(),
+        &config.network_for(slot_cfg),
+    )?
+    .with_retry(config.retry.clone())
+    .with_clock(clock);
+    let stats = client.fetch_slot_stats(slot_cfg).await?;
+    history::record(&state.host, UsageSample::from_stats(slot, &stats)).await;
+    Ok(stats)
+}
+
+/// Recorded samples for `slot` between `from_ms` and `to_ms` (default: the
+/// last 24 hours), optionally downsampled.
+pub async fn usage_history(
+    state: &SharedState,
+    slot: usize,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    bucket_minutes: Option<u64>,
+    max_points: Option<usize>,
+) -> Result<Vec<UsageSample>, String> {
+    let now_ms = scheduler_clock(state).await.now_ms();
+    let (from_ms, to_ms) = history_range(now_ms, from_ms, to_ms, DAY_MS);
+    let bucket = history::bucket_ms(from_ms, to_ms, bucket_minutes, max_points);
+    history::query(&state.host, slot, from_ms, to_ms, bucket).await
+}
+
+/// Quota windows observed for `slot` in the range (default: the last 7 days).
+pub async fn reset_cycles(
+    state: &SharedState,
+    slot: usize,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+) -> Result<Vec<ResetCycle>, String> {
+    let now_ms = scheduler_clock(state).await.now_ms();
+    let (from_ms, to_ms) = history_range(now_ms, from_ms, to_ms, 7 * DAY_MS);
+    history::reset_cycles(&state.host, slot, from_ms, to_ms).await
+}
+
+/// Next `count` fire times of `expressions`, or of the slot's saved
+/// `schedule_cron` when no expressions are given. Times are in the slot's
+/// zone, or in `timezone` when given (the system zone otherwise).
+pub async fn cron_preview(
+    state: &SharedState,
+    slot: Option<usize>,
+    expressions: Vec<String>,
+    timezone: Option<String>,
+    count: usize,
+) -> Result<Vec<CronFireTime>, String> {
+    let slot_cfg = match slot {
+        Some(slot) => Some(
+            state
+                .config
+                .read()
+                .await
+                .slot(slot)
+                .cloned()
+                .ok_or_else(|| format!("slot {slot} not found"))?,
+        ),
+        None => None,
+    };
+    let expressions = match (&slot_cfg, expressions.is_empty()) {
+        (Some(cfg), true) => cfg.schedule_cron.clone(),
+        (None, true) => return Err("no cron expressions given".into()),
+        (_, false) => expressions,
+    };
+    let zone = match (timezone, &slot_cfg) {
+        (Some(name), _) => SlotZone::parse(&name)?,
+        (None, Some(cfg)) => SlotZone::for_slot(cfg),
+        (None, None) => SlotZone::Local,
+    };
+    let clock = scheduler_clock(state).await;
+    cron_schedule::next_fire_times_in(&*clock, zone, &expressions, count)
+}
+
+/// Dry-run a slot's schedule over `input`. Uses `slot_config` when given
+/// (e.g. unsaved edits from the settings form), the saved slot otherwise.
+pub async fn simulate_schedule(
+    state: &SharedState,
+    slot: Option<usize>,
+    slot_config: Option<KeySlotConfig>,
+    input: SimulationInput,
+) -> Result<SimulationReport, String> {
+    let app = state.config.read().await.clone();
+    let slot_cfg = match (slot_config, slot) {
+        (Some(cfg), _) => cfg,
+        (None, Some(slot)) => app.slot(slot).cloned().ok_or_else(|| format!("slot {slot} not found"))?,
+        (None, None) => return Err("no slot to simulate".into()),
+    };
+    let slot_cfg = config::validate_slot(&app, slot_cfg);
+    simulation::simulate(&app, slot_cfg, input).await
+}
+
+/// Manual actions read time from the same clock as the slot tasks.
+async fn scheduler_clock(state: &SharedState) -> ClockHandle {
+    state.scheduler.lock().await.clock()
+}
+
+/// Missing bounds default to `now_ms` and `default_span_ms` before `to`.
+fn history_range(now_ms: i64, from_ms: Option<i64>, to_ms: Option<i64>, default_span_ms: i64) -> (i64, i64) {
+    let to_ms = to_ms.unwrap_or(now_ms);
+    (from_ms.unwrap_or(to_ms - default_span_ms), to_ms)
+}
+
+async fn is_slot_quota_full_realtime(
+    client: &api_client::ApiClient,
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    slot_cfg: &KeySlotConfig,
+) -> bool {
+    let now_ms = client.clock().now_ms();
+
+    let cached_reset = {
+        let runtime = runtime_status.read().await;
+        runtime
+            .slot(slot_cfg.slot)
+            .and_then(|slot_status| slot_status.last_updated_epoch_ms)
+    };
+
+    if let Some(next_reset_ms) = cached_reset {
+        // Timer is still running → window IS active → skip warmup
+        if next_reset_ms > now_ms {
+            return true;
+        }
+    }
+
+    // Cache expired or missing — fetch fresh quota to decide
+    match client.fetch_quota(slot_cfg, "warmup-precheck").await {
+        Ok(snapshot) => {
+            if let Some(current) = runtime_status.write().await.slot_mut(slot_cfg.slot) {
+                current.percentage = Some(snapshot.percentage);
+                current.timer_active = snapshot.timer_active;
+                current.next_reset_hms = snapshot.next_reset_hms;
+                current.last_updated_epoch_ms = snapshot.next_reset_epoch_ms;
+            }
+
+            match snapshot.next_reset_epoch_ms {
+                // Timer still active → window IS active → skip warmup
+                Some(next_reset_ms) if next_reset_ms > now_ms => true,
+                // Timer expired or no timer → cold → allow warmup
+                _ => false,
+            }
+        }
+        Err(err) => {
+            warn!(
+                "slot {} quota pre-check failed during warmup: {}",
+                slot_cfg.slot,
+                err
+            );
+            // On error, allow warmup attempt (prefer to try)
+            false
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Cron schedule mode: standard five-field expressions (`minute hour
+//! day-of-month month day-of-week`, plus nicknames like `@daily`) evaluated
+//! in the slot's time zone at minute resolution.
+
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, TimeZone, Timelike};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::slot_zone::SlotZone;
+
+/// Expressions a slot may carry, same cap as `schedule_times`.
+pub const MAX_EXPRESSIONS: usize = 5;
+
+/// Upper bound for one preview request.
+pub const MAX_PREVIEW: usize = 100;
+
+/// One upcoming fire time, as returned by the preview command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronFireTime {
+    pub epoch_ms: i64,
+    /// Wall-clock time in the slot's zone, `YYYY-MM-DD HH:MM (Day)`.
+    pub local: String,
+    /// Expression that produces this time (the first one when several do).
+    pub expression: String,
+}
+
+pub fn parse(expr: &str) -> Result<Cron, String> {
+    Cron::new(expr.trim())
+        .parse()
+        .map_err(|err| format!("invalid cron expression '{}': {err}", expr.trim()))
+}
+
+/// The first expression matching the minute `now` falls in.
+pub fn matching<Z: TimeZone>(expressions: &[String], now: DateTime<Z>) -> Option<&str> {
+    let minute = minute_start(now)?;
+    expressions
+        .iter()
+        .find(|expr| {
+            parse(expr)
+                .and_then(|cron| cron.is_time_matching(&minute).map_err(|err| err.to_string()))
+                .unwrap_or(false)
+        })
+        .map(String::as_str)
+}
+
+/// Dedup key for the minute a cron wake fired in.
+pub fn marker<Z: TimeZone>(now: &DateTime<Z>) -> String
+where
+    Z::Offset: Display,
+{
+    now.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// The next `count` distinct fire times of `expressions` after `from`.
+pub fn next_fire_times<Z: TimeZone>(
+    expressions: &[String],
+    from: DateTime<Z>,
+    count: usize,
+) -> Result<Vec<CronFireTime>, String>
+where
+    Z::Offset: Display,
+{
+    let crons = expressions
+        .iter()
+        .map(|expr| parse(expr).map(|cron| (expr.trim().to_string(), cron)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(start) = minute_start(from) else {
+        return Ok(Vec::new());
+    };
+
+    // Merge the per-expression iterators, keeping the earliest head each time.
+    let mut iters: Vec<_> = crons
+        .iter()
+        .map(|(expr, cron)| (expr, cron.iter_after(start.clone()).peekable()))
+        .collect();
+    let mut out: Vec<CronFireTime> = Vec::new();
+    while out.len() < count.min(MAX_PREVIEW) {
+        let Some((expr, next)) = iters
+            .iter_mut()
+            .filter_map(|(expr, iter)| iter.peek().map(|next| (*expr, next.clone())))
+            .min_by_key(|(_, next)| next.clone())
+        else {
+            break;
+        };
+        for (_, iter) in iters.iter_mut() {
+            if iter.peek() == Some(&next) {
+                iter.next();
+            }
+        }
+        out.push(CronFireTime {
+            epoch_ms: next.timestamp_millis(),
+            local: next.format("%Y-%m-%d %H:%M (%a)").to_string(),
+            expression: expr.clone(),
+        });
+    }
+    Ok(out)
+}
+
+/// `next_fire_times` from `clock`'s now, in `zone`.
+pub fn next_fire_times_in(
+    clock: &dyn Clock,
+    zone: SlotZone,
+    expressions: &[String],
+    count: usize,
+) -> Result<Vec<CronFireTime>, String> {
+    match zone {
+        SlotZone::Local => next_fire_times(expressions, clock.now_local(), count),
+        SlotZone::Iana(tz) => next_fire_times(expressions, clock.now().with_timezone(&tz), count),
+    }
+}
+
+/// Epoch ms of the minutes strictly between `from_ms` and `to_ms` in which
+/// any of `expressions` fires, at most `limit` per expression. Unparsable
+/// expressions are ignored.
+pub fn fires_between(
+    zone: SlotZone,
+    expressions: &[String],
+    from_ms: i64,
+    to_ms: i64,
+    limit: usize,
+) -> Vec<i64> {
+    let Some(from) = DateTime::from_timestamp_millis(from_ms) else {
+        return Vec::new();
+    };
+    match zone {
+        SlotZone::Local => collect_between(expressions, from.with_timezone(&Local), to_ms, limit),
+        SlotZone::Iana(tz) => collect_between(expressions, from.with_timezone(&tz), to_ms, limit),
+    }
+}
+
+fn collect_between<Z: TimeZone>(
+    expressions: &[String],
+    from: DateTime<Z>,
+    to_ms: i64,
+    limit: usize,
+) -> Vec<i64> {
+    let Some(start) = minute_start(from) else {
+        return Vec::new();
+    };
+    let mut fires: Vec<i64> = expressions
+        .iter()
+        .filter_map(|expr| parse(expr).ok())
+        .flat_map(|cron| {
+            cron.iter_after(start.clone())
+                .map(|at| at.timestamp_millis())
+                .take_while(|at| *at < to_ms)
+                .take(limit)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    fires.sort_unstable();
+    fires.dedup();
+    fires
+}
+
+fn minute_start<Z: TimeZone>(now: DateTime<Z>) -> Option<DateTime<Z>> {
+    now.with_second(0)?.with_nanosecond(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use chrono::{TimeZone, Utc};
+
+    fn exprs(list: &[&str]) -> Vec<String> {
+        list.iter().map(|expr| expr.to_string()).collect()
+    }
+
+    /// Monday 2026-03-02 at `h:m:s` UTC.
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, h, m, s).unwrap()
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected_or_skipped() {
+        assert!(parse("*/15 9-17 * * 1-5").is_ok());
+        assert!(parse(" @daily ").is_ok());
+        for expr in ["", "* * *", "61 * * * *", "* * * * 8", "every day"] {
+            assert!(parse(expr).is_err(), "{expr:?} should not parse");
+        }
+        assert!(next_fire_times(&exprs(&["0 9 * * *", "nope"]), at(8, 0, 0), 3).is_err());
+
+        // Matching and catch-up skip what doesn't parse.
+        let mixed = exprs(&["nope", "30 8 * * *"]);
+        assert_eq!(matching(&mixed, at(8, 30, 0)), Some("30 8 * * *"));
+        let zone = SlotZone::parse("UTC").unwrap();
+        let fires = fires_between(zone, &mixed, at(8, 0, 0).timestamp_millis(), at(9, 0, 0).timestamp_millis(), 10);
+        assert_eq!(fires, [at(8, 30, 0).timestamp_millis()]);
+    }
+
+    #[test]
+    fn matching_covers_the_whole_minute_and_marker_dedupes_it() {
+        let list = exprs(&["0 9 * * 1-5", "30 8 * * *"]);
+        assert_eq!(matching(&list, at(8, 30, 0)), Some("30 8 * * *"));
+        assert_eq!(matching(&list, at(8, 30, 59)), Some("30 8 * * *"));
+        assert_eq!(matching(&list, at(8, 31, 0)), None);
+        assert_eq!(matching(&list, at(9, 0, 12)), Some("0 9 * * 1-5"));
+
+        assert_eq!(marker(&at(8, 30, 0)), marker(&at(8, 30, 59)));
+        assert_ne!(marker(&at(8, 30, 59)), marker(&at(8, 31, 0)));
+        assert_eq!(marker(&at(8, 30, 17)), "2026-03-02 08:30");
+    }
+
+    #[test]
+    fn next_fire_times_merge_and_sort_expressions() {
+        let list = exprs(&["0 */2 * * *", "30 9 * * *", "0 10 * * *"]);
+        let fires = next_fire_times(&list, at(8, 15, 0), 4).unwrap();
+        let local: Vec<_> = fires.iter().map(|f| f.local.as_str()).collect();
+        assert_eq!(
+            local,
+            ["2026-03-02 09:30 (Mon)", "2026-03-02 10:00 (Mon)", "2026-03-02 12:00 (Mon)", "2026-03-02 14:00 (Mon)"]
+        );
+        // 10:00 comes from two expressions but is listed once, under the first.
+        assert_eq!(fires[1].expression, "0 */2 * * *");
+        assert!(fires.windows(2).all(|pair| pair[0].epoch_ms < pair[1].epoch_ms));
+        assert_eq!(next_fire_times(&list, at(8, 15, 0), 1_000).unwrap().len(), MAX_PREVIEW);
+    }
+
+    #[test]
+    fn next_fire_times_in_start_from_the_clock() {
+        let clock = FakeClock::new(at(8, 15, 0));
+        let zone = SlotZone::parse("UTC").unwrap();
+        let list = exprs(&["30 9 * * *"]);
+        let fires = next_fire_times_in(&*clock, zone, &list, 1).unwrap();
+        assert_eq!(fires[0].epoch_ms, at(9, 30, 0).timestamp_millis());
+
+        clock.advance(std::time::Duration::from_secs(2 * 3_600));
+        let fires = next_fire_times_in(&*clock, zone, &list, 1).unwrap();
+        assert_eq!(fires[0].local, "2026-03-03 09:30 (Tue)");
+    }
+
+    #[test]
+    fn fires_between_lists_missed_minutes_in_order() {
+        let zone = SlotZone::parse("UTC").unwrap();
+        let list = exprs(&["*/20 * * * *", "0 9 * * *"]);
+        let ms = |h, m| at(h, m, 0).timestamp_millis();
+
+        // The `from` minute itself already had its chance; `to` is exclusive.
+        let fires = fires_between(zone, &list, ms(8, 0) + 30_000, ms(9, 20), 10);
+        assert_eq!(fires, [ms(8, 20), ms(8, 40), ms(9, 0)]);
+        // `limit` caps each expression separately.
+        let fires = fires_between(zone, &list, ms(8, 0), ms(10, 0), 2);
+        assert_eq!(fires, [ms(8, 20), ms(8, 40), ms(9, 0)]);
+        assert!(fires_between(zone, &list, ms(8, 1), ms(8, 20), 10).is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Headless entry point (`glm-trayd`): runs the wake scheduler and quota
+//! poller from the same `settings.json` as the tray app, without a webview.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use log::{error, info, warn};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::host::{self, HeadlessHost, HostHandle};
+use crate::models::{AppConfig, RuntimeStatus};
+use crate::{config, file_logger, has_enabled_slot_with_key, history, ipc, proxy, scheduler, SharedState};
+
+const USAGE: &str = "\
+Usage: glm-trayd [--config-dir <DIR>]
+
+Runs the GLM Tray scheduler headless, using settings.json from the app
+config directory (or $GLM_TRAY_CONFIG_DIR).
+
+Signals:
+  SIGHUP           reload settings.json
+  SIGTERM, SIGINT  stop gracefully";
+
+enum Signal {
+    Reload,
+    Shutdown,
+}
+
+pub fn run() {
+    let _ = env_logger::builder().is_test(false).try_init();
+
+    let config_dir = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(dir)) => dir,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(err) => {
+            eprintln!("glm-trayd: {err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("glm-trayd: failed to start async runtime: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    runtime.block_on(run_async(config_dir));
+}
+
+/// Returns `Ok(None)` when help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<PathBuf>, String> {
+    let mut config_dir = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--config-dir" => {
+                let dir = args.next().ok_or("--config-dir needs a value")?;
+                config_dir = Some(PathBuf::from(dir));
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    match config_dir {
+        Some(dir) => Ok(Some(dir)),
+        None => host::default_config_dir().map(Some),
+    }
+}
+
+async fn run_async(config_dir: PathBuf) {
+    info!("glm-trayd starting (config dir: {})", config_dir.display());
+
+    let shared_config = Arc::new(RwLock::new(AppConfig::default()));
+    let host: HostHandle = Arc::new(HeadlessHost::new(config_dir, shared_config.clone()));
+
+    let initial_config = match config::load_config(&host).await {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to load persisted config, using defaults: {}", err);
+            AppConfig::default()
+        }
+    };
+    *shared_config.write().await = initial_config.clone();
+
+    let state = SharedState {
+        config: shared_config,
+        runtime_status: Arc::new(RwLock::new(RuntimeStatus::for_config(&initial_config))),
+        scheduler: Arc::new(Mutex::new(scheduler::SchedulerManager::new())),
+        proxy: Arc::new(Mutex::new(proxy::ProxyManager::new())),
+        host: host.clone(),
+    };
+
+    let mut signals = match Signals::install() {
+        Ok(signals) => signals,
+        Err(err) => {
+            error!("{err}");
+            return;
+        }
+    };
+
+    file_logger::cleanup_old_logs(&host).await;
+    history::cleanup(&host).await;
+    apply_config(&state, initial_config).await;
+
+    let ipc_state = state.clone();
+    tokio::spawn(async move {
+        if let Err(err) = ipc::serve(ipc_state).await {
+            warn!("control socket unavailable: {}", err);
+        }
+    });
+
+    loop {
+        match signals.next().await {
+            Signal::Reload => {
+                info!("SIGHUP received, reloading settings");
+                match config::load_config(&state.host).await {
+                    Ok(cfg) => {
+                        *state.config.write().await = cfg.clone();
+                        apply_config(&state, cfg).await;
+                    }
+                    Err(err) => warn!("reload failed, keeping current settings: {}", err),
+                }
+            }
+            Signal::Shutdown => {
+                info!("shutdown signal received");
+                break;
+            }
+        }
+    }
+
+    state.proxy.lock().await.stop();
+    state.scheduler.lock().await.stop().await;
+    scheduler::reset_runtime(&state.runtime_status).await;
+    info!("glm-trayd stopped");
+}
+
+/// Start, reload or stop the scheduler and proxy to match `cfg`.
+async fn apply_config(state: &SharedState, cfg: AppConfig) {
+    if let Err(err) = state.proxy.lock().await.apply(state, &cfg).await {
+        warn!("{}", err);
+    }
+
+    let mut scheduler = state.scheduler.lock().await;
+    let ready = has_enabled_slot_with_key(&cfg);
+
+    if scheduler.is_running() && ready {
+        scheduler
+            .reload_if_running(state.host.clone(), cfg, state.runtime_status.clone())
+            .await;
+    } else if ready {
+        let enabled = cfg
+            .slots
+            .iter()
+            .filter(|s| s.enabled && !s.api_key.trim().is_empty())
+            .count();
+        info!("monitoring {enabled} enabled slot(s)");
+        scheduler
+            .start(state.host.clone(), cfg, state.runtime_status.clone())
+            .await;
+    } else {
+        warn!("no enabled slots with an API key; waiting for SIGHUP");
+        scheduler.stop().await;
+        scheduler::reset_runtime(&state.runtime_status).await;
+        state.runtime_status.write().await.sync_slots(&cfg);
+    }
+}
+
+/// Signal handlers, installed once so no signal is missed between waits.
+struct Signals {
+    #[cfg(unix)]
+    hup: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    term: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    int: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    #[cfg(unix)]
+    fn install() -> Result<Self, String> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let install = |kind: SignalKind| {
+            signal(kind).map_err(|err| format!("failed to install signal handler: {err}"))
+        };
+        Ok(Self {
+            hup: install(SignalKind::hangup())?,
+            term: install(SignalKind::terminate())?,
+            int: install(SignalKind::interrupt())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn install() -> Result<Self, String> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    async fn next(&mut self) -> Signal {
+        tokio::select! {
+            _ = self.hup.recv() => Signal::Reload,
+            _ = self.term.recv() => Signal::Shutdown,
+            _ = self.int.recv() => Signal::Shutdown,
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn next(&mut self) -> Signal {
+        let _ = tokio::signal::ctrl_c().await;
+        Signal::Shutdown
+    }
+}
@@ -1,21 +1,49 @@
-use chrono::{Local, NaiveDateTime, TimeZone};
-use log::info;
-use serde::Serialize;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, Mutex};
+
+use crate::host::HostHandle;
+use crate::models::{AppConfig, LogRedaction};
+use crate::redact::Redactor;
 
 /// Default number of days to keep log files.
 const DEFAULT_MAX_LOG_DAYS: i64 = 7;
+const MB: u64 = 1024 * 1024;
+
+/// Held while writing or rotating, so concurrent appends agree on the
+/// active file and cleanup never compresses it under them. Holds the active
+/// file once known, so appends need no directory listing.
+static WRITER: Mutex<Option<ActiveFile>> = Mutex::const_new(None);
 
 struct LoggerConfig {
     dir: PathBuf,
     max_days: i64,
+    max_file_bytes: u64,
+    max_total_bytes: u64,
+    compress: bool,
+    redactor: Option<Arc<Redactor>>,
+}
+
+/// The file appends currently go to, and the bytes written to it so far.
+struct ActiveFile {
+    dir: PathBuf,
+    date: NaiveDate,
+    part: u32,
+    len: u64,
 }
 
 /// A single JSONL log entry written to the daily log file.
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub ts: String,
     pub slot: usize,
@@ -41,19 +69,25 @@ pub struct LogEntry {
 }
 
 /// Returns effective logger config using user overrides from config.
-async fn logger_config(app: &tauri::AppHandle) -> Result<LoggerConfig, String> {
-    use tauri::Manager;
-    let mut dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("failed to resolve app config dir: {e}"))?;
+async fn logger_config(host: &HostHandle) -> Result<LoggerConfig, String> {
+    let mut dir = host.config_dir()?;
+    let defaults = AppConfig::default();
     let mut max_days = DEFAULT_MAX_LOG_DAYS;
+    let mut max_file_mb = defaults.max_log_file_mb;
+    let mut max_total_mb = defaults.max_log_total_mb;
+    let mut compress = defaults.compress_logs;
+    // Without a config the built-in rules still apply.
+    let mut redactor = Some(Redactor::shared(&LogRedaction::default(), Vec::new()));
 
-    if let Some(state) = app.try_state::<crate::SharedState>() {
-        let cfg = state.config.read().await;
+    if let Some(config) = host.shared_config() {
+        let cfg = config.read().await;
         if cfg.max_log_days > 0 {
             max_days = cfg.max_log_days as i64;
         }
+        max_file_mb = cfg.max_log_file_mb.max(1);
+        max_total_mb = cfg.max_log_total_mb.max(max_file_mb);
+        compress = cfg.compress_logs;
+        redactor = Redactor::for_config(&cfg);
     }
 
     if max_days <= 0 {
@@ -61,62 +95,105 @@ async fn logger_config(app: &tauri::AppHandle) -> Result<LoggerConfig, String> {
     }
 
     dir.push("logs");
-    Ok(LoggerConfig { dir, max_days })
+    Ok(LoggerConfig {
+        dir,
+        max_days,
+        max_file_bytes: max_file_mb * MB,
+        max_total_bytes: max_total_mb * MB,
+        compress,
+        redactor,
+    })
 }
 
-/// Deletes log files older than configured retention.
-pub async fn cleanup_old_logs(app: &tauri::AppHandle) {
-    let config = match logger_config(app).await {
+/// Deletes log files older than configured retention, compresses closed
+/// files and trims the logs to the disk budget.
+pub async fn cleanup_old_logs(host: &HostHandle) {
+    let config = match logger_config(host).await {
         Ok(d) => d,
         Err(_) => return,
     };
-    let dir = config.dir;
-    let max_days = config.max_days;
-    if max_days <= 0 {
-        return;
-    }
-
-    let mut entries = match fs::read_dir(&dir).await {
-        Ok(e) => e,
-        Err(_) => return,
-    };
+    let _guard = WRITER.lock().await;
+    cleanup(&config).await;
+}
 
-    let cutoff = Local::now() - chrono::Duration::days(max_days);
+async fn cleanup(config: &LoggerConfig) {
+    let cutoff = Local::now() - chrono::Duration::days(config.max_days);
+    let mut kept = Vec::new();
+    for file in log_files(&config.dir).await {
+        let expired = Local
+            .from_local_datetime(&file.date.and_time(NaiveTime::MIN))
+            .single()
+            .is_some_and(|file_date| file_date < cutoff);
+        if expired {
+            remove_log_file(&file.path, "old").await;
+        } else {
+            kept.push(file);
+        }
+    }
 
-    while let Ok(Some(entry)) = entries.next_entry().await {
-        let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "jsonl") {
-            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                if let Ok(naive) = NaiveDateTime::parse_from_str(&format!("{stem} 00:00:00"), "%Y-%m-%d %H:%M:%S") {
-                    if let Some(file_date) = Local.from_local_datetime(&naive).single() {
-                        if file_date < cutoff {
-                            match fs::remove_file(&path).await {
-                                Ok(()) => info!("deleted old log file: {}", path.display()),
-                                Err(e) => info!("failed to delete old log {}: {}", path.display(), e),
-                            }
-                        }
-                    }
+    // Everything but today's newest file is closed.
+    let today = Local::now().date_naive();
+    let active = kept.last().filter(|file| file.date == today).map(|file| file.path.clone());
+    if config.compress {
+        for file in kept.iter_mut().filter(|file| !file.compressed && Some(&file.path) != active.as_ref()) {
+            match compress_log_file(&file.path).await {
+                Ok(path) => {
+                    file.path = path;
+                    file.compressed = true;
                 }
+                Err(e) => warn!("failed to compress log {}: {e}", file.path.display()),
             }
         }
     }
+
+    let mut sized = Vec::new();
+    for file in kept {
+        let len = fs::metadata(&file.path).await.map(|meta| meta.len()).unwrap_or(0);
+        sized.push((file.path, len));
+    }
+    let mut total: u64 = sized.iter().map(|(_, len)| len).sum();
+    for (path, len) in sized {
+        if total <= config.max_total_bytes {
+            break;
+        }
+        if Some(&path) == active.as_ref() {
+            continue;
+        }
+        remove_log_file(&path, "over-budget").await;
+        total = total.saturating_sub(len);
+    }
 }
 
-/// Appends a `LogEntry` as one JSONL line to `logs/YYYY-MM-DD.jsonl`.
-pub async fn append(app: &tauri::AppHandle, entry: LogEntry) -> Result<(), String> {
-    let config = logger_config(app).await?;
-    let dir = config.dir;
-    fs::create_dir_all(&dir)
-        .await
-        .map_err(|e| format!("create log dir: {e}"))?;
+async fn remove_log_file(path: &Path, reason: &str) {
+    match fs::remove_file(path).await {
+        Ok(()) => info!("deleted {reason} log file: {}", path.display()),
+        Err(e) => info!("failed to delete {reason} log {}: {}", path.display(), e),
+    }
+}
 
-    let date = Local::now().format("%Y-%m-%d").to_string();
-    let path = dir.join(format!("{date}.jsonl"));
+/// Appends a `LogEntry` as one JSONL line to today's log file, after
+/// redacting secrets from it. A file that would grow past the size cap is
+/// closed and the day continues in `YYYY-MM-DD.N.jsonl`. Starting a file,
+/// either way, runs the cleanup.
+pub async fn append(host: &HostHandle, entry: LogEntry) -> Result<(), String> {
+    let config = logger_config(host).await?;
+    append_to(&config, entry).await
+}
 
+async fn append_to(config: &LoggerConfig, entry: LogEntry) -> Result<(), String> {
+    let entry = match &config.redactor {
+        Some(redactor) => redacted(redactor, entry)?,
+        None => entry,
+    };
     let mut line =
         serde_json::to_string(&entry).map_err(|e| format!("serialize log entry: {e}"))?;
     line.push('\n');
 
+    let mut writer = WRITER.lock().await;
+    let (mut active, started) = next_active_file(config, writer.take(), line.len() as u64).await?;
+    let path = LogFile::path_in(&config.dir, active.date, active.part);
+
+    // On an error the active file is looked up again on the next append.
     let mut file = fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -127,10 +204,295 @@ pub async fn append(app: &tauri::AppHandle, entry: LogEntry) -> Result<(), Strin
     file.write_all(line.as_bytes())
         .await
         .map_err(|e| format!("write log entry: {e}"))?;
+    // Tokio finishes writes in the background; readers expect this one on disk.
+    file.flush()
+        .await
+        .map_err(|e| format!("write log entry: {e}"))?;
+    active.len += line.len() as u64;
+    *writer = Some(active);
 
+    // The file this one replaces is closed now; a new day also ages the others.
+    if started {
+        cleanup(config).await;
+    }
+
+    // Nobody listening is not an error.
+    let _ = live_entries().send(entry);
     Ok(())
 }
 
+/// The file to append `incoming` bytes to, and whether it is a new file,
+/// closing the previous one: the next part or a new day's first file.
+/// Only an unknown or stale `active` lists the logs dir.
+async fn next_active_file(
+    config: &LoggerConfig,
+    active: Option<ActiveFile>,
+    incoming: u64,
+) -> Result<(ActiveFile, bool), String> {
+    let today = Local::now().date_naive();
+    let (mut active, started) = match active {
+        Some(active) if active.dir == config.dir && active.date == today => (active, false),
+        _ => find_active_file(&config.dir, today).await?,
+    };
+    // A single oversized entry still gets written, to a file of its own.
+    if active.len == 0 || active.len + incoming <= config.max_file_bytes {
+        return Ok((active, started));
+    }
+    active.part += 1;
+    active.len = 0;
+    Ok((active, true))
+}
+
+/// Today's newest file in `dir`, or the next part if it was compressed,
+/// and whether that file has yet to be started.
+async fn find_active_file(dir: &Path, today: NaiveDate) -> Result<(ActiveFile, bool), String> {
+    fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("create log dir: {e}"))?;
+    let mut active = ActiveFile {
+        dir: dir.to_path_buf(),
+        date: today,
+        part: 0,
+        len: 0,
+    };
+    let Some(latest) = log_files(dir).await.into_iter().rfind(|file| file.date == today) else {
+        return Ok((active, true));
+    };
+    active.part = latest.part;
+    if latest.compressed {
+        active.part += 1;
+        return Ok((active, true));
+    }
+    active.len = fs::metadata(&latest.path).await.map(|meta| meta.len()).unwrap_or(0);
+    Ok((active, false))
+}
+
+/// `entry` with every redaction rule applied; the round trip through a
+/// `Value` keeps the field order of the written line.
+fn redacted(redactor: &Redactor, entry: LogEntry) -> Result<LogEntry, String> {
+    let mut value = serde_json::to_value(entry).map_err(|e| format!("serialize log entry: {e}"))?;
+    redactor.redact(&mut value);
+    serde_json::from_value(value).map_err(|e| format!("redact log entry: {e}"))
+}
+
+/// Returns the last `lines` log entries, oldest first.
+pub async fn tail(host: &HostHandle, lines: usize) -> Result<Vec<Value>, String> {
+    let dir = logger_config(host).await?.dir;
+    let mut newest_first = Vec::new();
+    for file in log_files(&dir).await.into_iter().rev() {
+        if newest_first.len() >= lines {
+            break;
+        }
+        let content = read_log_file(&file).await?;
+        newest_first.extend(
+            content
+                .lines()
+                .rev()
+                .filter(|l| !l.trim().is_empty())
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+                .take(lines - newest_first.len()),
+        );
+    }
+    newest_first.reverse();
+    Ok(newest_first)
+}
+
+/// Most entries one `query` page returns.
+const MAX_QUERY_LIMIT: usize = 1_000;
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Log filters; unset fields match every entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    pub from_ms: Option<i64>,
+    pub to_ms: Option<i64>,
+    pub slot: Option<usize>,
+    /// Exact action, or a prefix ending in `*` such as `wake.*`.
+    pub action: Option<String>,
+    pub phase: Option<String>,
+    pub flow_id: Option<String>,
+    /// Matching entries to skip, newest first.
+    pub offset: usize,
+    /// Page size; 100 when unset, at most 1000.
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    /// Whether `entry` passes every filter but the date range.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.slot.is_none_or(|slot| entry.slot == slot)
+            && self.action.as_deref().is_none_or(|pattern| action_matches(pattern, &entry.action))
+            && self.phase.as_deref().is_none_or(|phase| entry.phase.as_deref() == Some(phase))
+            && self.flow_id.as_deref().is_none_or(|flow| entry.flow_id.as_deref() == Some(flow))
+    }
+
+    fn in_range(&self, ts_ms: i64) -> bool {
+        self.from_ms.is_none_or(|from| ts_ms >= from) && self.to_ms.is_none_or(|to| ts_ms <= to)
+    }
+
+    /// Whether a file holding `date`'s entries can contain any in range.
+    fn covers_date(&self, date: NaiveDate) -> bool {
+        let local_date = |ms: i64| Local.timestamp_millis_opt(ms).single().map(|ts| ts.date_naive());
+        self.from_ms.and_then(local_date).is_none_or(|from| date >= from)
+            && self.to_ms.and_then(local_date).is_none_or(|to| date <= to)
+    }
+}
+
+fn action_matches(pattern: &str, action: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => action.starts_with(prefix),
+        None => action == pattern,
+    }
+}
+
+/// One page of `query` results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    /// Matching entries, newest first.
+    pub entries: Vec<LogEntry>,
+    /// Matches across all pages.
+    pub total: usize,
+    /// Offset of the next page, if there is one.
+    pub next_offset: Option<usize>,
+}
+
+/// Entries matching `query` across the daily log files, newest first.
+pub async fn query(host: &HostHandle, query: &LogQuery) -> Result<LogPage, String> {
+    let dir = logger_config(host).await?.dir;
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT).clamp(1, MAX_QUERY_LIMIT);
+    let mut total = 0;
+    let mut entries = Vec::new();
+
+    for file in log_files(&dir).await.into_iter().rev() {
+        if !query.covers_date(file.date) {
+            continue;
+        }
+        let content = read_log_file(&file).await?;
+        for line in content.lines().rev() {
+            let Ok(entry) = serde_json::from_str::<LogEntry>(line) else {
+                continue;
+            };
+            let in_range = DateTime::parse_from_rfc3339(&entry.ts)
+                .is_ok_and(|ts| query.in_range(ts.timestamp_millis()));
+            if !in_range || !query.matches(&entry) {
+                continue;
+            }
+            if total >= query.offset && entries.len() < limit {
+                entries.push(entry);
+            }
+            total += 1;
+        }
+    }
+
+    let next = query.offset + entries.len();
+    Ok(LogPage {
+        entries,
+        total,
+        next_offset: (next < total).then_some(next),
+    })
+}
+
+/// New entries as they are appended, for live tails. Lagging receivers
+/// miss entries rather than hold up logging.
+pub fn subscribe() -> broadcast::Receiver<LogEntry> {
+    live_entries().subscribe()
+}
+
+fn live_entries() -> &'static broadcast::Sender<LogEntry> {
+    static LIVE: OnceLock<broadcast::Sender<LogEntry>> = OnceLock::new();
+    LIVE.get_or_init(|| broadcast::channel(256).0)
+}
+
+/// A file in the logs dir: a day's `YYYY-MM-DD.jsonl`, its rollovers
+/// `YYYY-MM-DD.N.jsonl`, or either gzipped to `.jsonl.gz`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct LogFile {
+    date: NaiveDate,
+    /// 0 for the day's first file, N for `YYYY-MM-DD.N.jsonl`.
+    part: u32,
+    compressed: bool,
+    path: PathBuf,
+}
+
+impl LogFile {
+    fn parse(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let (name, compressed) = match name.strip_suffix(".gz") {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+        let stem = name.strip_suffix(".jsonl")?;
+        let (date, part) = match stem.split_once('.') {
+            Some((date, part)) => (date, part.parse().ok()?),
+            None => (stem, 0),
+        };
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        Some(Self { date, part, compressed, path })
+    }
+
+    fn path_in(dir: &Path, date: NaiveDate, part: u32) -> PathBuf {
+        let date = date.format("%Y-%m-%d");
+        match part {
+            0 => dir.join(format!("{date}.jsonl")),
+            part => dir.join(format!("{date}.{part}.jsonl")),
+        }
+    }
+}
+
+/// Log files in `dir`, oldest first.
+async fn log_files(dir: &Path) -> Vec<LogFile> {
+    let mut files = Vec::new();
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return files;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Some(file) = LogFile::parse(entry.path()) {
+            files.push(file);
+        }
+    }
+    files.sort();
+    // Compression stopped before removing the original: both hold the
+    // same lines, so keep the plain file and compress it again later.
+    files.dedup_by(|later, earlier| later.date == earlier.date && later.part == earlier.part);
+    files
+}
+
+async fn read_log_file(file: &LogFile) -> Result<String, String> {
+    let path = file.path.clone();
+    let content = if file.compressed {
+        tokio::task::spawn_blocking(move || {
+            let mut content = String::new();
+            GzDecoder::new(std::fs::File::open(&path)?).read_to_string(&mut content)?;
+            Ok::<_, std::io::Error>(content)
+        })
+        .await
+        .map_err(|e| format!("read log file {}: {e}", file.path.display()))?
+    } else {
+        fs::read_to_string(&path).await
+    };
+    content.map_err(|e| format!("read log file {}: {e}", file.path.display()))
+}
+
+/// Gzips a closed log file to `<name>.gz` and removes the original.
+async fn compress_log_file(path: &Path) -> Result<PathBuf, String> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let target = path.with_extension("jsonl.gz");
+        let partial = path.with_extension("jsonl.gz.partial");
+        let mut encoder = GzEncoder::new(std::fs::File::create(&partial)?, Compression::default());
+        std::io::copy(&mut std::fs::File::open(&path)?, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        std::fs::rename(&partial, &target)?;
+        std::fs::remove_file(&path)?;
+        info!("compressed log file: {}", target.display());
+        Ok::<_, std::io::Error>(target)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
 fn request_entry_internal(
     slot: usize,
     action: &str,
@@ -163,18 +525,6 @@ fn request_entry_internal(
 }
 
 
-/// Convenience: build a LogEntry for a response.
-pub fn response_entry(
-    slot: usize,
-    action: &str,
-    method: &str,
-    url: &str,
-    status: u16,
-    body: Option<serde_json::Value>,
-) -> LogEntry {
-    response_entry_internal(slot, action, method, url, status, body, None, None)
-}
-
 /// Convenience: build a LogEntry for a response with timing metadata and flow id.
 pub fn response_entry_with_timing_and_id(
     slot: usize,
@@ -198,13 +548,14 @@ pub fn response_entry_with_timing_and_id(
     )
 }
 
-/// Convenience: build a LogEntry for an error.
-pub fn error_entry(
+/// Convenience: build a LogEntry for an error with details and flow id.
+pub fn error_entry_with_id(
     slot: usize,
     action: &str,
     method: &str,
     url: &str,
     error: &str,
+    flow_id: String,
 ) -> LogEntry {
     request_entry_internal(
         slot,
@@ -217,20 +568,26 @@ pub fn error_entry(
         Some(error.to_string()),
         None,
         None,
-        None,
+        Some(flow_id),
         Some("error".to_string()),
     )
 }
 
-/// Convenience: build a LogEntry for an error with details and flow id.
-pub fn error_entry_with_id(
+/// Convenience: build a LogEntry for a failed attempt that is about to be
+/// retried; `details.status` is the HTTP status it got, if any.
+pub fn retry_entry(
     slot: usize,
     action: &str,
     method: &str,
     url: &str,
     error: &str,
+    details: Value,
     flow_id: String,
 ) -> LogEntry {
+    let status = details
+        .get("status")
+        .and_then(Value::as_u64)
+        .and_then(|status| u16::try_from(status).ok());
     request_entry_internal(
         slot,
         action,
@@ -238,12 +595,12 @@ pub fn error_entry_with_id(
         url,
         None,
         None,
-        None,
+        status,
         Some(error.to_string()),
-        None,
+        Some(details),
         None,
         Some(flow_id),
-        Some("error".to_string()),
+        Some("retry".to_string()),
     )
 }
 
@@ -269,6 +626,32 @@ pub fn event_entry(
     )
 }
 
+/// Convenience: build a LogEntry for one webhook delivery attempt.
+pub fn webhook_entry(
+    slot: usize,
+    url: &str,
+    status: Option<u16>,
+    error: Option<String>,
+    details: Value,
+    duration_ms: u64,
+    delivery_id: String,
+) -> LogEntry {
+    request_entry_internal(
+        slot,
+        "webhook.delivery",
+        "POST",
+        url,
+        None,
+        None,
+        status,
+        error,
+        Some(details),
+        Some(duration_ms),
+        Some(delivery_id),
+        Some("webhook".to_string()),
+    )
+}
+
 fn response_entry_internal(
     slot: usize,
     action: &str,
@@ -294,3 +677,213 @@ fn response_entry_internal(
         Some("response".to_string()),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+    use tokio::sync::RwLock;
+
+    use super::*;
+    use crate::host::HeadlessHost;
+    use crate::models::{AppConfig, KeySlotConfig};
+
+    #[tokio::test]
+    async fn configured_keys_never_reach_the_log_file() {
+        let key = "0123456789abcdef0123456789abcdef.ABCDEFGHijklmnop";
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let config = AppConfig {
+            slots: vec![KeySlotConfig {
+                slot: 1,
+                api_key: format!("Bearer {key}"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.clone(), Arc::new(RwLock::new(config))));
+
+        let entries = [
+            error_entry_with_id(1, "quota", "GET", &format!("https://example.com/quota?key={key}"), &format!("401 for {key}"), "f1".into()),
+            event_entry(1, "ui.save-key-settings", Some(json!({ "form": { "api_key": key, "note": format!("pasted Bearer {key}") } }))),
+            response_entry_with_timing_and_id(1, "wake", "POST", "https://example.com", 200, Some(json!({ "echo": [key] })), 5, "f2".into()),
+        ];
+        for entry in entries {
+            append(&host, entry).await.unwrap();
+        }
+
+        let written = tail(&host, 10).await.unwrap();
+        assert_eq!(written.len(), 3);
+        let text = serde_json::to_string(&written).unwrap();
+        assert!(!text.contains(key) && !text.contains("ABCDEFGHijklmnop"), "{text}");
+        assert_eq!(written[1]["details"]["form"]["api_key"], "[REDACTED]");
+        assert_eq!(written[2]["status"], 200);
+
+        host.shared_config().unwrap().write().await.log_redaction.enabled = false;
+        append(&host, event_entry(1, "ui.debug", Some(json!({ "raw": key })))).await.unwrap();
+        assert_eq!(tail(&host, 1).await.unwrap()[0]["details"]["raw"], key);
+    }
+
+    /// RFC 3339 timestamp of a local time on March 2026, matching the daily file names.
+    fn at(day: u32, hour: u32, min: u32, sec: u32) -> String {
+        Local.with_ymd_and_hms(2026, 3, day, hour, min, sec).unwrap().to_rfc3339()
+    }
+
+    fn logged(ts: &str, slot: usize, action: &str, phase: &str, flow_id: &str) -> String {
+        let entry = LogEntry {
+            ts: ts.to_string(),
+            flow_id: Some(flow_id.to_string()),
+            phase: Some(phase.to_string()),
+            ..event_entry(slot, action, None)
+        };
+        serde_json::to_string(&entry).unwrap()
+    }
+
+    #[tokio::test]
+    async fn queries_filter_and_page_across_days() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join("logs")).unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.clone(), Arc::default()));
+        let day = |date: &str, lines: &[String]| std::fs::write(dir.join("logs").join(format!("{date}.jsonl")), lines.join("\n")).unwrap();
+        day("2026-03-01", &[
+            logged(&at(1, 10, 0, 0), 3, "wake.request", "request", "f1"),
+            logged(&at(1, 10, 0, 1), 3, "wake.request", "response", "f1"),
+            logged(&at(1, 10, 5, 0), 1, "quota", "response", "f2"),
+        ]);
+        day("2026-03-02", &[
+            logged(&at(2, 9, 0, 0), 3, "wake.scheduler.fired", "event", "f3"),
+            "not json".to_string(),
+            logged(&at(2, 9, 0, 2), 3, "quota", "error", "f4"),
+        ]);
+
+        let wakes = LogQuery { slot: Some(3), action: Some("wake.*".into()), limit: Some(2), ..Default::default() };
+        let page = query(&host, &wakes).await.unwrap();
+        let flows: Vec<_> = page.entries.iter().map(|e| e.flow_id.clone().unwrap()).collect();
+        assert_eq!((flows, page.total, page.next_offset), (vec!["f3".to_string(), "f1".to_string()], 3, Some(2)));
+        let rest = query(&host, &LogQuery { offset: 2, ..wakes }).await.unwrap();
+        assert_eq!((rest.entries.len(), rest.next_offset), (1, None));
+
+        let flow = query(&host, &LogQuery { flow_id: Some("f1".into()), ..Default::default() }).await.unwrap();
+        let phases: Vec<_> = flow.entries.iter().rev().map(|e| e.phase.clone().unwrap()).collect();
+        assert_eq!(phases, ["request", "response"]);
+
+        let ms = |ts: String| DateTime::parse_from_rfc3339(&ts).unwrap().timestamp_millis();
+        let (from_ms, to_ms) = (ms(at(1, 10, 4, 0)), ms(at(2, 9, 0, 1)));
+        let ranged = query(&host, &LogQuery { from_ms: Some(from_ms), to_ms: Some(to_ms), ..Default::default() }).await.unwrap();
+        let actions: Vec<_> = ranged.entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["wake.scheduler.fired", "quota"]);
+        assert!(!LogQuery { action: Some("wake".into()), ..Default::default() }.matches(&ranged.entries[0]));
+
+        let mut live = subscribe();
+        append(&host, event_entry(5, "wake.live", None)).await.unwrap();
+        loop {
+            let entry = live.recv().await.unwrap();
+            if entry.slot == 5 {
+                assert_eq!(entry.action, "wake.live");
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn a_known_active_file_rolls_over_without_listing_the_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("logs");
+        let config = LoggerConfig {
+            dir: dir.clone(),
+            max_days: 7,
+            max_file_bytes: 400,
+            max_total_bytes: 100 * MB,
+            compress: true,
+            redactor: None,
+        };
+        let known = |len| ActiveFile { dir: dir.clone(), date: Local::now().date_naive(), part: 2, len };
+
+        let (active, started) = next_active_file(&config, Some(known(300)), 100).await.unwrap();
+        assert_eq!((active.part, active.len, started), (2, 300, false));
+        let (active, started) = next_active_file(&config, Some(known(300)), 101).await.unwrap();
+        assert_eq!((active.part, active.len, started), (3, 0, true));
+        assert!(!dir.exists());
+
+        // An unknown active file is looked up, creating the dir.
+        let (active, started) = next_active_file(&config, None, 101).await.unwrap();
+        assert_eq!((active.part, active.len, started), (0, 0, true));
+        assert!(dir.exists());
+    }
+
+    #[tokio::test]
+    async fn a_new_days_file_compresses_yesterdays() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let yesterday = Local::now().date_naive() - chrono::Duration::days(1);
+        let line = serde_json::to_string(&event_entry(1, "e-yesterday", None)).unwrap();
+        std::fs::write(LogFile::path_in(&dir, yesterday, 0), &line).unwrap();
+        let config = LoggerConfig {
+            dir: dir.clone(),
+            max_days: 7,
+            max_file_bytes: 100 * MB,
+            max_total_bytes: 100 * MB,
+            compress: true,
+            redactor: None,
+        };
+
+        // Yesterday's file was still the active one when the day changed.
+        *WRITER.lock().await = Some(ActiveFile { dir: dir.clone(), date: yesterday, part: 0, len: line.len() as u64 });
+        append_to(&config, event_entry(1, "e-today", None)).await.unwrap();
+
+        let files = log_files(&dir).await;
+        assert_eq!(files.len(), 2, "{files:?}");
+        assert!(files[0].date == yesterday && files[0].compressed, "{files:?}");
+        assert!(!files[1].compressed, "{files:?}");
+    }
+
+    #[tokio::test]
+    async fn full_files_roll_over_compress_and_stay_within_budget() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let logs = dir.join("logs");
+        std::fs::create_dir_all(&logs).unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.clone(), Arc::default()));
+        let yesterday = Local::now().date_naive() - chrono::Duration::days(1);
+        let earlier = LogEntry {
+            ts: (Local::now() - chrono::Duration::days(1)).to_rfc3339(),
+            ..event_entry(1, "e-yesterday", None)
+        };
+        std::fs::write(LogFile::path_in(&logs, yesterday, 0), serde_json::to_string(&earlier).unwrap()).unwrap();
+
+        let mut config = LoggerConfig {
+            dir: logs.clone(),
+            max_days: 7,
+            max_file_bytes: 400,
+            max_total_bytes: 100 * MB,
+            compress: true,
+            redactor: None,
+        };
+        for i in 0..6 {
+            append_to(&config, event_entry(1, &format!("e{i}"), Some(json!({ "pad": "x".repeat(150) })))).await.unwrap();
+        }
+
+        let files = log_files(&logs).await;
+        let (closed, active) = files.split_at(files.len() - 1);
+        assert!(closed.len() >= 3, "{files:?}");
+        assert!(closed.iter().all(|file| file.compressed) && !active[0].compressed, "{files:?}");
+        assert_eq!(active[0].part, closed.last().unwrap().part + 1);
+        assert!(!LogFile::path_in(&logs, yesterday, 0).exists());
+
+        let page = query(&host, &LogQuery::default()).await.unwrap();
+        let actions: Vec<_> = page.entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["e5", "e4", "e3", "e2", "e1", "e0", "e-yesterday"]);
+        let tailed = tail(&host, 4).await.unwrap();
+        let actions: Vec<_> = tailed.iter().map(|e| e["action"].as_str().unwrap()).collect();
+        assert_eq!(actions, ["e2", "e3", "e4", "e5"]);
+
+        config.max_total_bytes = 600;
+        cleanup(&config).await;
+        let remaining = log_files(&logs).await;
+        let total: u64 = remaining.iter().map(|file| std::fs::metadata(&file.path).unwrap().len()).sum();
+        assert!(total <= 600 && remaining.len() < files.len(), "{remaining:?}");
+        assert_eq!(remaining, files[files.len() - remaining.len()..]);
+    }
+}
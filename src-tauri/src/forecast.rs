@@ -0,0 +1,195 @@
+//! Quota exhaustion forecast: estimates how fast a slot burns its
+//! `TOKENS_LIMIT` window and whether it hits 100% before the next reset.
+
+use std::collections::VecDeque;
+
+use crate::models::QuotaForecast;
+
+const HOUR_MS: f64 = 3_600_000.0;
+
+/// Length of a `TOKENS_LIMIT` window.
+const WINDOW_MS: i64 = 5 * 3_600_000;
+
+/// Polls older than this are ignored for the trend, so the rate follows
+/// recent activity instead of the whole window.
+const TREND_LOOKBACK_MS: i64 = 60 * 60_000;
+
+/// Minimum time span before a rate is trusted.
+const MIN_SPAN_MS: i64 = 5 * 60_000;
+
+/// Reset times that move by less than this belong to the same window.
+pub(crate) const RESET_JITTER_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    ts: i64,
+    percentage: f64,
+    tokens: f64,
+}
+
+/// Recent observations for the slot's current window. Lives in the quota
+/// poller task and starts over whenever the reset time moves.
+#[derive(Debug, Default)]
+pub struct BurnTracker {
+    reset_epoch_ms: Option<i64>,
+    points: VecDeque<Point>,
+}
+
+impl BurnTracker {
+    /// Record a poll and return the updated forecast, or `None` while no
+    /// window is running or there is too little data for an estimate.
+    pub fn observe(
+        &mut self,
+        now_ms: i64,
+        percentage: u8,
+        tokens_5h: u64,
+        next_reset_epoch_ms: Option<i64>,
+    ) -> Option<QuotaForecast> {
+        let Some(reset_ms) = next_reset_epoch_ms.filter(|reset| *reset > now_ms) else {
+            self.reset_epoch_ms = None;
+            self.points.clear();
+            return None;
+        };
+        let same_window = self
+            .reset_epoch_ms
+            .is_some_and(|known| (known - reset_ms).abs() < RESET_JITTER_MS);
+        if !same_window {
+            self.points.clear();
+        }
+        self.reset_epoch_ms = Some(reset_ms);
+
+        self.points.push_back(Point {
+            ts: now_ms,
+            percentage: f64::from(percentage),
+            tokens: tokens_5h as f64,
+        });
+        while self
+            .points
+            .front()
+            .is_some_and(|p| now_ms - p.ts > TREND_LOOKBACK_MS)
+        {
+            self.points.pop_front();
+        }
+
+        let current = f64::from(percentage);
+        let (pct_rate, tokens_rate, basis) = self
+            .trend()
+            .map(|(pct, tokens)| (pct, tokens, "trend"))
+            .or_else(|| {
+                let elapsed = now_ms - (reset_ms - WINDOW_MS);
+                (elapsed >= MIN_SPAN_MS).then(|| {
+                    let hours = elapsed as f64 / HOUR_MS;
+                    (current / hours, tokens_5h as f64 / hours, "window-average")
+                })
+            })?;
+        let pct_rate = pct_rate.max(0.0);
+
+        let hours_to_reset = (reset_ms - now_ms) as f64 / HOUR_MS;
+        let exhausts_at_epoch_ms = if current >= 100.0 {
+            Some(now_ms)
+        } else if pct_rate > 0.0 {
+            let at = now_ms + ((100.0 - current) / pct_rate * HOUR_MS) as i64;
+            (at < reset_ms).then_some(at)
+        } else {
+            None
+        };
+
+        Some(QuotaForecast {
+            burn_rate_pct_per_hour: round2(pct_rate),
+            tokens_per_hour: tokens_rate.max(0.0).round(),
+            will_exhaust: exhausts_at_epoch_ms.is_some(),
+            exhausts_at_epoch_ms,
+            projected_percentage_at_reset: round2((current + pct_rate * hours_to_reset).min(100.0)),
+            basis: basis.to_string(),
+            samples: self.points.len(),
+            computed_at_epoch_ms: now_ms,
+        })
+    }
+
+    /// Least-squares slopes (per hour) of percentage and tokens over the
+    /// retained polls.
+    fn trend(&self) -> Option<(f64, f64)> {
+        let first = self.points.front()?;
+        let last = self.points.back()?;
+        if self.points.len() < 2 || last.ts - first.ts < MIN_SPAN_MS {
+            return None;
+        }
+        let n = self.points.len() as f64;
+        let hours = |p: &Point| (p.ts - first.ts) as f64 / HOUR_MS;
+        let mean_t = self.points.iter().map(hours).sum::<f64>() / n;
+        let mean_pct = self.points.iter().map(|p| p.percentage).sum::<f64>() / n;
+        let mean_tokens = self.points.iter().map(|p| p.tokens).sum::<f64>() / n;
+
+        let mut var_t = 0.0;
+        let mut cov_pct = 0.0;
+        let mut cov_tokens = 0.0;
+        for p in &self.points {
+            let dt = hours(p) - mean_t;
+            var_t += dt * dt;
+            cov_pct += dt * (p.percentage - mean_pct);
+            cov_tokens += dt * (p.tokens - mean_tokens);
+        }
+        (var_t > 0.0).then(|| (cov_pct / var_t, cov_tokens / var_t))
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    #[test]
+    fn linear_burn_is_fitted_and_projects_exhaustion() {
+        let mut tracker = BurnTracker::default();
+        let reset_ms = 4 * 60 * MINUTE_MS;
+        // 5 points and 1,000 tokens every 10 minutes: 30%/h, 6,000 tokens/h.
+        let mut forecast = None;
+        for step in 0..4u8 {
+            let now_ms = i64::from(step) * 10 * MINUTE_MS;
+            forecast = tracker.observe(now_ms, 20 + step * 5, 1_000 * u64::from(step), Some(reset_ms));
+        }
+        let forecast = forecast.unwrap();
+        assert_eq!(forecast.basis, "trend");
+        assert_eq!(forecast.samples, 4);
+        assert_eq!(forecast.burn_rate_pct_per_hour, 30.0);
+        assert_eq!(forecast.tokens_per_hour, 6_000.0);
+        // 65 points left at 30%/h is 130 minutes, well before the reset.
+        assert!(forecast.will_exhaust);
+        let exhausts_at = forecast.exhausts_at_epoch_ms.unwrap();
+        assert!((exhausts_at - 160 * MINUTE_MS).abs() < 1_000);
+        assert_eq!(forecast.projected_percentage_at_reset, 100.0);
+    }
+
+    #[test]
+    fn flat_or_sparse_polls_do_not_project_exhaustion() {
+        let mut tracker = BurnTracker::default();
+        let reset_ms = 5 * 60 * MINUTE_MS;
+        // No reset time, or the window only just started: nothing to go on.
+        assert_eq!(tracker.observe(0, 0, 0, None), None);
+        assert_eq!(tracker.observe(MINUTE_MS, 0, 0, Some(reset_ms)), None);
+
+        // A lone poll (the first one aged out) falls back to the average
+        // since the window began.
+        let forecast = tracker.observe(120 * MINUTE_MS, 20, 3_000, Some(reset_ms)).unwrap();
+        assert_eq!(forecast.basis, "window-average");
+        assert_eq!(forecast.burn_rate_pct_per_hour, 10.0);
+        assert_eq!(forecast.projected_percentage_at_reset, 50.0);
+        assert!(!forecast.will_exhaust);
+
+        // Polls that never move fit a zero slope.
+        let forecast = tracker.observe(150 * MINUTE_MS, 20, 3_000, Some(reset_ms)).unwrap();
+        assert_eq!((forecast.basis.as_str(), forecast.burn_rate_pct_per_hour), ("trend", 0.0));
+        assert_eq!(forecast.projected_percentage_at_reset, 20.0);
+        assert_eq!(forecast.exhausts_at_epoch_ms, None);
+
+        // A new window starts the samples over.
+        let next_reset_ms = reset_ms + 5 * 60 * MINUTE_MS;
+        let forecast = tracker.observe(reset_ms + 30 * MINUTE_MS, 5, 100, Some(next_reset_ms)).unwrap();
+        assert_eq!((forecast.basis.as_str(), forecast.samples), ("window-average", 1));
+    }
+}
@@ -0,0 +1,374 @@
+use std::sync::Arc;
+
+use log::{error, info, warn};
+use tauri::Manager;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::host::{HostHandle, TauriHost};
+use crate::models::{AppConfig, KeySlotConfig, RuntimeStatus, SlotStats};
+use crate::{config, control, cron_schedule, file_logger, history, ipc, proxy, has_enabled_slot_with_key, scheduler, secrets, simulation, tray, update_checker, SharedState};
+
+#[tauri::command]
+async fn load_settings(state: tauri::State<'_, SharedState>) -> Result<AppConfig, String> {
+    info!("loading settings from disk");
+    let loaded = {
+        let mut guard = state.config.write().await;
+        let loaded = config::load_config(&state.host).await?;
+        *guard = loaded.clone();
+        loaded
+    };
+    let enabled = loaded.slots.iter().filter(|s| s.enabled).count();
+    info!("settings loaded: {enabled} slot(s) enabled");
+    Ok(secrets::masked(&loaded))
+}
+
+#[tauri::command]
+async fn save_settings(
+    state: tauri::State<'_, SharedState>,
+    mut settings: AppConfig,
+) -> Result<AppConfig, String> {
+    // The UI only holds masked keys; they change through `set_slot_api_key`.
+    let saved = edit_config(&state, |config| {
+        secrets::restore_keys(&mut settings, config)?;
+        *config = settings;
+        Ok(())
+    })
+    .await?;
+    info!("settings saved to disk");
+    Ok(secrets::masked(&saved))
+}
+
+/// Replace a slot's API key; an empty key removes it. `settings` from the
+/// UI, when given, are saved in the same write.
+#[tauri::command]
+async fn set_slot_api_key(
+    state: tauri::State<'_, SharedState>,
+    slot: usize,
+    api_key: String,
+    settings: Option<AppConfig>,
+) -> Result<AppConfig, String> {
+    let saved = edit_config(&state, |config| {
+        if let Some(mut settings) = settings {
+            secrets::restore_keys(&mut settings, config)?;
+            *config = settings;
+        }
+        let slot_cfg = config
+            .slots
+            .iter_mut()
+            .find(|s| s.slot == slot)
+            .ok_or_else(|| format!("slot {slot} not found"))?;
+        slot_cfg.api_key = api_key.trim().to_string();
+        Ok(())
+    })
+    .await?;
+    info!("slot {slot} API key replaced");
+    Ok(secrets::masked(&saved))
+}
+
+#[tauri::command]
+async fn add_slot(
+    state: tauri::State<'_, SharedState>,
+    name: Option<String>,
+) -> Result<AppConfig, String> {
+    let mut id = 0;
+    let saved = edit_config(&state, |config| {
+        id = config.add_slot(name.unwrap_or_default());
+        Ok(())
+    })
+    .await?;
+    info!("slot {id} added");
+    Ok(secrets::masked(&saved))
+}
+
+#[tauri::command]
+async fn remove_slot(
+    state: tauri::State<'_, SharedState>,
+    slot: usize,
+) -> Result<AppConfig, String> {
+    let saved = edit_config(&state, |config| {
+        if !config.remove_slot(slot) {
+            return Err(format!("slot {slot} not found"));
+        }
+        Ok(())
+    })
+    .await?;
+    info!("slot {slot} removed");
+    Ok(secrets::masked(&saved))
+}
+
+/// Apply `edit` to the current config and save it, holding the config lock
+/// from snapshot to write so concurrent edits cannot revert each other,
+/// then publish the saved config.
+async fn edit_config(
+    state: &SharedState,
+    edit: impl FnOnce(&mut AppConfig) -> Result<(), String>,
+) -> Result<AppConfig, String> {
+    let saved = {
+        let mut guard = state.config.write().await;
+        let mut edited = guard.clone();
+        edit(&mut edited)?;
+        let saved = config::save_config(&state.host, edited).await?;
+        *guard = saved.clone();
+        saved
+    };
+    apply_saved_config(state, saved.clone()).await;
+    Ok(saved)
+}
+
+/// Publish a freshly saved config to the running scheduler and the proxy.
+async fn apply_saved_config(state: &SharedState, saved: AppConfig) {
+    let runtime_status = state.runtime_status.clone();
+    let mut scheduler = state.scheduler.lock().await;
+    if scheduler.is_running() {
+        scheduler
+            .reload_if_running(state.host.clone(), saved.clone(), runtime_status)
+            .await;
+    } else {
+        runtime_status.write().await.sync_slots(&saved);
+    }
+    drop(scheduler);
+
+    if let Err(err) = state.proxy.lock().await.apply(state, &saved).await {
+        warn!("{}", err);
+    }
+}
+
+#[tauri::command]
+async fn start_monitoring(app: tauri::AppHandle) -> Result<(), String> {
+    start_monitoring_internal(app).await
+}
+
+#[tauri::command]
+async fn stop_monitoring(app: tauri::AppHandle) -> Result<(), String> {
+    stop_monitoring_internal(app).await
+}
+
+#[tauri::command]
+async fn get_runtime_status(state: tauri::State<'_, SharedState>) -> Result<RuntimeStatus, String> {
+    Ok(state.runtime_status.read().await.clone())
+}
+
+#[tauri::command]
+async fn warmup_all(app: tauri::AppHandle) -> Result<(), String> {
+    warmup_all_internal(app).await
+}
+
+#[tauri::command]
+async fn warmup_slot(state: tauri::State<'_, SharedState>, slot: usize) -> Result<(), String> {
+    control::warmup_slot(&state, slot).await
+}
+
+#[tauri::command]
+async fn log_ui_action(
+    state: tauri::State<'_, SharedState>,
+    action: String,
+    slot: Option<usize>,
+    details: Option<serde_json::Value>,
+) -> Result<(), String> {
+    let slot_num = slot.unwrap_or(0);
+    let entry = file_logger::event_entry(slot_num, &format!("ui.{action}"), details);
+    file_logger::append(&state.host, entry).await
+}
+
+#[tauri::command]
+async fn query_logs(
+    state: tauri::State<'_, SharedState>,
+    query: file_logger::LogQuery,
+) -> Result<file_logger::LogPage, String> {
+    file_logger::query(&state.host, &query).await
+}
+
+#[tauri::command]
+async fn fetch_slot_stats(state: tauri::State<'_, SharedState>, slot: usize) -> Result<SlotStats, String> {
+    control::fetch_slot_stats(&state, slot).await
+}
+
+#[tauri::command]
+async fn query_usage_history(
+    state: tauri::State<'_, SharedState>,
+    slot: usize,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    bucket_minutes: Option<u64>,
+    max_points: Option<usize>,
+) -> Result<Vec<history::UsageSample>, String> {
+    control::usage_history(&state, slot, from_ms, to_ms, bucket_minutes, max_points).await
+}
+
+#[tauri::command]
+async fn query_reset_cycles(
+    state: tauri::State<'_, SharedState>,
+    slot: usize,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+) -> Result<Vec<history::ResetCycle>, String> {
+    control::reset_cycles(&state, slot, from_ms, to_ms).await
+}
+
+#[tauri::command]
+async fn preview_cron_schedule(
+    state: tauri::State<'_, SharedState>,
+    slot: Option<usize>,
+    expressions: Vec<String>,
+    timezone: Option<String>,
+    count: usize,
+) -> Result<Vec<cron_schedule::CronFireTime>, String> {
+    control::cron_preview(&state, slot, expressions, timezone, count).await
+}
+
+#[tauri::command]
+async fn simulate_schedule(
+    state: tauri::State<'_, SharedState>,
+    config: KeySlotConfig,
+    input: simulation::SimulationInput,
+) -> Result<simulation::SimulationReport, String> {
+    control::simulate_schedule(&state, None, Some(config), input).await
+}
+
+#[tauri::command]
+async fn check_for_updates_cmd(state: tauri::State<'_, SharedState>) -> Result<update_checker::UpdateInfo, String> {
+    let network = state.config.read().await.network.clone();
+    update_checker::check_for_updates(&network).await
+}
+
+pub async fn start_monitoring_internal(app: tauri::AppHandle) -> Result<(), String> {
+    control::start_monitoring(&app.state::<SharedState>()).await
+}
+
+pub async fn stop_monitoring_internal(app: tauri::AppHandle) -> Result<(), String> {
+    control::stop_monitoring(&app.state::<SharedState>()).await
+}
+
+pub async fn warmup_all_internal(app: tauri::AppHandle) -> Result<(), String> {
+    control::warmup_all(&app.state::<SharedState>()).await
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let _ = env_logger::builder().is_test(false).try_init();
+
+    tauri::Builder::default()
+        .setup(|app| {
+            // Single-instance plugin must be registered first
+            #[cfg(desktop)]
+            {
+                let app_handle = app.handle().clone();
+                app.handle().plugin(tauri_plugin_single_instance::init(move |_app, _args, _cwd| {
+                    // Focus the main window when a second instance is attempted
+                    if let Some(win) = app_handle.get_webview_window("main") {
+                        let _ = win.set_focus();
+                        let _ = win.show();
+                    }
+                }))?;
+            }
+
+            let app_handle = app.handle().clone();
+            let host: HostHandle = Arc::new(TauriHost::new(app_handle.clone()));
+            let (initial_config, _) = tauri::async_runtime::block_on(async {
+                let initial_config = match config::load_config(&host).await {
+                    Ok(cfg) => cfg,
+                    Err(err) => {
+                        error!("failed to load persisted config, using defaults: {}", err);
+                        AppConfig::default()
+                    }
+                };
+                let has_ready_slots = has_enabled_slot_with_key(&initial_config);
+
+                app.manage(SharedState {
+                    config: Arc::new(RwLock::new(initial_config.clone())),
+                    runtime_status: Arc::new(RwLock::new(RuntimeStatus::for_config(&initial_config))),
+                    scheduler: Arc::new(Mutex::new(scheduler::SchedulerManager::new())),
+                    proxy: Arc::new(Mutex::new(proxy::ProxyManager::new())),
+                    host: host.clone(),
+                });
+
+                let state = app.state::<SharedState>();
+                if let Err(err) = state.proxy.lock().await.apply(&state, &initial_config).await {
+                    warn!("{}", err);
+                }
+
+                // Clean up old log files (keep max 7 days)
+                file_logger::cleanup_old_logs(&host).await;
+                history::cleanup(&host).await;
+                (initial_config, has_ready_slots)
+            });
+
+            let has_ready_slots = has_enabled_slot_with_key(&initial_config);
+            tray::setup_tray(&app_handle, has_ready_slots)?;
+
+            // Auto-start monitoring on launch
+            let startup_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = start_monitoring_internal(startup_handle).await {
+                    warn!("auto-start monitoring failed: {}", err);
+                } else {
+                    info!("monitoring auto-started on launch");
+                }
+            });
+
+            // Live tail: every new log entry goes to the webview as `log-entry`
+            let log_host = host.clone();
+            tauri::async_runtime::spawn(async move {
+                use tokio::sync::broadcast::error::RecvError;
+
+                let mut entries = file_logger::subscribe();
+                loop {
+                    match entries.recv().await {
+                        Ok(entry) => {
+                            if let Ok(payload) = serde_json::to_value(&entry) {
+                                log_host.emit("log-entry", payload);
+                            }
+                        }
+                        Err(RecvError::Lagged(missed)) => warn!("live log tail skipped {missed} entries"),
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            // Control socket for the `glm-tray <command>` CLI
+            let ipc_state = app.state::<SharedState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = ipc::serve(ipc_state).await {
+                    warn!("control socket unavailable: {}", err);
+                }
+            });
+
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = window.hide();
+            }
+        })
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            None,
+        ))
+        .invoke_handler(tauri::generate_handler![
+            load_settings,
+            save_settings,
+            set_slot_api_key,
+            add_slot,
+            remove_slot,
+            start_monitoring,
+            stop_monitoring,
+            get_runtime_status,
+            warmup_all,
+            warmup_slot,
+            fetch_slot_stats,
+            query_usage_history,
+            query_reset_cycles,
+            preview_cron_schedule,
+            simulate_schedule,
+            check_for_updates_cmd,
+            query_logs,
+            log_ui_action
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
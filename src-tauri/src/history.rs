@@ -0,0 +1,435 @@
+//! On-disk usage history: one JSONL sample per quota poll or stats fetch,
+//! stored in `history/YYYY-MM-DD.jsonl` (UTC days) next to the logs.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::forecast::RESET_JITTER_MS;
+use crate::host::HostHandle;
+use crate::models::{QuotaSnapshot, SlotStats};
+
+/// Default number of days of history to keep.
+const DEFAULT_RETENTION_DAYS: u64 = 90;
+
+/// Upper bound on points returned by a downsampled query.
+const MAX_POINTS: usize = 5_000;
+
+/// One observation of a slot's quota and usage counters.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageSample {
+    /// Epoch ms when the sample was taken (bucket start when downsampled).
+    pub ts: i64,
+    pub slot: usize,
+    /// `poll` (quota poller) or `stats` (on-demand stats fetch).
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timer_active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_reset_epoch_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_calls_5h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_5h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_calls_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_search_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_read_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zread_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_mcp_24h: Option<u64>,
+}
+
+impl UsageSample {
+    pub fn from_poll(slot: usize, snapshot: &QuotaSnapshot, model_calls_5h: u64, tokens_5h: u64) -> Self {
+        Self {
+            ts: Utc::now().timestamp_millis(),
+            slot,
+            source: "poll".to_string(),
+            percentage: Some(snapshot.percentage),
+            timer_active: Some(snapshot.timer_active),
+            next_reset_epoch_ms: snapshot.next_reset_epoch_ms,
+            model_calls_5h: Some(model_calls_5h),
+            tokens_5h: Some(tokens_5h),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_stats(slot: usize, stats: &SlotStats) -> Self {
+        let tokens_limit = stats.limits.iter().find(|l| l.type_name == "TOKENS_LIMIT");
+        Self {
+            ts: Utc::now().timestamp_millis(),
+            slot,
+            source: "stats".to_string(),
+            percentage: tokens_limit.map(|l| l.percentage),
+            timer_active: None,
+            next_reset_epoch_ms: tokens_limit.and_then(|l| l.next_reset_time),
+            model_calls_5h: Some(stats.total_model_calls_5h),
+            tokens_5h: Some(stats.total_tokens_5h),
+            model_calls_24h: Some(stats.total_model_calls_24h),
+            tokens_24h: Some(stats.total_tokens_24h),
+            network_search_24h: Some(stats.total_network_search_24h),
+            web_read_24h: Some(stats.total_web_read_24h),
+            zread_24h: Some(stats.total_zread_24h),
+            search_mcp_24h: Some(stats.total_search_mcp_24h),
+        }
+    }
+
+    /// Fold another sample from the same bucket into this one, keeping peaks
+    /// for percentages and counters and the latest reset/timer state.
+    fn merge(&mut self, other: &UsageSample) {
+        fn max<T: Ord + Copy>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        self.percentage = max(self.percentage, other.percentage);
+        self.model_calls_5h = max(self.model_calls_5h, other.model_calls_5h);
+        self.tokens_5h = max(self.tokens_5h, other.tokens_5h);
+        self.model_calls_24h = max(self.model_calls_24h, other.model_calls_24h);
+        self.tokens_24h = max(self.tokens_24h, other.tokens_24h);
+        self.network_search_24h = max(self.network_search_24h, other.network_search_24h);
+        self.web_read_24h = max(self.web_read_24h, other.web_read_24h);
+        self.zread_24h = max(self.zread_24h, other.zread_24h);
+        self.search_mcp_24h = max(self.search_mcp_24h, other.search_mcp_24h);
+        if other.next_reset_epoch_ms.is_some() {
+            self.next_reset_epoch_ms = other.next_reset_epoch_ms;
+        }
+        if other.timer_active.is_some() {
+            self.timer_active = other.timer_active;
+        }
+        if self.source != other.source {
+            self.source = "mixed".to_string();
+        }
+    }
+}
+
+/// Summary of one 5-hour quota window, keyed by its reset time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetCycle {
+    pub reset_epoch_ms: i64,
+    pub first_seen_ms: i64,
+    pub last_seen_ms: i64,
+    pub peak_percentage: u8,
+    pub peak_model_calls_5h: u64,
+    pub peak_tokens_5h: u64,
+    pub samples: usize,
+}
+
+async fn history_dir(host: &HostHandle) -> Result<(PathBuf, u64), String> {
+    let dir = host.config_dir()?.join("history");
+    let mut retention = DEFAULT_RETENTION_DAYS;
+    if let Some(config) = host.shared_config() {
+        let cfg = config.read().await;
+        if cfg.history_retention_days > 0 {
+            retention = cfg.history_retention_days;
+        }
+    }
+    Ok((dir, retention))
+}
+
+fn day_file(dir: &std::path::Path, day: NaiveDate) -> PathBuf {
+    dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")))
+}
+
+fn day_of(ts_ms: i64) -> NaiveDate {
+    DateTime::<Utc>::from_timestamp_millis(ts_ms)
+        .unwrap_or_default()
+        .date_naive()
+}
+
+/// Append a sample. Failures are logged, never propagated: history must not
+/// break polling.
+pub async fn record(host: &HostHandle, sample: UsageSample) {
+    if let Err(err) = append(host, &sample).await {
+        warn!("slot {}: failed to record usage history: {}", sample.slot, err);
+    }
+}
+
+async fn append(host: &HostHandle, sample: &UsageSample) -> Result<(), String> {
+    let (dir, _) = history_dir(host).await?;
+    fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("create history dir: {e}"))?;
+
+    let mut line = serde_json::to_string(sample).map_err(|e| format!("serialize sample: {e}"))?;
+    line.push('\n');
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(day_file(&dir, day_of(sample.ts)))
+        .await
+        .map_err(|e| format!("open history file: {e}"))?;
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| format!("write sample: {e}"))
+}
+
+/// Samples for `slot` with `from_ms <= ts <= to_ms`, oldest first.
+///
+/// With `bucket_ms`, samples are merged into fixed buckets aligned to the
+/// epoch (peaks per bucket), which keeps week-long charts small.
+pub async fn query(
+    host: &HostHandle,
+    slot: usize,
+    from_ms: i64,
+    to_ms: i64,
+    bucket_ms: Option<i64>,
+) -> Result<Vec<UsageSample>, String> {
+    if to_ms < from_ms {
+        return Err("invalid range: 'to' is before 'from'".into());
+    }
+    let (dir, _) = history_dir(host).await?;
+
+    let mut samples = Vec::new();
+    let mut day = day_of(from_ms);
+    let last_day = day_of(to_ms);
+    while day <= last_day {
+        if let Ok(content) = fs::read_to_string(day_file(&dir, day)).await {
+            samples.extend(
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<UsageSample>(line).ok())
+                    .filter(|s| s.slot == slot && s.ts >= from_ms && s.ts <= to_ms),
+            );
+        }
+        day += Duration::days(1);
+    }
+    samples.sort_by_key(|s| s.ts);
+
+    let Some(bucket_ms) = bucket_ms.filter(|b| *b > 0) else {
+        return Ok(samples);
+    };
+    let mut buckets: BTreeMap<i64, UsageSample> = BTreeMap::new();
+    for sample in samples {
+        let start = sample.ts.div_euclid(bucket_ms) * bucket_ms;
+        buckets
+            .entry(start)
+            .and_modify(|b| b.merge(&sample))
+            .or_insert_with(|| UsageSample { ts: start, ..sample });
+    }
+    Ok(buckets.into_values().collect())
+}
+
+/// Bucket width for a range: `bucket_minutes` if given, otherwise just wide
+/// enough to return at most `max_points` points.
+pub fn bucket_ms(
+    from_ms: i64,
+    to_ms: i64,
+    bucket_minutes: Option<u64>,
+    max_points: Option<usize>,
+) -> Option<i64> {
+    if let Some(minutes) = bucket_minutes.filter(|m| *m > 0) {
+        return Some(i64::try_from(minutes).unwrap_or(i64::MAX).saturating_mul(60_000));
+    }
+    let points = max_points?.clamp(1, MAX_POINTS) as i64;
+    let span = to_ms.saturating_sub(from_ms).max(1);
+    Some(span.saturating_add(points - 1) / points)
+}
+
+/// Past quota windows seen in `[from_ms, to_ms]`, oldest first. Reset
+/// times that drift by less than `RESET_JITTER_MS` between polls belong to
+/// the same window, which reports the latest one seen.
+pub async fn reset_cycles(
+    host: &HostHandle,
+    slot: usize,
+    from_ms: i64,
+    to_ms: i64,
+) -> Result<Vec<ResetCycle>, String> {
+    let samples = query(host, slot, from_ms, to_ms, None).await?;
+    let mut cycles: Vec<ResetCycle> = Vec::new();
+    for sample in &samples {
+        let Some(reset) = sample.next_reset_epoch_ms else {
+            continue;
+        };
+        let index = match cycles
+            .iter()
+            .position(|cycle| (cycle.reset_epoch_ms - reset).abs() < RESET_JITTER_MS)
+        {
+            Some(index) => index,
+            None => {
+                cycles.push(ResetCycle {
+                    reset_epoch_ms: reset,
+                    first_seen_ms: sample.ts,
+                    last_seen_ms: sample.ts,
+                    peak_percentage: 0,
+                    peak_model_calls_5h: 0,
+                    peak_tokens_5h: 0,
+                    samples: 0,
+                });
+                cycles.len() - 1
+            }
+        };
+        let cycle = &mut cycles[index];
+        cycle.reset_epoch_ms = reset;
+        cycle.last_seen_ms = sample.ts;
+        cycle.samples += 1;
+        cycle.peak_percentage = cycle.peak_percentage.max(sample.percentage.unwrap_or(0));
+        cycle.peak_model_calls_5h = cycle.peak_model_calls_5h.max(sample.model_calls_5h.unwrap_or(0));
+        cycle.peak_tokens_5h = cycle.peak_tokens_5h.max(sample.tokens_5h.unwrap_or(0));
+    }
+    cycles.sort_by_key(|cycle| cycle.reset_epoch_ms);
+    Ok(cycles)
+}
+
+/// Deletes history files older than the configured retention.
+pub async fn cleanup(host: &HostHandle) {
+    let Ok((dir, retention)) = history_dir(host).await else {
+        return;
+    };
+    let mut entries = match fs::read_dir(&dir).await {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    let cutoff = Utc::now().date_naive() - Duration::days(retention as i64);
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let Some(day) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        if day < cutoff {
+            match fs::remove_file(&path).await {
+                Ok(()) => info!("deleted old history file: {}", path.display()),
+                Err(e) => info!("failed to delete old history {}: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    use crate::host::HeadlessHost;
+    use crate::models::AppConfig;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    /// Epoch ms of 2026-03-02 `h:m` UTC.
+    fn at(h: u32, m: u32) -> i64 {
+        Utc.with_ymd_and_hms(2026, 3, 2, h, m, 0).unwrap().timestamp_millis()
+    }
+
+    fn sample(slot: usize, ts: i64, source: &str, percentage: u8, tokens_5h: u64, reset_ms: i64) -> UsageSample {
+        UsageSample {
+            ts,
+            slot,
+            source: source.to_string(),
+            percentage: Some(percentage),
+            next_reset_epoch_ms: Some(reset_ms),
+            tokens_5h: Some(tokens_5h),
+            ..Default::default()
+        }
+    }
+
+    /// Host over a fresh config directory holding `samples`; the directory
+    /// is removed when the returned guard drops.
+    async fn host_with(samples: Vec<UsageSample>) -> (HostHandle, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::new(RwLock::new(AppConfig::default()))));
+        for sample in samples {
+            record(&host, sample).await;
+        }
+        (host, dir)
+    }
+
+    #[test]
+    fn bucket_width_saturates_instead_of_overflowing() {
+        assert_eq!(bucket_ms(0, 0, Some(30), Some(10)), Some(30 * MINUTE_MS));
+        assert_eq!(bucket_ms(0, 60 * MINUTE_MS, None, Some(4)), Some(15 * MINUTE_MS));
+        assert_eq!(bucket_ms(0, 60 * MINUTE_MS + 1, None, Some(4)), Some(15 * MINUTE_MS + 1));
+        assert_eq!(bucket_ms(0, 60 * MINUTE_MS, Some(0), None), None);
+        assert_eq!(bucket_ms(0, 0, Some(u64::MAX), None), Some(i64::MAX));
+        assert_eq!(bucket_ms(0, 0, Some(i64::MAX as u64 / 60_000 + 1), None), Some(i64::MAX));
+        assert_eq!(bucket_ms(i64::MIN, i64::MAX, None, Some(1)), Some(i64::MAX));
+    }
+
+    #[tokio::test]
+    async fn queries_downsample_to_bucket_peaks() {
+        let reset = at(12, 0);
+        let (host, _dir) = host_with(
+            vec![
+                sample(1, at(8, 5), "poll", 10, 1_000, reset),
+                sample(1, at(8, 20), "stats", 30, 3_000, reset),
+                sample(2, at(8, 25), "poll", 90, 9_000, reset),
+                sample(1, at(8, 25), "poll", 20, 2_500, reset + MINUTE_MS),
+                sample(1, at(8, 50), "poll", 40, 4_000, reset),
+            ],
+        )
+        .await;
+
+        let raw = query(&host, 1, at(8, 0), at(9, 0), None).await.unwrap();
+        let ts: Vec<_> = raw.iter().map(|s| s.ts).collect();
+        assert_eq!(ts, [at(8, 5), at(8, 20), at(8, 25), at(8, 50)]);
+
+        let buckets = query(&host, 1, at(8, 0), at(9, 0), Some(30 * MINUTE_MS)).await.unwrap();
+        let summary: Vec<_> = buckets
+            .iter()
+            .map(|b| (b.ts, b.source.as_str(), b.percentage, b.tokens_5h, b.next_reset_epoch_ms))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (at(8, 0), "mixed", Some(30), Some(3_000), Some(reset + MINUTE_MS)),
+                (at(8, 30), "poll", Some(40), Some(4_000), Some(reset)),
+            ]
+        );
+        assert!(query(&host, 1, at(9, 0), at(8, 0), None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn reset_cycles_group_samples_by_window_across_days() {
+        let first = at(23, 0);
+        let second = first + 5 * 60 * MINUTE_MS;
+        let next_day = |m: i64| first + (60 + m) * MINUTE_MS;
+        let (host, _dir) = host_with(
+            vec![
+                sample(1, at(18, 30), "poll", 5, 500, first - 30_000),
+                sample(1, at(22, 0), "poll", 70, 7_000, first),
+                // The API's reset time drifts by seconds between polls.
+                sample(1, at(20, 0), "poll", 45, 4_500, first + 20_000),
+                sample(1, next_day(10), "poll", 15, 1_500, second),
+                UsageSample { next_reset_epoch_ms: None, ..sample(1, next_day(20), "poll", 99, 0, 0) },
+            ],
+        )
+        .await;
+
+        let cycles = reset_cycles(&host, 1, at(0, 0), next_day(30)).await.unwrap();
+        let summary: Vec<_> = cycles
+            .iter()
+            .map(|c| (c.reset_epoch_ms, c.first_seen_ms, c.last_seen_ms, c.peak_percentage, c.peak_tokens_5h, c.samples))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (first, at(18, 30), at(22, 0), 70, 7_000, 3),
+                (second, next_day(10), next_day(10), 15, 1_500, 1),
+            ]
+        );
+    }
+}
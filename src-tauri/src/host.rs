@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::models::{AppConfig, RuntimeStatus};
+
+/// Same directory Tauri resolves via `app_config_dir()` for this identifier,
+/// so the tray app and the headless daemon share `settings.json` and logs.
+pub const APP_IDENTIFIER: &str = "com.kiwina.glm-tray";
+
+/// Environment override for the config directory (daemon and CLI).
+pub const CONFIG_DIR_ENV: &str = "GLM_TRAY_CONFIG_DIR";
+
+/// The process hosting the scheduler: the Tauri tray app or the headless daemon.
+///
+/// Scheduler, API client and file logger only talk to their host through
+/// this trait, so none of them depend on a Tauri `AppHandle`.
+pub trait Host: Send + Sync {
+    /// Directory holding `settings.json` and the `logs/` folder.
+    fn config_dir(&self) -> Result<PathBuf, String>;
+
+    /// Live app config, once shared state has been set up.
+    fn shared_config(&self) -> Option<Arc<RwLock<AppConfig>>>;
+
+    /// Forward an event (e.g. `quota-updated`) to whoever is listening.
+    fn emit(&self, event: &str, payload: Value);
+
+    /// Runtime status changed; refresh the tray or equivalent.
+    fn refresh_status(&self, runtime: RuntimeStatus, has_ready_slot: bool) -> Result<(), String>;
+
+    /// Show a desktop notification (or its closest equivalent).
+    fn notify(&self, title: &str, body: &str);
+}
+
+pub type HostHandle = Arc<dyn Host>;
+
+/// Resolve the config directory outside of Tauri: `$GLM_TRAY_CONFIG_DIR`,
+/// otherwise the platform config dir joined with the app identifier.
+pub fn default_config_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "failed to resolve platform config dir".to_string())
+}
+
+/// Host backed by the Tauri app: events go to the webview, status to the tray.
+#[cfg(feature = "gui")]
+pub struct TauriHost {
+    app: tauri::AppHandle,
+}
+
+#[cfg(feature = "gui")]
+impl TauriHost {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Host for TauriHost {
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        use tauri::Manager;
+        self.app
+            .path()
+            .app_config_dir()
+            .map_err(|err| format!("failed to resolve app config dir: {err}"))
+    }
+
+    fn shared_config(&self) -> Option<Arc<RwLock<AppConfig>>> {
+        use tauri::Manager;
+        self.app
+            .try_state::<crate::SharedState>()
+            .map(|state| state.config.clone())
+    }
+
+    fn emit(&self, event: &str, payload: Value) {
+        use tauri::Emitter;
+        let _ = self.app.emit(event, payload);
+    }
+
+    fn refresh_status(&self, runtime: RuntimeStatus, has_ready_slot: bool) -> Result<(), String> {
+        crate::tray::refresh_tray(&self.app, runtime, has_ready_slot)
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        use tauri_plugin_notification::NotificationExt;
+        if let Err(err) = self.app.notification().builder().title(title).body(body).show() {
+            log::warn!("failed to show notification: {err}");
+        }
+    }
+}
+
+/// Host for the headless daemon: no tray, events and notifications only go
+/// to the log.
+pub struct HeadlessHost {
+    config_dir: PathBuf,
+    config: Arc<RwLock<AppConfig>>,
+}
+
+impl HeadlessHost {
+    pub fn new(config_dir: PathBuf, config: Arc<RwLock<AppConfig>>) -> Self {
+        Self { config_dir, config }
+    }
+}
+
+impl Host for HeadlessHost {
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.config_dir.clone())
+    }
+
+    fn shared_config(&self) -> Option<Arc<RwLock<AppConfig>>> {
+        Some(self.config.clone())
+    }
+
+    fn emit(&self, event: &str, payload: Value) {
+        log::debug!("event {event}: {payload}");
+    }
+
+    fn refresh_status(&self, runtime: RuntimeStatus, _has_ready_slot: bool) -> Result<(), String> {
+        let active = runtime.slots.iter().filter(|s| s.enabled).count();
+        log::debug!("status: monitoring={} active_slots={active}", runtime.monitoring);
+        Ok(())
+    }
+
+    fn notify(&self, title: &str, body: &str) {
+        log::info!("notification: {title}: {body}");
+    }
+}
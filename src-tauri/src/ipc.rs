@@ -0,0 +1,404 @@
+//! Local control socket shared by the tray app and `glm-trayd`.
+//!
+//! The protocol is one JSON object per line: a [`Request`] from the client,
+//! answered by a [`Response`] from the server. The socket lives next to
+//! `settings.json`, so it follows the same `--config-dir` / env override.
+
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::file_logger::LogQuery;
+use crate::models::KeySlotConfig;
+use crate::simulation::SimulationInput;
+use crate::SharedState;
+#[cfg(unix)]
+use crate::{control, file_logger};
+
+const SOCKET_FILE: &str = "glm-tray.sock";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    Status,
+    Start,
+    Stop,
+    Warmup { slot: usize },
+    WarmupAll,
+    Stats { slot: usize },
+    LogsTail { lines: usize },
+    /// One page of matching log entries.
+    LogsQuery(LogQuery),
+    /// Matching entries as they are written, one response line each,
+    /// until the client disconnects.
+    LogsFollow(LogQuery),
+    History {
+        slot: usize,
+        #[serde(default)]
+        from_ms: Option<i64>,
+        #[serde(default)]
+        to_ms: Option<i64>,
+        #[serde(default)]
+        bucket_minutes: Option<u64>,
+    },
+    ResetCycles {
+        slot: usize,
+        #[serde(default)]
+        from_ms: Option<i64>,
+    },
+    CronPreview {
+        #[serde(default)]
+        slot: Option<usize>,
+        #[serde(default)]
+        expressions: Vec<String>,
+        #[serde(default)]
+        timezone: Option<String>,
+        count: usize,
+    },
+    Simulate {
+        #[serde(default)]
+        slot: Option<usize>,
+        #[serde(default)]
+        config: Option<Box<KeySlotConfig>>,
+        #[serde(default)]
+        input: SimulationInput,
+    },
+}
+
+impl Request {
+    /// The `command` tag. Requests are logged by this alone: `Simulate` can
+    /// carry a slot config, API key included.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Request::Status => "status",
+            Request::Start => "start",
+            Request::Stop => "stop",
+            Request::Warmup { .. } => "warmup",
+            Request::WarmupAll => "warmup_all",
+            Request::Stats { .. } => "stats",
+            Request::LogsTail { .. } => "logs_tail",
+            Request::LogsQuery(_) => "logs_query",
+            Request::LogsFollow(_) => "logs_follow",
+            Request::History { .. } => "history",
+            Request::ResetCycles { .. } => "reset_cycles",
+            Request::CronPreview { .. } => "cron_preview",
+            Request::Simulate { .. } => "simulate",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[cfg(unix)]
+impl Response {
+    fn from_result(result: Result<Value, String>) -> Self {
+        match result {
+            Ok(data) => Self { ok: true, data: Some(data), error: None },
+            Err(err) => Self { ok: false, data: None, error: Some(err) },
+        }
+    }
+}
+
+pub fn socket_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(SOCKET_FILE)
+}
+
+/// Accept control connections until the process exits.
+///
+/// Fails if another instance already owns the socket; a stale socket file
+/// left behind by a crash is replaced.
+#[cfg(unix)]
+pub async fn serve(state: SharedState) -> Result<(), String> {
+    use tokio::net::UnixStream;
+
+    let path = socket_path(&state.host.config_dir()?);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("failed to create config dir: {err}"))?;
+    }
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(format!("another instance is listening on {}", path.display()));
+        }
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    let listener = bind_private(&path)?;
+    info!("control socket listening on {}", path.display());
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = handle_connection(stream, &state).await {
+                        warn!("control connection failed: {}", err);
+                    }
+                });
+            }
+            Err(err) => {
+                warn!("control socket accept failed: {}", err);
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Bind `path` so only the owner can ever connect: the socket is created in
+/// a 0700 staging directory, restricted to 0600, then renamed into place.
+#[cfg(unix)]
+fn bind_private(path: &Path) -> Result<tokio::net::UnixListener, String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let staging = path.with_extension(format!("sock.{}.d", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|err| format!("failed to create {}: {err}", staging.display()))?;
+    let staged = staging.join(SOCKET_FILE);
+    let result = tokio::net::UnixListener::bind(&staged)
+        .map_err(|err| format!("failed to bind {}: {err}", path.display()))
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))
+                .map_err(|err| format!("failed to restrict {}: {err}", path.display()))?;
+            std::fs::rename(&staged, path)
+                .map_err(|err| format!("failed to move socket to {}: {err}", path.display()))?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+#[cfg(not(unix))]
+pub async fn serve(_state: SharedState) -> Result<(), String> {
+    Err("the control socket is only supported on Unix".into())
+}
+
+#[cfg(unix)]
+async fn handle_connection(stream: tokio::net::UnixStream, state: &SharedState) -> Result<(), String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await.map_err(|err| format!("read: {err}"))? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::LogsFollow(query)) => return follow_logs(&mut writer, &query).await,
+            Ok(request) => Response::from_result(dispatch(state, request).await),
+            Err(err) => Response::from_result(Err(format!("invalid request: {err}"))),
+        };
+        let mut out = serde_json::to_string(&response).map_err(|err| format!("serialize: {err}"))?;
+        out.push('\n');
+        writer
+            .write_all(out.as_bytes())
+            .await
+            .map_err(|err| format!("write: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Stream entries matching `query` until the client goes away.
+#[cfg(unix)]
+async fn follow_logs(writer: &mut tokio::net::unix::OwnedWriteHalf, query: &LogQuery) -> Result<(), String> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut entries = file_logger::subscribe();
+    loop {
+        let entry = match entries.recv().await {
+            Ok(entry) => entry,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("log follower fell behind, {missed} entries skipped");
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        if !query.matches(&entry) {
+            continue;
+        }
+        let mut out = serde_json::to_string(&Response::from_result(to_value(&entry)))
+            .map_err(|err| format!("serialize: {err}"))?;
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn dispatch(state: &SharedState, request: Request) -> Result<Value, String> {
+    info!("control request: {}", request.command());
+    match request {
+        Request::Status => {
+            let config = state.config.read().await.clone();
+            let mut runtime = state.runtime_status.read().await.clone();
+            for slot in &mut runtime.slots {
+                if slot.name.is_empty() {
+                    if let Some(slot_cfg) = config.slot(slot.slot) {
+                        slot.name = slot_cfg.name.clone();
+                    }
+                }
+            }
+            to_value(&runtime)
+        }
+        Request::Start => {
+            control::start_monitoring(state).await?;
+            Ok(Value::Bool(state.scheduler.lock().await.is_running()))
+        }
+        Request::Stop => {
+            control::stop_monitoring(state).await?;
+            Ok(Value::Bool(false))
+        }
+        Request::Warmup { slot } => control::warmup_slot(state, slot).await.map(|()| Value::Null),
+        Request::WarmupAll => control::warmup_all(state).await.map(|()| Value::Null),
+        Request::Stats { slot } => to_value(&control::fetch_slot_stats(state, slot).await?),
+        Request::LogsTail { lines } => to_value(&file_logger::tail(&state.host, lines).await?),
+        Request::LogsQuery(query) => to_value(&file_logger::query(&state.host, &query).await?),
+        Request::LogsFollow(_) => Err("logs_follow streams; it is handled per connection".into()),
+        Request::History { slot, from_ms, to_ms, bucket_minutes } => to_value(
+            &control::usage_history(state, slot, from_ms, to_ms, bucket_minutes, None).await?,
+        ),
+        Request::ResetCycles { slot, from_ms } => {
+            to_value(&control::reset_cycles(state, slot, from_ms, None).await?)
+        }
+        Request::CronPreview { slot, expressions, timezone, count } => {
+            to_value(&control::cron_preview(state, slot, expressions, timezone, count).await?)
+        }
+        Request::Simulate { slot, config, input } => {
+            to_value(&control::simulate_schedule(state, slot, config.map(|cfg| *cfg), input).await?)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn to_value(value: &impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|err| format!("serialize: {err}"))
+}
+
+/// Send one request to the running instance and return its `data`.
+#[cfg(unix)]
+pub async fn request(config_dir: &Path, request: &Request) -> Result<Value, String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let path = socket_path(config_dir);
+    let stream = UnixStream::connect(&path).await.map_err(|err| {
+        format!("cannot reach glm-tray at {} ({err}); is the app or glm-trayd running?", path.display())
+    })?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut line = serde_json::to_string(request).map_err(|err| format!("serialize: {err}"))?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|err| format!("write: {err}"))?;
+
+    let reply = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await
+        .map_err(|err| format!("read: {err}"))?
+        .ok_or("connection closed without a response")?;
+    let response: Response =
+        serde_json::from_str(&reply).map_err(|err| format!("invalid response: {err}"))?;
+    if response.ok {
+        Ok(response.data.unwrap_or(Value::Null))
+    } else {
+        Err(response.error.unwrap_or_else(|| "request failed".into()))
+    }
+}
+
+/// Send a streaming request and pass each `data` to `on_data` until the
+/// running instance closes the connection.
+#[cfg(unix)]
+pub async fn follow(config_dir: &Path, request: &Request, mut on_data: impl FnMut(Value)) -> Result<(), String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let path = socket_path(config_dir);
+    let stream = UnixStream::connect(&path).await.map_err(|err| {
+        format!("cannot reach glm-tray at {} ({err}); is the app or glm-trayd running?", path.display())
+    })?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut line = serde_json::to_string(request).map_err(|err| format!("serialize: {err}"))?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|err| format!("write: {err}"))?;
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(reply) = lines.next_line().await.map_err(|err| format!("read: {err}"))? {
+        let response: Response =
+            serde_json::from_str(&reply).map_err(|err| format!("invalid response: {err}"))?;
+        if !response.ok {
+            return Err(response.error.unwrap_or_else(|| "request failed".into()));
+        }
+        on_data(response.data.unwrap_or(Value::Null));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn follow(_config_dir: &Path, _request: &Request, _on_data: impl FnMut(Value)) -> Result<(), String> {
+    Err("the control socket is only supported on Unix".into())
+}
+
+#[cfg(not(unix))]
+pub async fn request(_config_dir: &Path, _request: &Request) -> Result<Value, String> {
+    Err("the control socket is only supported on Unix".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logged_command_matches_the_wire_tag() {
+        let requests = [
+            Request::WarmupAll,
+            Request::LogsQuery(LogQuery::default()),
+            Request::ResetCycles { slot: 1, from_ms: None },
+            Request::Simulate {
+                slot: None,
+                config: Some(Box::new(KeySlotConfig { api_key: "sk-live-secret".to_string(), ..Default::default() })),
+                input: SimulationInput::default(),
+            },
+        ];
+        for request in requests {
+            let wire = serde_json::to_value(&request).unwrap();
+            assert_eq!(wire["command"], request.command());
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn socket_is_owner_only_and_staging_dir_is_removed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = socket_path(dir.path());
+        let _listener = bind_private(&path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        tokio::net::UnixStream::connect(&path).await.unwrap();
+    }
+}
@@ -2,5 +2,14 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
-    glm_tray_lib::run()
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if glm_tray_lib::cli::is_cli_invocation(&args) {
+        std::process::exit(glm_tray_lib::cli::run(args));
+    }
+
+    #[cfg(feature = "gui")]
+    glm_tray_lib::run();
+
+    #[cfg(not(feature = "gui"))]
+    std::process::exit(glm_tray_lib::cli::run(args));
 }
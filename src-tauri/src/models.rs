@@ -1,7 +1,8 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-pub const MAX_SLOTS: usize = 4;
-pub const CURRENT_CONFIG_VERSION: u32 = 3;
+pub const CURRENT_CONFIG_VERSION: u32 = 6;
 
 fn default_global_quota_url() -> String {
     "https://api.z.ai/api/monitor/usage/quota/limit".to_string()
@@ -15,6 +16,18 @@ fn default_max_log_days() -> u64 {
     7
 }
 
+fn default_max_log_file_mb() -> u64 {
+    20
+}
+
+fn default_max_log_total_mb() -> u64 {
+    200
+}
+
+fn default_compress_logs() -> bool {
+    true
+}
+
 fn default_wake_quota_retry_window_minutes() -> u64 {
     15
 }
@@ -27,19 +40,46 @@ fn default_quota_poll_backoff_cap_minutes() -> u64 {
     480
 }
 
+fn default_circuit_probe_minutes() -> u64 {
+    15
+}
+
 fn default_auto_update() -> bool {
     true
 }
 
+fn default_next_slot_id() -> usize {
+    1
+}
+
+fn default_history_retention_days() -> u64 {
+    90
+}
+
+fn default_proxy_port() -> u16 {
+    8484
+}
+
+pub const DEFAULT_WAKE_MODEL: &str = "glm-5";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct KeySlotConfig {
+    /// Stable slot ID. Assigned once from `AppConfig::next_slot_id` and never
+    /// reused, so logs and runtime state stay attributable after removals.
     pub slot: usize,
     pub name: String,
     pub enabled: bool,
     pub api_key: String,
+    /// Platform the key belongs to. Built-in providers derive `quota_url`
+    /// and `request_url`; `custom` uses them as entered.
+    pub provider: ProviderKind,
     pub quota_url: String,
     pub request_url: Option<String>,
+    /// Shape and auth headers of the wake and warmup requests.
+    pub protocol: ApiProtocol,
+    /// Proxy for this key's requests; `None` uses `network.proxy_url`.
+    pub proxy_url: Option<String>,
     // Schedule modes - can enable multiple simultaneously
     #[serde(default)]
     pub schedule_interval_enabled: bool,
@@ -47,12 +87,26 @@ pub struct KeySlotConfig {
     pub schedule_times_enabled: bool,
     #[serde(default)]
     pub schedule_after_reset_enabled: bool,
+    #[serde(default)]
+    pub schedule_cron_enabled: bool,
     // Mode-specific settings
     pub schedule_interval_minutes: u64,
     pub schedule_times: Vec<String>,
     pub schedule_after_reset_minutes: u64,
+    /// Five-field cron expressions (max 5), matched in `schedule_timezone`.
+    pub schedule_cron: Vec<String>,
+    /// IANA zone (e.g. `Europe/Berlin`) for times, after-reset and cron
+    /// modes and the displayed reset time. Empty means the system zone.
+    pub schedule_timezone: String,
+    /// Days times and after-reset modes may fire on, bit 0 = Monday …
+    /// bit 6 = Sunday.
+    pub schedule_days: u8,
+    /// What to do with scheduled wakes that fell inside a system sleep.
+    pub missed_fire_policy: MissedFirePolicy,
     pub poll_interval_minutes: u64,
     pub logging: bool,
+    /// Body of the chat completion sent by wakes and warmups.
+    pub wake_request: WakeRequestConfig,
 }
 
 impl Default for KeySlotConfig {
@@ -62,16 +116,99 @@ impl Default for KeySlotConfig {
             name: String::new(),
             enabled: false,
             api_key: String::new(),
+            provider: ProviderKind::default(),
             quota_url: default_global_quota_url(),
             request_url: Some(default_global_request_url()),
+            protocol: ApiProtocol::default(),
+            proxy_url: None,
             schedule_interval_enabled: false,
             schedule_times_enabled: false,
             schedule_after_reset_enabled: false,
+            schedule_cron_enabled: false,
             schedule_interval_minutes: 60,
             schedule_times: Vec::new(),
             schedule_after_reset_minutes: 1,
+            schedule_cron: Vec::new(),
+            schedule_timezone: String::new(),
+            schedule_days: crate::slot_zone::ALL_DAYS,
+            missed_fire_policy: MissedFirePolicy::default(),
             poll_interval_minutes: 30,
             logging: false,
+            wake_request: WakeRequestConfig::default(),
+        }
+    }
+}
+
+/// Platform behind a slot; see `provider::Provider`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ProviderKind {
+    #[default]
+    #[serde(rename = "zai")]
+    Zai,
+    #[serde(rename = "bigmodel")]
+    BigModel,
+    #[serde(rename = "custom")]
+    Custom,
+}
+
+/// API flavour spoken to `request_url`. Quota and usage polling use the
+/// monitor endpoints either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ApiProtocol {
+    /// `POST …/chat/completions` with `Authorization: Bearer <key>`.
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// `POST …/v1/messages` with `x-api-key` and `anthropic-version`.
+    #[serde(rename = "anthropic")]
+    Anthropic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WakeMessage {
+    /// `system`, `user` or `assistant`.
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeRequestConfig {
+    pub model: String,
+    /// Sent in order. `{{slot}}`, `{{slot_name}}` and `{{timestamp}}` in a
+    /// message's content are filled in per request.
+    pub messages: Vec<WakeMessage>,
+    /// Cap on the reply's tokens; `None` leaves it to the API (required,
+    /// and defaulted, for the Anthropic protocol).
+    pub max_tokens: Option<u32>,
+    /// Send `"thinking": {"type": "disabled"}` so reasoning models do not
+    /// spend tokens thinking about the ping.
+    pub disable_thinking: bool,
+    /// Extra top-level body fields, e.g. `temperature`.
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl WakeRequestConfig {
+    pub fn default_messages() -> Vec<WakeMessage> {
+        let message = |role: &str, content: &str| WakeMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        };
+        vec![
+            message("system", "You are a helpful assistant."),
+            message("user", "ping"),
+        ]
+    }
+}
+
+impl Default for WakeRequestConfig {
+    fn default() -> Self {
+        Self {
+            model: DEFAULT_WAKE_MODEL.to_string(),
+            messages: Self::default_messages(),
+            max_tokens: None,
+            disable_thinking: false,
+            extra: serde_json::Map::new(),
         }
     }
 }
@@ -87,12 +224,27 @@ pub struct AppConfig {
     pub global_request_url: String,
     #[serde(default = "default_max_log_days")]
     pub max_log_days: u64,
+    /// Size at which a day's log file rolls over to `YYYY-MM-DD.N.jsonl`.
+    #[serde(default = "default_max_log_file_mb")]
+    pub max_log_file_mb: u64,
+    /// Disk budget for all log files; the oldest go first when it is exceeded.
+    #[serde(default = "default_max_log_total_mb")]
+    pub max_log_total_mb: u64,
+    /// Gzip log files once they are closed.
+    #[serde(default = "default_compress_logs")]
+    pub compress_logs: bool,
+    /// Days of usage history (`history/*.jsonl`) to keep.
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u64,
     #[serde(default = "default_wake_quota_retry_window_minutes")]
     pub wake_quota_retry_window_minutes: u64,
     #[serde(default = "default_max_consecutive_errors")]
     pub max_consecutive_errors: u32,
     #[serde(default = "default_quota_poll_backoff_cap_minutes")]
     pub quota_poll_backoff_cap_minutes: u64,
+    /// Minutes between probes while a slot's quota or wake circuit is open.
+    #[serde(default = "default_circuit_probe_minutes")]
+    pub circuit_probe_minutes: u64,
     #[serde(default)]
     pub debug: bool,
     #[serde(default)]
@@ -101,33 +253,339 @@ pub struct AppConfig {
     pub config_version: u32,
     #[serde(default = "default_auto_update")]
     pub auto_update: bool,
+    /// ID handed to the next slot created by `add_slot`.
+    #[serde(default = "default_next_slot_id")]
+    pub next_slot_id: usize,
+    /// Serve the OpenAI-compatible routing proxy on `127.0.0.1:proxy_port`.
+    #[serde(default)]
+    pub proxy_enabled: bool,
+    #[serde(default = "default_proxy_port")]
+    pub proxy_port: u16,
+    /// Bearer token proxy clients must send; any local client is served when unset.
+    #[serde(default)]
+    pub proxy_token: Option<String>,
+    /// Desktop notification rules evaluated by the scheduler.
+    #[serde(default)]
+    pub alerts: AlertSettings,
+    /// Outbound webhook targets for scheduler and quota events.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookTarget>,
+    /// Proxy and TLS settings for every outgoing request.
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// In-request retries for quota, usage, stats and wake calls.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// What is scrubbed from log entries before they reach disk.
+    #[serde(default)]
+    pub log_redaction: LogRedaction,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
-        let mut slots = Vec::with_capacity(MAX_SLOTS);
-        for idx in 0..MAX_SLOTS {
-            let mut slot = KeySlotConfig::default();
-            slot.slot = idx + 1;
-            slots.push(slot);
-        }
         Self {
-            slots,
+            slots: Vec::new(),
             theme: "glm".to_string(),
             global_quota_url: default_global_quota_url(),
             global_request_url: default_global_request_url(),
             max_log_days: default_max_log_days(),
+            max_log_file_mb: default_max_log_file_mb(),
+            max_log_total_mb: default_max_log_total_mb(),
+            compress_logs: default_compress_logs(),
+            history_retention_days: default_history_retention_days(),
             wake_quota_retry_window_minutes: default_wake_quota_retry_window_minutes(),
             max_consecutive_errors: default_max_consecutive_errors(),
             quota_poll_backoff_cap_minutes: default_quota_poll_backoff_cap_minutes(),
+            circuit_probe_minutes: default_circuit_probe_minutes(),
             debug: false,
             mock_url: None,
             config_version: CURRENT_CONFIG_VERSION,
             auto_update: default_auto_update(),
+            next_slot_id: default_next_slot_id(),
+            proxy_enabled: false,
+            proxy_port: default_proxy_port(),
+            proxy_token: None,
+            alerts: AlertSettings::default(),
+            network: NetworkConfig::default(),
+            retry: RetryPolicy::default(),
+            log_redaction: LogRedaction::default(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookTarget {
+    pub enabled: bool,
+    pub name: String,
+    pub url: String,
+    /// Scheduler event names to send (`quota-poller.wake-confirmed`), or
+    /// prefixes ending in `*`; empty means every event.
+    pub events: Vec<String>,
+    /// Slot IDs to send events for; empty means every slot.
+    pub slots: Vec<usize>,
+    /// Percentages that raise `quota.threshold-crossed` for this target.
+    pub quota_thresholds: Vec<u8>,
+    /// JSON body with `{{event}}`, `{{slot}}`, `{{slot_name}}`,
+    /// `{{timestamp}}`, `{{details}}` or `{{details.<field>}}` placeholders.
+    /// Without a template the full event context is sent.
+    pub template: Option<serde_json::Value>,
+    /// Signs each body as `X-GLM-Tray-Signature: sha256=<hmac>`.
+    pub secret: Option<String>,
+    /// Extra request headers, e.g. an auth token.
+    pub headers: BTreeMap<String, String>,
+    pub max_retries: u32,
+}
+
+impl Default for WebhookTarget {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            name: String::new(),
+            url: String::new(),
+            events: Vec::new(),
+            slots: Vec::new(),
+            quota_thresholds: vec![80, 95],
+            template: None,
+            secret: None,
+            headers: BTreeMap::new(),
+            max_retries: 3,
         }
     }
 }
 
+/// Handling of interval, times and cron wakes missed while the machine was
+/// asleep, applied when the scheduler notices the wall-clock jump on resume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedFirePolicy {
+    /// One catch-up wake, however many were missed.
+    #[default]
+    FireOnce,
+    /// One catch-up wake per missed fire. A wake opens a 5-hour window and
+    /// a key is never woken inside one, so each owed wake goes out once the
+    /// previous window has closed: the key is kept warm for as many
+    /// consecutive windows as fires were missed.
+    FireAll,
+    /// Drop missed fires and carry on with the schedule.
+    Skip,
+}
+
+/// What an alert rule reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// `TOKENS_LIMIT` percentage crossed `threshold` upwards.
+    #[default]
+    QuotaThreshold,
+    /// A new 5h reset window started.
+    ResetStarted,
+    /// The reset window ended (timer no longer active).
+    ResetEnded,
+    WakeConfirmed,
+    WakeFailed,
+    WakeAutoDisabled,
+    /// The forecast started predicting 100% before the next reset.
+    ForecastExhaustion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRule {
+    pub enabled: bool,
+    pub kind: AlertKind,
+    /// Percentage for `quota_threshold` rules.
+    pub threshold: u8,
+    /// Slot IDs this rule applies to; empty means every slot.
+    pub slots: Vec<usize>,
+    /// After firing for a slot, stay silent for this long.
+    pub quiet_minutes: u64,
+}
+
+impl Default for AlertRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            kind: AlertKind::QuotaThreshold,
+            threshold: 80,
+            slots: Vec::new(),
+            quiet_minutes: 60,
+        }
+    }
+}
+
+/// Value of a `proxy_url` that bypasses both the configured proxy and the
+/// proxy environment variables.
+pub const DIRECT_PROXY: &str = "direct";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` proxy URL, with
+    /// optional `user:password@`. Empty uses the `HTTPS_PROXY`/`ALL_PROXY`
+    /// environment; `direct` uses no proxy at all.
+    pub proxy_url: String,
+    /// Hosts that skip `proxy_url`, in `NO_PROXY` syntax: comma-separated
+    /// hosts, `.domain` suffixes, IPs and CIDR ranges.
+    pub no_proxy: String,
+    /// PEM file of extra root certificates, e.g. a corporate CA.
+    pub ca_bundle_path: String,
+    /// PEM client certificate for mutual TLS; its key may be in the same
+    /// file or in `client_key_path`.
+    pub client_cert_path: String,
+    pub client_key_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Tries per request, the first included; 1 turns retries off.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each one after it.
+    pub base_delay_ms: u64,
+    /// Longest single delay. A `Retry-After` asking for more ends the
+    /// retries instead.
+    pub max_delay_ms: u64,
+    /// Up to this percentage of each delay is randomly taken off, so slots
+    /// that failed together don't retry in lockstep.
+    pub jitter_percent: u8,
+    /// HTTP statuses worth another try. Connection errors and timeouts
+    /// always are.
+    pub retry_statuses: Vec<u16>,
+    /// Wait as long as a `Retry-After` header asks, when there is one.
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+            jitter_percent: 50,
+            retry_statuses: vec![408, 429, 500, 502, 503, 504],
+            respect_retry_after: true,
+        }
+    }
+}
+
+/// Rules on top of the built-in ones, which always scrub configured keys,
+/// auth headers and strings shaped like API keys or bearer tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRedaction {
+    pub enabled: bool,
+    /// Header, JSON field and URL query parameter names whose values are
+    /// replaced, compared case-insensitively.
+    pub header_names: Vec<String>,
+    /// Dotted paths into an entry, `*` matching any field or array index,
+    /// e.g. `request_body.messages.*.content`.
+    pub json_paths: Vec<String>,
+    /// Regular expressions; every match inside a string is replaced.
+    pub patterns: Vec<String>,
+}
+
+impl Default for LogRedaction {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            header_names: Vec::new(),
+            json_paths: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A single attempt per request.
+    #[cfg(test)]
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    /// Alerts raised within this many seconds of the last notification are
+    /// batched into a single notification.
+    pub debounce_seconds: u64,
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        let rule = |kind, threshold, quiet_minutes| AlertRule {
+            kind,
+            threshold,
+            quiet_minutes,
+            ..Default::default()
+        };
+        Self {
+            enabled: false,
+            debounce_seconds: 30,
+            rules: vec![
+                rule(AlertKind::QuotaThreshold, 80, 60),
+                rule(AlertKind::QuotaThreshold, 95, 60),
+                rule(AlertKind::WakeFailed, 0, 30),
+                rule(AlertKind::WakeAutoDisabled, 0, 0),
+            ],
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn slot(&self, id: usize) -> Option<&KeySlotConfig> {
+        self.slots.iter().find(|s| s.slot == id)
+    }
+
+    /// Network settings for `slot`'s requests, with its proxy override.
+    pub fn network_for(&self, slot: &KeySlotConfig) -> NetworkConfig {
+        let mut network = self.network.clone();
+        if let Some(proxy_url) = &slot.proxy_url {
+            network.proxy_url = proxy_url.clone();
+        }
+        network
+    }
+
+    /// Append a new blank slot with a fresh ID and return that ID.
+    pub fn add_slot(&mut self, name: String) -> usize {
+        let id = self.allocate_slot_id();
+        self.slots.push(KeySlotConfig {
+            slot: id,
+            name,
+            provider: ProviderKind::detect(
+                &self.global_quota_url,
+                Some(&self.global_request_url),
+                ApiProtocol::default(),
+            ),
+            quota_url: self.global_quota_url.clone(),
+            request_url: Some(self.global_request_url.clone()),
+            ..Default::default()
+        });
+        id
+    }
+
+    /// Remove a slot by ID. Returns `false` if no such slot exists.
+    pub fn remove_slot(&mut self, id: usize) -> bool {
+        let before = self.slots.len();
+        self.slots.retain(|s| s.slot != id);
+        self.slots.len() != before
+    }
+
+    pub fn allocate_slot_id(&mut self) -> usize {
+        let max_id = self.slots.iter().map(|s| s.slot).max().unwrap_or(0);
+        let id = self.next_slot_id.max(max_id + 1).max(1);
+        self.next_slot_id = id + 1;
+        id
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SlotRuntimeStatus {
     pub slot: usize,
@@ -143,8 +601,12 @@ pub struct SlotRuntimeStatus {
     pub consecutive_errors: u32,
     pub wake_pending: bool,
     pub wake_reset_epoch_ms: Option<i64>,
-    pub wake_auto_disabled: bool,
-    pub auto_disabled: bool,
+    /// Breaker for quota polling, opened after `max_consecutive_errors`.
+    #[serde(default)]
+    pub quota_circuit: CircuitStatus,
+    /// Breaker for wake requests, opened after `max_consecutive_errors`.
+    #[serde(default)]
+    pub wake_circuit: CircuitStatus,
     /// 5h model call count (updated by quota poller)
     #[serde(default)]
     pub total_model_calls_5h: u64,
@@ -154,49 +616,128 @@ pub struct SlotRuntimeStatus {
     /// ISO timestamp of last quota poller update
     #[serde(default)]
     pub quota_last_updated: Option<String>,
+    /// Projected quota exhaustion before the next reset (updated by quota poller)
+    #[serde(default)]
+    pub forecast: Option<QuotaForecast>,
+    /// Requests routed through this slot by the local proxy
+    #[serde(default)]
+    pub proxy: ProxyUsage,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SlotRuntimeStatus {
+    /// Wake circuit is not closed: no scheduled wakes until a probe succeeds.
+    pub fn wake_auto_disabled(&self) -> bool {
+        !self.wake_circuit.is_closed()
+    }
+
+    /// Quota circuit is not closed: polling waits for the next probe.
+    pub fn auto_disabled(&self) -> bool {
+        !self.quota_circuit.is_closed()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally.
+    #[default]
+    Closed,
+    /// Too many consecutive failures; nothing is sent until the next probe.
+    Open,
+    /// A probe is in flight: success closes the circuit, failure reopens it.
+    HalfOpen,
+}
+
+/// One per-slot, per-endpoint circuit breaker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    /// When the circuit opened, kept across failed probes.
+    pub opened_epoch_ms: Option<i64>,
+    /// When the next probe is due while open.
+    pub next_probe_epoch_ms: Option<i64>,
+    /// Probes that failed since the circuit opened.
+    pub failed_probes: u32,
+}
+
+/// Forecast for one slot, recomputed on every successful quota poll.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaForecast {
+    /// Percentage points of `TOKENS_LIMIT` used per hour.
+    pub burn_rate_pct_per_hour: f64,
+    /// Tokens used per hour, from the 5h token counter.
+    pub tokens_per_hour: f64,
+    /// 100% is expected before the next reset.
+    pub will_exhaust: bool,
+    /// When the slot is projected to reach 100% (only if before the reset).
+    pub exhausts_at_epoch_ms: Option<i64>,
+    /// Projected percentage at the next reset, capped at 100.
+    pub projected_percentage_at_reset: f64,
+    /// `trend` (fitted over recent polls) or `window-average` (usage since
+    /// the window started, used until enough polls are collected).
+    pub basis: String,
+    /// Polls the estimate is based on.
+    pub samples: usize,
+    pub computed_at_epoch_ms: i64,
+}
+
+/// Per-slot traffic attributed by the local proxy. Survives monitoring
+/// restarts; only cleared when the app exits.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ProxyUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Times the slot answered 429 / quota exhausted and the request moved on
+    pub failovers: u64,
+    pub last_used: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
 pub struct RuntimeStatus {
     pub monitoring: bool,
     pub slots: Vec<SlotRuntimeStatus>,
 }
 
-impl Default for RuntimeStatus {
-    fn default() -> Self {
-        let mut slots = Vec::with_capacity(MAX_SLOTS);
-        for idx in 0..MAX_SLOTS {
-            slots.push(SlotRuntimeStatus {
-                slot: idx + 1,
-                ..Default::default()
-            });
-        }
-        Self {
-            monitoring: false,
-            slots,
-        }
+impl RuntimeStatus {
+    pub fn for_config(cfg: &AppConfig) -> Self {
+        let mut status = Self::default();
+        status.sync_slots(cfg);
+        status
     }
-}
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct QuotaApiResponse {
-    pub code: i32,
-    pub data: Option<QuotaData>,
-}
+    pub fn slot(&self, id: usize) -> Option<&SlotRuntimeStatus> {
+        self.slots.iter().find(|s| s.slot == id)
+    }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct QuotaData {
-    pub limits: Vec<QuotaLimit>,
-}
+    pub fn slot_mut(&mut self, id: usize) -> Option<&mut SlotRuntimeStatus> {
+        self.slots.iter_mut().find(|s| s.slot == id)
+    }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct QuotaLimit {
-    pub r#type: String,
-    pub percentage: u8,
-    #[serde(default)]
-    pub next_reset_time: Option<i64>,
+    /// Mirror the configured slot IDs: keep existing entries, add blank ones
+    /// for new slots, drop removed ones, and follow the config order.
+    pub fn sync_slots(&mut self, cfg: &AppConfig) {
+        let mut previous = std::mem::take(&mut self.slots);
+        self.slots = cfg
+            .slots
+            .iter()
+            .map(|slot_cfg| {
+                previous
+                    .iter()
+                    .position(|s| s.slot == slot_cfg.slot)
+                    .map(|pos| previous.swap_remove(pos))
+                    .unwrap_or_else(|| SlotRuntimeStatus {
+                        slot: slot_cfg.slot,
+                        ..Default::default()
+                    })
+            })
+            .collect();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -303,7 +844,7 @@ pub struct ToolUsageApiResponse {
 }
 
 // Combined stats returned to frontend
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LimitInfo {
     pub type_name: String,
     pub percentage: u8,
@@ -316,13 +857,13 @@ pub struct LimitInfo {
     pub usage_details: Vec<UsageDetailInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageDetailInfo {
     pub model_code: String,
     pub usage: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlotStats {
     pub level: String,
     pub limits: Vec<LimitInfo>,
@@ -0,0 +1,104 @@
+//! Outbound HTTP settings shared by the API client, the local proxy, webhooks
+//! and the update checker: an HTTP(S) or SOCKS5 proxy with `NO_PROXY`
+//! exceptions, extra root certificates, and a client certificate for mutual
+//! TLS.
+
+use std::path::Path;
+
+use reqwest::{Certificate, ClientBuilder, Identity, NoProxy, Proxy};
+
+use crate::models::{NetworkConfig, DIRECT_PROXY};
+
+/// A `reqwest` client builder with `network` applied.
+pub fn client_builder(network: &NetworkConfig) -> Result<ClientBuilder, String> {
+    let mut builder = reqwest::Client::builder();
+
+    let proxy_url = network.proxy_url.trim();
+    if proxy_url.eq_ignore_ascii_case(DIRECT_PROXY) {
+        builder = builder.no_proxy();
+    } else if !proxy_url.is_empty() {
+        let proxy = Proxy::all(proxy_url)
+            .map_err(|err| format!("invalid proxy URL: {err}"))?
+            .no_proxy(NoProxy::from_string(&network.no_proxy));
+        builder = builder.proxy(proxy);
+    }
+
+    if !network.ca_bundle_path.trim().is_empty() {
+        let pem = read(&network.ca_bundle_path, "CA bundle")?;
+        let certificates =
+            Certificate::from_pem_bundle(&pem).map_err(|err| format!("invalid CA bundle: {err}"))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if !network.client_cert_path.trim().is_empty() {
+        let mut pem = read(&network.client_cert_path, "client certificate")?;
+        if !network.client_key_path.trim().is_empty() {
+            pem.push(b'\n');
+            pem.extend(read(&network.client_key_path, "client key")?);
+        }
+        let identity = Identity::from_pem(&pem).map_err(|err| format!("invalid client certificate: {err}"))?;
+        builder = builder.identity(identity);
+    }
+
+    Ok(builder)
+}
+
+/// Whether `url` is usable as a `proxy_url`.
+pub fn is_valid_proxy_url(url: &str) -> bool {
+    let url = url.trim();
+    if url.is_empty() || url.eq_ignore_ascii_case(DIRECT_PROXY) {
+        return true;
+    }
+    reqwest::Url::parse(url).is_ok_and(|parsed| {
+        matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") && parsed.host_str().is_some()
+    })
+}
+
+fn read(path: &str, what: &str) -> Result<Vec<u8>, String> {
+    let path = Path::new(path.trim());
+    std::fs::read(path).map_err(|err| format!("failed to read {what} {}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(proxy_url: &str) -> NetworkConfig {
+        NetworkConfig {
+            proxy_url: proxy_url.to_string(),
+            no_proxy: "localhost,.internal".to_string(),
+            ..NetworkConfig::default()
+        }
+    }
+
+    #[test]
+    fn proxy_urls_need_a_supported_scheme_and_a_host() {
+        for url in ["", " direct ", "DIRECT", "http://proxy:3128", "https://user:pw@proxy", "socks5://10.0.0.1:1080", "socks5h://proxy:1080"] {
+            assert!(is_valid_proxy_url(url), "{url:?} should be accepted");
+        }
+        for url in ["ftp://proxy:21", "proxy:3128", "http://", "socks5h://:1080", "not a url"] {
+            assert!(!is_valid_proxy_url(url), "{url:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn direct_and_socks5h_proxies_build_clients() {
+        for url in ["direct", "socks5h://proxy:1080", "http://proxy:3128"] {
+            let builder = client_builder(&network(url)).unwrap_or_else(|err| panic!("{url}: {err}"));
+            assert!(builder.build().is_ok(), "{url} should build a client");
+        }
+    }
+
+    #[test]
+    fn unreadable_ca_bundle_names_the_file() {
+        let path = std::env::temp_dir().join("glm-tray-missing-ca.pem");
+        let config = NetworkConfig {
+            ca_bundle_path: format!(" {} ", path.display()),
+            ..network("direct")
+        };
+        let err = client_builder(&config).expect_err("a missing CA bundle is an error");
+        assert!(err.starts_with(&format!("failed to read CA bundle {}: ", path.display())), "{err}");
+    }
+}
@@ -0,0 +1,266 @@
+//! API providers. A `Provider` owns what differs between platforms: the
+//! endpoint layout, auth headers, how usage time ranges are written and how
+//! the monitor responses are parsed. Z.ai and BigModel are built in; a
+//! `custom` slot keeps its own URLs and speaks the Z.ai dialect, for
+//! gateways and private deployments.
+
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Asia::Shanghai;
+use reqwest::header::{ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::RequestBuilder;
+
+use crate::models::{
+    ApiProtocol, KeySlotConfig, ModelUsageApiResponse, ModelUsageTotals, ProviderKind, QuotaApiResponseFull,
+    QuotaDataFull, ToolUsageApiResponse, ToolUsageTotals,
+};
+
+/// Value of the `anthropic-version` header on Anthropic Messages requests.
+pub(crate) const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Paths shared by the built-in platforms.
+pub(crate) const MONITOR_PATH: &str = "/api/monitor/usage";
+pub(crate) const OPENAI_CHAT_PATH: &str = "/api/coding/paas/v4/chat/completions";
+pub(crate) const ANTHROPIC_MESSAGES_PATH: &str = "/api/anthropic/v1/messages";
+
+/// Usage query bounds, `yyyy-MM-dd HH:mm:ss`.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const ZAI_BASE: &str = "https://api.z.ai";
+const BIGMODEL_BASE: &str = "https://open.bigmodel.cn";
+
+/// Monitor endpoints beside the quota one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageKind {
+    Model,
+    Tool,
+}
+
+impl UsageKind {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Model => "model-usage",
+            Self::Tool => "tool-usage",
+        }
+    }
+}
+
+pub trait Provider: Send + Sync {
+    /// Monitor API root; the quota and usage endpoints sit below it.
+    fn monitor_base(&self) -> String;
+
+    /// Wake and warmup endpoint for `protocol`.
+    fn request_url(&self, protocol: ApiProtocol) -> String;
+
+    fn quota_url(&self) -> String {
+        format!("{}/quota/limit", self.monitor_base())
+    }
+
+    /// Usage endpoint for `start..end`.
+    fn usage_url(&self, kind: UsageKind, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+        format!(
+            "{}/{}?startTime={}&endTime={}",
+            self.monitor_base(),
+            kind.path(),
+            urlencoding::encode(&self.format_time(start)),
+            urlencoding::encode(&self.format_time(end))
+        )
+    }
+
+    /// A usage query bound, in the zone the platform reads it in.
+    fn format_time(&self, at: DateTime<Utc>) -> String {
+        at.with_timezone(&Local).format(TIME_FORMAT).to_string()
+    }
+
+    fn accept_language(&self) -> &'static str {
+        "en-US"
+    }
+
+    /// Add the auth and content headers `protocol` expects to `request`.
+    fn authorize(&self, request: RequestBuilder, api_key: &str, protocol: ApiProtocol) -> RequestBuilder {
+        let request = match protocol {
+            ApiProtocol::OpenAi => request.header(AUTHORIZATION, bearer(api_key)),
+            ApiProtocol::Anthropic => request
+                .header("x-api-key", bare_key(api_key))
+                .header("anthropic-version", ANTHROPIC_VERSION),
+        };
+        request
+            .header(ACCEPT_LANGUAGE, self.accept_language())
+            .header(CONTENT_TYPE, "application/json")
+    }
+
+    /// Limits and level from a quota response body.
+    fn parse_quota(&self, body: &str) -> Result<QuotaDataFull, String> {
+        let payload: QuotaApiResponseFull =
+            serde_json::from_str(body).map_err(|err| format!("invalid quota JSON response: {err}"))?;
+        if payload.code != 200 {
+            return Err(format!("quota API code {}", payload.code));
+        }
+        let data = payload.data.ok_or_else(|| "quota response missing data".to_string())?;
+        if data.limits.is_empty() {
+            return Err("quota limits missing".to_string());
+        }
+        Ok(data)
+    }
+
+    /// Totals from a model-usage response, if it has them.
+    fn parse_model_usage(&self, body: &str) -> Option<ModelUsageTotals> {
+        serde_json::from_str::<ModelUsageApiResponse>(body)
+            .ok()
+            .filter(|payload| payload.code == 200)
+            .and_then(|payload| payload.data)
+            .and_then(|data| data.total_usage)
+    }
+
+    /// Totals from a tool-usage response, if it has them.
+    fn parse_tool_usage(&self, body: &str) -> Option<ToolUsageTotals> {
+        serde_json::from_str::<ToolUsageApiResponse>(body)
+            .ok()
+            .filter(|payload| payload.code == 200)
+            .and_then(|payload| payload.data)
+            .and_then(|data| data.total_usage)
+    }
+}
+
+/// `Authorization` value for `api_key`, which may already carry `Bearer `.
+pub(crate) fn bearer(api_key: &str) -> String {
+    format!("Bearer {}", bare_key(api_key))
+}
+
+/// `api_key` without any `Bearer ` prefix, as `x-api-key` wants it.
+pub(crate) fn bare_key(api_key: &str) -> String {
+    let key = api_key.trim();
+    key.strip_prefix("Bearer ").unwrap_or(key).trim().to_string()
+}
+
+fn request_path(protocol: ApiProtocol) -> &'static str {
+    match protocol {
+        ApiProtocol::OpenAi => OPENAI_CHAT_PATH,
+        ApiProtocol::Anthropic => ANTHROPIC_MESSAGES_PATH,
+    }
+}
+
+/// `api.z.ai`.
+pub struct ZAi;
+
+impl Provider for ZAi {
+    fn monitor_base(&self) -> String {
+        format!("{ZAI_BASE}{MONITOR_PATH}")
+    }
+
+    fn request_url(&self, protocol: ApiProtocol) -> String {
+        format!("{ZAI_BASE}{}", request_path(protocol))
+    }
+}
+
+/// `open.bigmodel.cn`. Usage ranges are read in China Standard Time.
+pub struct BigModel;
+
+impl Provider for BigModel {
+    fn monitor_base(&self) -> String {
+        format!("{BIGMODEL_BASE}{MONITOR_PATH}")
+    }
+
+    fn request_url(&self, protocol: ApiProtocol) -> String {
+        format!("{BIGMODEL_BASE}{}", request_path(protocol))
+    }
+
+    fn format_time(&self, at: DateTime<Utc>) -> String {
+        at.with_timezone(&Shanghai).format(TIME_FORMAT).to_string()
+    }
+
+    fn accept_language(&self) -> &'static str {
+        "zh-CN,en-US;q=0.9"
+    }
+}
+
+/// A slot's own quota and request URLs with the Z.ai layout; the usage
+/// endpoints are taken to sit beside the quota one.
+pub struct Custom {
+    quota_url: String,
+    request_url: String,
+}
+
+impl Provider for Custom {
+    fn monitor_base(&self) -> String {
+        self.quota_url.trim_end_matches("/quota/limit").to_string()
+    }
+
+    fn quota_url(&self) -> String {
+        self.quota_url.clone()
+    }
+
+    fn request_url(&self, _protocol: ApiProtocol) -> String {
+        self.request_url.clone()
+    }
+}
+
+impl ProviderKind {
+    /// The built-in provider whose default endpoints these are; `Custom` for
+    /// anything else, even another path on a built-in host. An unset
+    /// request URL counts as the default.
+    pub fn detect(quota_url: &str, request_url: Option<&str>, protocol: ApiProtocol) -> Self {
+        [Self::Zai, Self::BigModel]
+            .into_iter()
+            .find(|kind| {
+                kind.builtin().is_some_and(|provider| {
+                    quota_url.trim() == provider.quota_url()
+                        && request_url.is_none_or(|url| url.trim() == provider.request_url(protocol))
+                })
+            })
+            .unwrap_or(Self::Custom)
+    }
+
+    /// The built-in provider, or `None` for custom URLs.
+    pub fn builtin(self) -> Option<Box<dyn Provider>> {
+        match self {
+            Self::Zai => Some(Box::new(ZAi)),
+            Self::BigModel => Some(Box::new(BigModel)),
+            Self::Custom => None,
+        }
+    }
+}
+
+/// Provider of `cfg`.
+pub fn for_slot(cfg: &KeySlotConfig) -> Box<dyn Provider> {
+    cfg.provider.builtin().unwrap_or_else(|| {
+        Box::new(Custom {
+            quota_url: cfg.quota_url.clone(),
+            request_url: cfg.request_url.clone().unwrap_or_default(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn builtin_providers_own_their_endpoints() {
+        assert_eq!(ZAi.quota_url(), "https://api.z.ai/api/monitor/usage/quota/limit");
+        assert_eq!(
+            BigModel.request_url(ApiProtocol::Anthropic),
+            "https://open.bigmodel.cn/api/anthropic/v1/messages"
+        );
+
+        let start = Utc.with_ymd_and_hms(2026, 2, 14, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 2, 14, 5, 0, 0).unwrap();
+        assert_eq!(
+            BigModel.usage_url(UsageKind::Tool, start, end),
+            "https://open.bigmodel.cn/api/monitor/usage/tool-usage\
+             ?startTime=2026-02-14%2008%3A00%3A00&endTime=2026-02-14%2013%3A00%3A00"
+        );
+
+        let detect = |quota: &str, request: &str| ProviderKind::detect(quota, Some(request), ApiProtocol::Anthropic);
+        assert_eq!(
+            detect(&BigModel.quota_url(), &BigModel.request_url(ApiProtocol::Anthropic)),
+            ProviderKind::BigModel
+        );
+        assert_eq!(detect(&ZAi.quota_url(), &BigModel.request_url(ApiProtocol::Anthropic)), ProviderKind::Custom);
+        assert_eq!(
+            detect("https://glm.example.com/api/monitor/usage/quota/limit", &ZAi.request_url(ApiProtocol::Anthropic)),
+            ProviderKind::Custom
+        );
+        assert_eq!(bearer("Bearer  abc "), "Bearer abc");
+    }
+}
@@ -0,0 +1,736 @@
+//! Optional local OpenAI-compatible proxy.
+//!
+//! Coding tools use `http://127.0.0.1:<proxy_port>/v4` as their base URL.
+//! Each `POST /v4/chat/completions` is forwarded to the enabled slot with the
+//! most quota headroom, and moves on to the next slot when a key answers 429
+//! or a quota error. Usage from the response is attributed to the slot that
+//! served it in `SlotRuntimeStatus::proxy`.
+//!
+//! Only JSON requests addressed to a loopback host are served, so web pages
+//! open in a browser can neither spend quota with no-cors form posts nor
+//! read answers through DNS rebinding. With `proxy_token` set, clients must
+//! also send it as their bearer token.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use futures_util::StreamExt;
+use log::{info, warn};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::api_client;
+use crate::clock::SystemClock;
+use crate::file_logger;
+use crate::models::{ApiProtocol, AppConfig, KeySlotConfig, NetworkConfig};
+use crate::network;
+use crate::provider;
+use crate::SharedState;
+
+pub const CHAT_COMPLETIONS_PATH: &str = "/v4/chat/completions";
+
+/// Z.ai / BigModel error codes meaning the key is rate limited or out of quota.
+const QUOTA_ERROR_CODES: &[&str] = &["1113", "1302", "1303", "1304", "1308", "1309", "1310"];
+
+/// How long a slot is skipped after a quota error when no reset time is known.
+const DEFAULT_COOLDOWN_MS: i64 = 60_000;
+
+/// Non-streamed bodies larger than this are passed through without usage parsing.
+const MAX_USAGE_SCAN_BYTES: usize = 8 * 1024 * 1024;
+
+/// Owns the listener task; restarted when `proxy_enabled`, `proxy_port` or
+/// the network settings change.
+#[derive(Default)]
+pub struct ProxyManager {
+    port: Option<u16>,
+    network: NetworkConfig,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ProxyManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start, restart or stop the listener to match `cfg`.
+    pub async fn apply(&mut self, state: &SharedState, cfg: &AppConfig) -> Result<(), String> {
+        let wanted = cfg.proxy_enabled.then_some(cfg.proxy_port);
+        let running = self.handle.as_ref().is_some_and(|h| !h.is_finished());
+        if running && wanted == self.port && self.network == cfg.network {
+            return Ok(());
+        }
+        self.stop();
+        let Some(port) = wanted else {
+            return Ok(());
+        };
+
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|err| format!("proxy: failed to bind 127.0.0.1:{port}: {err}"))?;
+        let client = network::client_builder(&cfg.network)
+            .map_err(|err| format!("proxy: {err}"))?
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|err| format!("proxy: failed to create HTTP client: {err}"))?;
+        let ctx = ProxyContext {
+            state: state.clone(),
+            client,
+            cooldowns: Arc::default(),
+        };
+        let app = Router::new()
+            .route(CHAT_COMPLETIONS_PATH, post(chat_completions))
+            .fallback(not_found)
+            // Long coding sessions send far more than axum's 2 MB default;
+            // the upstream API enforces its own limit.
+            .layer(DefaultBodyLimit::disable())
+            .with_state(ctx);
+
+        info!("proxy listening on http://127.0.0.1:{port}{CHAT_COMPLETIONS_PATH}");
+        self.handle = Some(tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                warn!("proxy server stopped: {}", err);
+            }
+        }));
+        self.port = Some(port);
+        self.network = cfg.network.clone();
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+            info!("proxy stopped");
+        }
+        self.port = None;
+    }
+}
+
+#[derive(Clone)]
+struct ProxyContext {
+    state: SharedState,
+    client: reqwest::Client,
+    /// Slot ID → epoch ms until which the slot is skipped after a quota error.
+    cooldowns: Arc<Mutex<HashMap<usize, i64>>>,
+}
+
+enum Attempt {
+    /// Final answer for the client (success, or an error failover can't fix).
+    Done(Response),
+    /// The slot is rate limited or out of quota; try the next one.
+    QuotaExhausted(Response),
+}
+
+async fn chat_completions(
+    State(ctx): State<ProxyContext>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let config = ctx.state.config.read().await.clone();
+    if let Err((status, message)) = check_client(&headers, config.proxy_token.as_deref()) {
+        return error_response(status, message);
+    }
+    let candidates = ctx.candidates(&config).await;
+    if candidates.is_empty() {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "no slot available: every key is disabled, missing or cooling down after a quota error",
+        );
+    }
+
+    let mut last_quota_error = None;
+    for slot_cfg in &candidates {
+        match ctx.forward(&config, slot_cfg, &headers, &body).await {
+            Attempt::Done(response) => return response,
+            Attempt::QuotaExhausted(response) => {
+                warn!("proxy: slot {} out of quota, failing over", slot_cfg.slot);
+                ctx.mark_exhausted(slot_cfg).await;
+                last_quota_error = Some(response);
+            }
+        }
+    }
+
+    last_quota_error.unwrap_or_else(|| {
+        error_response(StatusCode::TOO_MANY_REQUESTS, "all slots are out of quota")
+    })
+}
+
+/// Reject requests a local coding tool would not send: foreign `Host`
+/// names, non-JSON bodies and, when a token is configured, a missing or
+/// wrong bearer token.
+fn check_client(headers: &HeaderMap, token: Option<&str>) -> Result<(), (StatusCode, &'static str)> {
+    let host = headers.get(header::HOST).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if !is_loopback_host(host) {
+        return Err((StatusCode::FORBIDDEN, "requests must be addressed to 127.0.0.1 or localhost"));
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .unwrap_or_default()
+        .trim();
+    if !content_type.eq_ignore_ascii_case("application/json") {
+        return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, "Content-Type must be application/json"));
+    }
+
+    if let Some(token) = token {
+        let sent = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim);
+        if sent != Some(token) {
+            return Err((StatusCode::UNAUTHORIZED, "missing or wrong proxy token"));
+        }
+    }
+    Ok(())
+}
+
+/// `127.0.0.1`, `localhost` or `[::1]`, with or without a port.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+async fn not_found() -> Response {
+    error_response(
+        StatusCode::NOT_FOUND,
+        &format!("unsupported route; use POST {CHAT_COMPLETIONS_PATH}"),
+    )
+}
+
+impl ProxyContext {
+    /// Enabled slots ordered by headroom: lowest quota percentage first,
+    /// never-polled slots last, ties broken by fewest proxied requests.
+    /// Slots the scheduler auto-disabled for quota or wake failures are
+    /// skipped.
+    async fn candidates(&self, config: &AppConfig) -> Vec<KeySlotConfig> {
+        let now_ms = chrono::Local::now().timestamp_millis();
+        let runtime = self.state.runtime_status.read().await;
+        let mut cooldowns = self.cooldowns.lock().await;
+        cooldowns.retain(|_, until| *until > now_ms);
+
+        let mut ranked: Vec<_> = config
+            .slots
+            .iter()
+            .filter(|s| s.enabled && !s.api_key.trim().is_empty())
+            .filter(|s| speaks_openai(s))
+            .filter(|s| !cooldowns.contains_key(&s.slot))
+            .filter_map(|s| {
+                let status = runtime.slot(s.slot);
+                if status.is_some_and(|st| st.auto_disabled() || st.wake_auto_disabled()) {
+                    return None;
+                }
+                let percentage = status.and_then(|st| st.percentage);
+                let requests = status.map_or(0, |st| st.proxy.requests);
+                Some(((percentage.is_none(), percentage.unwrap_or(0), requests), s))
+            })
+            .collect();
+        ranked.sort_by_key(|(rank, _)| *rank);
+        ranked.into_iter().map(|(_, s)| s.clone()).collect()
+    }
+
+    async fn forward(
+        &self,
+        config: &AppConfig,
+        slot_cfg: &KeySlotConfig,
+        headers: &HeaderMap,
+        body: &Bytes,
+    ) -> Attempt {
+        // Clients speak OpenAI chat completions whatever the slot wakes with
+        let provider = provider::for_slot(slot_cfg);
+        let request_url = match provider.request_url(ApiProtocol::OpenAi) {
+            url if url.is_empty() => config.global_request_url.clone(),
+            url => url,
+        };
+        let url = api_client::debug_url(&request_url, Some(config.debug), config.mock_url.as_deref());
+        let flow_id = api_client::next_flow_id(&SystemClock, slot_cfg.slot, "proxy");
+        let start = Instant::now();
+
+        let mut request = provider
+            .authorize(self.client.post(&url), &slot_cfg.api_key, ApiProtocol::OpenAi)
+            .body(body.clone());
+        if let Some(accept) = headers.get(header::ACCEPT) {
+            request = request.header(header::ACCEPT, accept);
+        }
+
+        let upstream = match request.send().await {
+            Ok(response) => response,
+            Err(err) => {
+                let msg = format!("upstream request failed: {err}");
+                self.log(
+                    slot_cfg,
+                    file_logger::error_entry_with_id(slot_cfg.slot, "proxy", "POST", &url, &msg, flow_id),
+                )
+                .await;
+                return Attempt::Done(error_response(StatusCode::BAD_GATEWAY, &msg));
+            }
+        };
+
+        let status = upstream.status();
+        let content_type = upstream.headers().get(header::CONTENT_TYPE).cloned();
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        if !status.is_success() {
+            let bytes = upstream.bytes().await.unwrap_or_default();
+            let parsed: Option<Value> = serde_json::from_slice(&bytes).ok();
+            self.log(
+                slot_cfg,
+                file_logger::response_entry_with_timing_and_id(
+                    slot_cfg.slot,
+                    "proxy",
+                    "POST",
+                    &url,
+                    status.as_u16(),
+                    parsed.clone(),
+                    elapsed,
+                    flow_id,
+                ),
+            )
+            .await;
+
+            let response = with_slot_header(
+                buffered_response(status, content_type, bytes),
+                slot_cfg.slot,
+            );
+            let quota_error = status == StatusCode::TOO_MANY_REQUESTS
+                || parsed.as_ref().is_some_and(is_quota_error);
+            return if quota_error {
+                Attempt::QuotaExhausted(response)
+            } else {
+                Attempt::Done(response)
+            };
+        }
+
+        self.log(
+            slot_cfg,
+            file_logger::response_entry_with_timing_and_id(
+                slot_cfg.slot,
+                "proxy",
+                "POST",
+                &url,
+                status.as_u16(),
+                None,
+                elapsed,
+                flow_id,
+            ),
+        )
+        .await;
+
+        // Pump the upstream body through a channel so usage can be read off
+        // the stream (SSE or plain JSON) without delaying the client.
+        let is_sse = content_type
+            .as_ref()
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        let (tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(16);
+        let ctx = self.clone();
+        let slot = slot_cfg.slot;
+        tokio::spawn(async move {
+            let mut stream = upstream.bytes_stream();
+            let mut scanner = UsageScanner::new(is_sse);
+            while let Some(chunk) = stream.next().await {
+                match chunk {
+                    Ok(bytes) => {
+                        scanner.feed(&bytes);
+                        if tx.send(Ok(bytes)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        let _ = tx.send(Err(std::io::Error::other(err))).await;
+                        break;
+                    }
+                }
+            }
+            ctx.record_usage(slot, scanner.finish()).await;
+        });
+
+        let body = Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        }));
+        let mut response = Response::new(body);
+        *response.status_mut() = status;
+        if let Some(content_type) = content_type {
+            response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+        }
+        Attempt::Done(with_slot_header(response, slot))
+    }
+
+    /// Skip the slot until its quota resets (when known to be full) or for a
+    /// short cooldown (rate limits, unknown reset).
+    async fn mark_exhausted(&self, slot_cfg: &KeySlotConfig) {
+        let now_ms = chrono::Local::now().timestamp_millis();
+        let until = {
+            let mut runtime = self.state.runtime_status.write().await;
+            match runtime.slot_mut(slot_cfg.slot) {
+                Some(current) => {
+                    current.proxy.failovers += 1;
+                    match (current.percentage, current.last_updated_epoch_ms) {
+                        (Some(100..), Some(reset_ms)) if reset_ms > now_ms => reset_ms,
+                        _ => now_ms + DEFAULT_COOLDOWN_MS,
+                    }
+                }
+                None => now_ms + DEFAULT_COOLDOWN_MS,
+            }
+        };
+        self.cooldowns.lock().await.insert(slot_cfg.slot, until);
+        self.log(
+            slot_cfg,
+            file_logger::event_entry(
+                slot_cfg.slot,
+                "proxy.failover",
+                Some(json!({ "cooldown_until_epoch_ms": until })),
+            ),
+        )
+        .await;
+    }
+
+    async fn record_usage(&self, slot: usize, usage: Option<Value>) {
+        let now_iso = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        let snapshot = {
+            let mut runtime = self.state.runtime_status.write().await;
+            let Some(current) = runtime.slot_mut(slot) else {
+                return;
+            };
+            let proxy = &mut current.proxy;
+            proxy.requests += 1;
+            if let Some(usage) = usage {
+                let prompt = usage["prompt_tokens"].as_u64().unwrap_or(0);
+                let completion = usage["completion_tokens"].as_u64().unwrap_or(0);
+                proxy.prompt_tokens += prompt;
+                proxy.completion_tokens += completion;
+                proxy.total_tokens += usage["total_tokens"].as_u64().unwrap_or(prompt + completion);
+            }
+            proxy.last_used = Some(now_iso);
+            proxy.clone()
+        };
+        self.state
+            .host
+            .emit("proxy-usage", json!({ "slot": slot, "proxy": snapshot }));
+    }
+
+    async fn log(&self, slot_cfg: &KeySlotConfig, entry: file_logger::LogEntry) {
+        if slot_cfg.logging {
+            let _ = file_logger::append(&self.state.host, entry).await;
+        }
+    }
+}
+
+/// Built-in providers serve both protocols; a custom slot only has the one
+/// request URL, for its own protocol.
+fn speaks_openai(slot_cfg: &KeySlotConfig) -> bool {
+    slot_cfg.provider.builtin().is_some() || slot_cfg.protocol == ApiProtocol::OpenAi
+}
+
+fn is_quota_error(body: &Value) -> bool {
+    let code = &body["error"]["code"];
+    let code = code
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| code.as_i64().map(|c| c.to_string()));
+    code.is_some_and(|c| QUOTA_ERROR_CODES.contains(&c.as_str()))
+}
+
+/// Pulls the last `usage` object out of a chat completion body, either a
+/// single JSON document or an SSE stream of `data:` chunks.
+struct UsageScanner {
+    sse: bool,
+    buffer: Vec<u8>,
+    usage: Option<Value>,
+}
+
+impl UsageScanner {
+    fn new(sse: bool) -> Self {
+        Self { sse, buffer: Vec::new(), usage: None }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        if !self.sse {
+            if self.buffer.len() + chunk.len() <= MAX_USAGE_SCAN_BYTES {
+                self.buffer.extend_from_slice(chunk);
+            }
+            return;
+        }
+        self.buffer.extend_from_slice(chunk);
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.scan_line(&line);
+        }
+    }
+
+    fn scan_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            return;
+        };
+        if let Ok(chunk) = serde_json::from_str::<Value>(data.trim()) {
+            if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+                self.usage = Some(usage.clone());
+            }
+        }
+    }
+
+    fn finish(mut self) -> Option<Value> {
+        if self.sse {
+            let rest = std::mem::take(&mut self.buffer);
+            self.scan_line(&rest);
+            return self.usage;
+        }
+        serde_json::from_slice::<Value>(&self.buffer)
+            .ok()
+            .and_then(|body| body.get("usage").filter(|u| u.is_object()).cloned())
+    }
+}
+
+fn buffered_response(status: StatusCode, content_type: Option<HeaderValue>, bytes: Bytes) -> Response {
+    let mut response = (status, bytes).into_response();
+    if let Some(content_type) = content_type {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+}
+
+fn with_slot_header(mut response: Response, slot: usize) -> Response {
+    response
+        .headers_mut()
+        .insert("x-glm-tray-slot", HeaderValue::from(slot));
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    let body = json!({ "error": { "message": message, "type": "glm_tray_proxy" } });
+    (status, axum::Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::host::{HeadlessHost, HostHandle};
+    use crate::models::{ProviderKind, RuntimeStatus, SlotRuntimeStatus};
+    use crate::scheduler::SchedulerManager;
+    use tempfile::TempDir;
+    use tokio::sync::RwLock;
+
+    fn slot(id: usize) -> KeySlotConfig {
+        KeySlotConfig {
+            slot: id,
+            enabled: true,
+            api_key: format!("key-{id}"),
+            logging: false,
+            ..KeySlotConfig::default()
+        }
+    }
+
+    /// A slot on a custom provider sending requests to `request_url`.
+    fn custom_slot(id: usize, request_url: &str) -> KeySlotConfig {
+        KeySlotConfig {
+            provider: ProviderKind::Custom,
+            request_url: Some(request_url.to_string()),
+            ..slot(id)
+        }
+    }
+
+    /// Proxy context over `slots`, with the runtime status `status` gives
+    /// each slot, and its config directory, removed on drop.
+    fn context(slots: Vec<KeySlotConfig>, status: impl Fn(&mut SlotRuntimeStatus)) -> (ProxyContext, TempDir) {
+        let config = AppConfig {
+            slots,
+            ..AppConfig::default()
+        };
+        let mut runtime = RuntimeStatus::for_config(&config);
+        runtime.slots.iter_mut().for_each(status);
+        let config = Arc::new(RwLock::new(config));
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), config.clone()));
+        let ctx = ProxyContext {
+            state: SharedState {
+                config,
+                runtime_status: Arc::new(RwLock::new(runtime)),
+                scheduler: Arc::new(Mutex::new(SchedulerManager::new())),
+                proxy: Arc::new(Mutex::new(ProxyManager::new())),
+                host,
+            },
+            client: reqwest::Client::new(),
+            cooldowns: Arc::default(),
+        };
+        (ctx, dir)
+    }
+
+    /// Upstream answering each request with the next scripted status and
+    /// body, and with a plain completion once the script runs out. Returns
+    /// its chat URL and the number of requests it has served.
+    async fn upstream(script: Vec<(u16, Value)>) -> (String, Arc<AtomicUsize>) {
+        let served = Arc::new(AtomicUsize::new(0));
+        let counter = served.clone();
+        let app = Router::new().route(
+            "/chat",
+            post(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = script
+                    .get(n)
+                    .cloned()
+                    .unwrap_or((200, json!({ "choices": [], "usage": { "total_tokens": 3 } })));
+                async move { (StatusCode::from_u16(status).unwrap(), axum::Json(body)) }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("http://{}/chat", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, served)
+    }
+
+    async fn ranked(ctx: &ProxyContext) -> Vec<usize> {
+        let config = ctx.state.config.read().await.clone();
+        ctx.candidates(&config).await.iter().map(|s| s.slot).collect()
+    }
+
+    /// Headers a local coding tool sends.
+    fn local_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("127.0.0.1:8484"));
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers
+    }
+
+    async fn send(ctx: &ProxyContext, headers: HeaderMap) -> Response {
+        let body = Bytes::from_static(br#"{"model":"glm-4.6","messages":[{"role":"user","content":"hi"}]}"#);
+        chat_completions(State(ctx.clone()), headers, body).await
+    }
+
+    async fn complete(ctx: &ProxyContext) -> Response {
+        send(ctx, local_headers()).await
+    }
+
+    fn served_by(response: &Response) -> Option<&str> {
+        response.headers().get("x-glm-tray-slot").and_then(|v| v.to_str().ok())
+    }
+
+    #[tokio::test]
+    async fn candidates_rank_by_headroom_and_skip_unavailable_slots() {
+        let custom = |id, protocol| KeySlotConfig {
+            protocol,
+            ..custom_slot(id, "https://glm.example.com/v1/chat")
+        };
+        let slots = vec![
+            slot(1),
+            slot(2),
+            slot(3),
+            slot(4),
+            slot(5),
+            slot(6),
+            custom(7, ApiProtocol::Anthropic),
+            custom(8, ApiProtocol::OpenAi),
+            KeySlotConfig { enabled: false, ..slot(9) },
+            KeySlotConfig { api_key: " ".to_string(), ..slot(10) },
+            slot(11),
+        ];
+        let (ctx, _dir) = context(slots, |st| {
+            st.percentage = match st.slot {
+                1 => Some(80),
+                3 => None,
+                8 => Some(50),
+                _ => Some(10),
+            };
+            st.proxy.requests = if st.slot == 2 { 3 } else { 0 };
+            match st.slot {
+                5 => st.quota_circuit.open(0, 15),
+                6 => st.wake_circuit.open(0, 15),
+                _ => {}
+            }
+        });
+        let later = chrono::Local::now().timestamp_millis() + 60_000;
+        ctx.cooldowns.lock().await.insert(11, later);
+
+        assert_eq!(ranked(&ctx).await, [4, 2, 8, 1, 3]);
+    }
+
+    #[tokio::test]
+    async fn rate_limits_and_quota_errors_fail_over_to_the_next_slot() {
+        let (url, served) = upstream(vec![
+            // HTTP 429 on the roomiest slot: the request moves on.
+            (429, json!({})),
+            (200, json!({ "choices": [] })),
+            // Business code 1113 behind a non-429 status counts as exhausted too.
+            (400, json!({ "error": { "code": "1113" } })),
+            (200, json!({ "choices": [] })),
+            // Other errors go back to the client without failing over.
+            (500, json!({})),
+            // The last slot out of quota: its error is returned.
+            (429, json!({})),
+        ])
+        .await;
+        let slots = (1..=3)
+            .map(|id| custom_slot(id, &url))
+            .collect();
+        let (ctx, _dir) = context(slots, |st| st.percentage = Some(st.slot as u8 * 10));
+
+        let response = complete(&ctx).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(served_by(&response), Some("2"));
+        assert_eq!(ranked(&ctx).await, [2, 3]);
+
+        let response = complete(&ctx).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(served_by(&response), Some("3"));
+
+        let response = complete(&ctx).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(served_by(&response), Some("3"));
+
+        let response = complete(&ctx).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(served_by(&response), Some("3"));
+        assert_eq!(complete(&ctx).await.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(served.load(Ordering::SeqCst), 6);
+        let runtime = ctx.state.runtime_status.read().await;
+        let failovers: Vec<_> = runtime.slots.iter().map(|st| st.proxy.failovers).collect();
+        assert_eq!(failovers, [1, 1, 1]);
+    }
+
+    #[tokio::test]
+    async fn only_local_json_clients_with_the_token_are_served() {
+        let (url, served) = upstream(Vec::new()).await;
+        let (ctx, _dir) = context(vec![custom_slot(1, &url)], |_| {});
+        let with = |name: header::HeaderName, value: &'static str| {
+            let mut headers = local_headers();
+            headers.insert(name, HeaderValue::from_static(value));
+            headers
+        };
+
+        // A page posting text/plain in no-cors mode, and a rebound hostname.
+        assert_eq!(send(&ctx, with(header::CONTENT_TYPE, "text/plain")).await.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(send(&ctx, with(header::HOST, "attacker.example:8484")).await.status(), StatusCode::FORBIDDEN);
+        let mut no_host = local_headers();
+        no_host.remove(header::HOST);
+        assert_eq!(send(&ctx, no_host).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(served.load(Ordering::SeqCst), 0);
+
+        for host in ["localhost:8484", "[::1]:8484", "127.0.0.1"] {
+            let mut headers = with(header::CONTENT_TYPE, "application/json; charset=utf-8");
+            headers.insert(header::HOST, HeaderValue::from_static(host));
+            assert_eq!(send(&ctx, headers).await.status(), StatusCode::OK, "{host}");
+        }
+
+        ctx.state.config.write().await.proxy_token = Some("local-secret".to_string());
+        assert_eq!(complete(&ctx).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&ctx, with(header::AUTHORIZATION, "Bearer wrong")).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(&ctx, with(header::AUTHORIZATION, "Bearer local-secret")).await.status(), StatusCode::OK);
+        assert_eq!(served.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,272 @@
+//! Redaction of log entries before `file_logger::append` writes them.
+//! Built-in rules always scrub the configured API keys and webhook secrets,
+//! auth headers and anything shaped like a bearer token or API key;
+//! `log_redaction` in the config adds header names, JSON paths and patterns.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use log::warn;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::models::{AppConfig, LogRedaction};
+use crate::provider::bare_key;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+const BUILTIN_HEADER_NAMES: [&str; 10] = [
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "api_key",
+    "apikey",
+    "access_token",
+    "token",
+    "cookie",
+    "set-cookie",
+];
+
+const BUILTIN_PATTERNS: [&str; 4] = [
+    // Bearer tokens in headers or error messages
+    r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]{8,}",
+    // OpenAI and Anthropic style keys
+    r"\bsk-[A-Za-z0-9_-]{16,}",
+    // Z.ai and BigModel `id.secret` keys
+    r"\b[0-9a-fA-F]{32}\.[A-Za-z0-9]{16}\b",
+    // JWTs, as BigModel issues for signed requests
+    r"\beyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+];
+
+/// Shorter secrets are not matched literally; they would scrub ordinary text.
+const MIN_SECRET_LEN: usize = 8;
+
+/// The last redactor `shared` built, with the rules and secrets it came from.
+type SharedRedactor = (LogRedaction, Vec<String>, Arc<Redactor>);
+static SHARED: Mutex<Option<SharedRedactor>> = Mutex::new(None);
+
+pub struct Redactor {
+    names: HashSet<String>,
+    query: Option<Regex>,
+    paths: Vec<Vec<String>>,
+    patterns: Vec<Regex>,
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    /// Redactor for `config`, or `None` when redaction is turned off.
+    /// Webhook URLs count as secrets: chat webhooks embed theirs in the path.
+    pub fn for_config(config: &AppConfig) -> Option<Arc<Self>> {
+        if !config.log_redaction.enabled {
+            return None;
+        }
+        let keys = config.slots.iter().map(|slot| bare_key(&slot.api_key));
+        let webhook_secrets = config
+            .webhooks
+            .iter()
+            .flat_map(|target| [target.secret.clone(), Some(target.url.clone())])
+            .flatten();
+        let secrets = keys.chain(webhook_secrets).chain(config.proxy_token.clone());
+        Some(Self::shared(&config.log_redaction, secrets.collect()))
+    }
+
+    /// Redactor for `rules` and `secrets`, reusing the last one built until
+    /// either changes, so log lines don't each compile the patterns again.
+    pub fn shared(rules: &LogRedaction, secrets: Vec<String>) -> Arc<Self> {
+        let mut last = SHARED.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((last_rules, last_secrets, redactor)) = last.as_ref() {
+            if last_rules == rules && *last_secrets == secrets {
+                return redactor.clone();
+            }
+        }
+        let redactor = Arc::new(Self::new(rules, secrets.clone()));
+        *last = Some((rules.clone(), secrets, redactor.clone()));
+        redactor
+    }
+
+    pub fn new(rules: &LogRedaction, secrets: impl IntoIterator<Item = String>) -> Self {
+        let names: HashSet<String> = BUILTIN_HEADER_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(rules.header_names.iter().map(|name| name.trim().to_ascii_lowercase()))
+            .filter(|name| !name.is_empty())
+            .collect();
+        let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+        let query = Regex::new(&format!(r"(?i)([?&](?:{})=)[^&#\s]*", alternatives.join("|"))).ok();
+
+        let paths = rules
+            .json_paths
+            .iter()
+            .map(|path| path.split('.').map(str::to_string).collect::<Vec<_>>())
+            .filter(|segments| segments.iter().all(|segment| !segment.is_empty()))
+            .collect();
+
+        let patterns = BUILTIN_PATTERNS
+            .iter()
+            .copied()
+            .chain(rules.patterns.iter().map(String::as_str))
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(err) => {
+                    warn!("log redaction: ignoring invalid pattern '{pattern}': {err}");
+                    None
+                }
+            })
+            .collect();
+
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .map(|secret| secret.trim().to_string())
+            .filter(|secret| secret.chars().count() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a key is never left half replaced by a shorter one inside it.
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+
+        Self { names, query, paths, patterns, secrets }
+    }
+
+    /// Scrub a serialised log entry in place.
+    pub fn redact(&self, entry: &mut Value) {
+        for path in &self.paths {
+            redact_path(entry, path);
+        }
+        self.redact_value(entry);
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.names.contains(&key.to_ascii_lowercase()) && is_redactable(field) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::String(text) => {
+                if let Some(scrubbed) = self.scrub(text) {
+                    *text = scrubbed;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `text` with every secret, pattern match and sensitive query value
+    /// replaced, or `None` if nothing matched.
+    fn scrub(&self, text: &str) -> Option<String> {
+        let mut scrubbed = text.to_string();
+        for secret in &self.secrets {
+            if scrubbed.contains(secret.as_str()) {
+                scrubbed = scrubbed.replace(secret.as_str(), REDACTED);
+            }
+        }
+        if let Some(query) = &self.query {
+            scrubbed = query.replace_all(&scrubbed, format!("${{1}}{REDACTED}")).into_owned();
+        }
+        for pattern in &self.patterns {
+            scrubbed = pattern.replace_all(&scrubbed, REDACTED).into_owned();
+        }
+        (scrubbed != text).then_some(scrubbed)
+    }
+}
+
+/// Strings, objects and arrays; numbers, booleans and nulls carry no
+/// secrets, and typed entry fields such as `status` must stay numbers.
+fn is_redactable(value: &Value) -> bool {
+    matches!(value, Value::String(_) | Value::Object(_) | Value::Array(_))
+}
+
+fn redact_path(value: &mut Value, path: &[String]) {
+    let Some((segment, rest)) = path.split_first() else {
+        if is_redactable(value) {
+            *value = Value::String(REDACTED.to_string());
+        }
+        return;
+    };
+    match value {
+        Value::Object(map) if segment == "*" => map.values_mut().for_each(|field| redact_path(field, rest)),
+        Value::Object(map) => {
+            if let Some(field) = map.get_mut(segment) {
+                redact_path(field, rest);
+            }
+        }
+        Value::Array(items) if segment == "*" => items.iter_mut().for_each(|item| redact_path(item, rest)),
+        Value::Array(items) => {
+            if let Some(item) = segment.parse::<usize>().ok().and_then(|index| items.get_mut(index)) {
+                redact_path(item, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn built_in_rules_scrub_keys_tokens_and_auth_fields() {
+        let redactor = Redactor::new(&LogRedaction::default(), ["configured-key-0001".to_string()]);
+        let mut entry = json!({
+            "url": "https://api.example.com/v1/usage?api_key=abc123&window=5h",
+            "error": "401 for Bearer abcdefgh.ijklmnop with configured-key-0001",
+            "request_body": {
+                "headers": { "Authorization": "Bearer anything", "X-Api-Key": "k", "Accept": "json" },
+                "note": "keys sk-proj-0123456789abcdef and 0123456789abcdef0123456789abcdef.ABCDEFGHijklmnop",
+            },
+            "details": { "token": null, "total_tokens": 42 },
+            "status": 401,
+        });
+
+        redactor.redact(&mut entry);
+
+        assert_eq!(
+            entry,
+            json!({
+                "url": "https://api.example.com/v1/usage?api_key=[REDACTED]&window=5h",
+                "error": "401 for [REDACTED] with [REDACTED]",
+                "request_body": {
+                    "headers": { "Authorization": REDACTED, "X-Api-Key": REDACTED, "Accept": "json" },
+                    "note": "keys [REDACTED] and [REDACTED]",
+                },
+                "details": { "token": null, "total_tokens": 42 },
+                "status": 401,
+            })
+        );
+    }
+
+    #[test]
+    fn configured_rules_add_names_paths_and_patterns() {
+        let rules = LogRedaction {
+            enabled: true,
+            header_names: vec!["X-Session".to_string()],
+            json_paths: vec!["request_body.messages.*.content".to_string(), "details.0".to_string()],
+            patterns: vec![r"user-\d+".to_string(), "(".to_string()],
+        };
+        let redactor = Redactor::new(&rules, []);
+        let mut entry = json!({
+            "url": "https://example.com/?x-session=s1",
+            "request_body": { "messages": [{ "role": "user", "content": "secret prompt" }] },
+            "details": ["first", "user-42 second"],
+            "x-session": "s2",
+        });
+
+        redactor.redact(&mut entry);
+
+        assert_eq!(
+            entry,
+            json!({
+                "url": "https://example.com/?x-session=[REDACTED]",
+                "request_body": { "messages": [{ "role": "user", "content": REDACTED }] },
+                "details": [REDACTED, "[REDACTED] second"],
+                "x-session": REDACTED,
+            })
+        );
+    }
+}
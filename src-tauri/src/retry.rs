@@ -0,0 +1,85 @@
+//! Retry timing for `ApiClient` requests: exponential backoff with jitter,
+//! and `Retry-After` in both its delay-seconds and HTTP-date forms.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::models::RetryPolicy;
+
+impl RetryPolicy {
+    pub fn retries_status(&self, status: u16) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    /// Backoff before retry number `retry`, counting from 1. `roll`, in
+    /// `0..1`, picks how much of the jitter range is taken off.
+    pub fn backoff(&self, retry: u32, roll: f64) -> Duration {
+        let delay = self
+            .base_delay_ms
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_delay_ms);
+        let jitter = delay as f64 * f64::from(self.jitter_percent.min(100)) / 100.0 * roll.clamp(0.0, 1.0);
+        Duration::from_millis(delay - jitter as u64)
+    }
+
+    /// Wait before retry number `retry`, or `None` when the server asked for
+    /// longer than `max_delay_ms`.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after.filter(|_| self.respect_retry_after) {
+            Some(wait) if wait > Duration::from_millis(self.max_delay_ms) => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(retry, fastrand::f64())),
+        }
+    }
+}
+
+/// How long the `Retry-After` header in `headers` asks to wait from `now`.
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_doubles_to_the_cap_and_honours_retry_after() {
+        let policy = RetryPolicy {
+            base_delay_ms: 500,
+            max_delay_ms: 3_000,
+            jitter_percent: 50,
+            ..RetryPolicy::default()
+        };
+        let ms = |retry, roll| policy.backoff(retry, roll).as_millis();
+        assert_eq!((ms(1, 0.0), ms(2, 0.0), ms(3, 0.0), ms(4, 0.0)), (500, 1_000, 2_000, 3_000));
+        assert_eq!((ms(2, 1.0), ms(2, 0.5)), (500, 750));
+
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(2))), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(30))), None);
+        let ignoring = RetryPolicy {
+            respect_retry_after: false,
+            jitter_percent: 0,
+            ..policy
+        };
+        assert_eq!(ignoring.delay(1, Some(Duration::from_secs(30))), Some(Duration::from_millis(500)));
+
+        let now = Utc.with_ymd_and_hms(2026, 2, 14, 7, 28, 0).unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers, now), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(7)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Sat, 14 Feb 2026 07:28:05 GMT"));
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(5)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Sat, 14 Feb 2026 07:27:00 GMT"));
+        assert_eq!(retry_after(&headers, now), Some(Duration::ZERO));
+    }
+}
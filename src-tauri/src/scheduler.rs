@@ -1,26 +1,47 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use chrono::{Local, Timelike};
+use chrono::{Datelike, NaiveTime};
 use log::{error, info, warn};
 use serde_json::json;
-use tauri::{AppHandle, Emitter};
 use tokio::sync::{watch, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::{self, Duration, Instant};
 
+use crate::alerts::{AlertEngine, AlertEvent, QuotaTransition};
 use crate::api_client::ApiClient;
+use crate::clock::{self, Clock, ClockHandle};
+use crate::cron_schedule;
 use crate::file_logger;
-use crate::models::{AppConfig, KeySlotConfig, RuntimeStatus, SlotRuntimeStatus};
-use crate::tray;
+use crate::forecast::BurnTracker;
+use crate::history::{self, UsageSample};
+use crate::host::HostHandle;
+use crate::models::{
+    AppConfig, CircuitState, KeySlotConfig, MissedFirePolicy, NetworkConfig, QuotaSnapshot, RuntimeStatus,
+    SlotRuntimeStatus,
+};
+use crate::scheduler_state::{self, SlotState};
+use crate::slot_zone::{self, SlotZone};
+use crate::webhooks;
 
 const WAKE_RETRY_INTERVAL_SECONDS: u64 = 60;
 
+/// Wall-clock time gained over the monotonic clock between two ticks that
+/// counts as a system sleep (the monotonic clock stops while suspended).
+const RESUME_GAP_MS: i64 = 90_000;
+
+/// Upper bound on catch-up wakes owed under `MissedFirePolicy::FireAll`.
+const MAX_CATCH_UP_WAKES: usize = 24;
+
+/// How often the scheduler state snapshot is refreshed on disk.
+const STATE_SAVE_INTERVAL_SECONDS: u64 = 15;
+
 #[derive(Clone, Copy)]
-struct SchedulerPolicy {
-    max_consecutive_errors: u32,
-    quota_backoff_cap_minutes: u64,
-    wake_quota_retry_window_minutes: u64,
+pub(crate) struct SchedulerPolicy {
+    pub(crate) max_consecutive_errors: u32,
+    pub(crate) quota_backoff_cap_minutes: u64,
+    pub(crate) wake_quota_retry_window_minutes: u64,
+    pub(crate) circuit_probe_minutes: u64,
 }
 
 impl From<&AppConfig> for SchedulerPolicy {
@@ -29,12 +50,19 @@ impl From<&AppConfig> for SchedulerPolicy {
             max_consecutive_errors: cfg.max_consecutive_errors.max(1),
             quota_backoff_cap_minutes: cfg.quota_poll_backoff_cap_minutes.max(1),
             wake_quota_retry_window_minutes: cfg.wake_quota_retry_window_minutes.max(1),
+            circuit_probe_minutes: cfg.circuit_probe_minutes.max(1),
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CircuitEndpoint {
+    Quota,
+    Wake,
+}
+
 #[derive(Clone, Copy)]
-enum WakeConfirmOutcome {
+pub(crate) enum WakeConfirmOutcome {
     NotPending,
     Confirmed,
     FailedMissing,
@@ -51,28 +79,109 @@ fn has_enabled_slot(cfg: &AppConfig) -> bool {
 
 /// Shared schedule state between wake scheduler and quota poller
 #[derive(Debug, Clone)]
-struct SlotSchedule {
-    next_reset_epoch_ms: Option<i64>,
-    last_known_reset_epoch_ms: Option<i64>,
+pub(crate) struct SlotSchedule {
+    pub(crate) next_reset_epoch_ms: Option<i64>,
+    pub(crate) last_known_reset_epoch_ms: Option<i64>,
     last_times_marker: Option<String>,
+    last_cron_marker: Option<String>,
     last_reset_marker: Option<i64>,
     last_interval_fire: Instant,
-    wake_retry_window_deadline: Option<Instant>,
-    wake_timeout_retry_fired: bool,
+    pub(crate) wake_retry_window_deadline: Option<Instant>,
+    pub(crate) wake_timeout_retry_fired: bool,
+    /// Catch-up wakes still owed after a resume; each fire consumes one.
+    missed_wakes: u32,
 }
 
-impl Default for SlotSchedule {
-    fn default() -> Self {
+impl SlotSchedule {
+    pub(crate) fn new(now: Instant) -> Self {
         Self {
             next_reset_epoch_ms: None,
             last_known_reset_epoch_ms: None,
             last_times_marker: None,
+            last_cron_marker: None,
             last_reset_marker: None,
-            last_interval_fire: Instant::now(),
+            last_interval_fire: now,
             wake_retry_window_deadline: None,
             wake_timeout_retry_fired: false,
+            missed_wakes: 0,
+        }
+    }
+
+    fn to_state(&self, clock: &dyn Clock) -> SlotState {
+        SlotState {
+            next_reset_epoch_ms: self.next_reset_epoch_ms,
+            last_known_reset_epoch_ms: self.last_known_reset_epoch_ms,
+            last_times_marker: self.last_times_marker.clone(),
+            last_cron_marker: self.last_cron_marker.clone(),
+            last_reset_marker: self.last_reset_marker,
+            last_interval_fire_epoch_ms: Some(instant_to_epoch_ms(clock, self.last_interval_fire)),
+            wake_retry_window_deadline_epoch_ms: self
+                .wake_retry_window_deadline
+                .map(|at| instant_to_epoch_ms(clock, at)),
+            wake_timeout_retry_fired: self.wake_timeout_retry_fired,
+            missed_wakes: self.missed_wakes,
+            ..SlotState::default()
         }
     }
+
+    fn from_state(state: &SlotState, clock: &dyn Clock) -> Self {
+        Self {
+            next_reset_epoch_ms: state.next_reset_epoch_ms,
+            last_known_reset_epoch_ms: state.last_known_reset_epoch_ms,
+            last_times_marker: state.last_times_marker.clone(),
+            last_cron_marker: state.last_cron_marker.clone(),
+            last_reset_marker: state.last_reset_marker,
+            last_interval_fire: state
+                .last_interval_fire_epoch_ms
+                .map_or_else(|| clock.instant(), |at| epoch_ms_to_instant(clock, at)),
+            wake_retry_window_deadline: state
+                .wake_retry_window_deadline_epoch_ms
+                .map(|at| epoch_ms_to_instant(clock, at)),
+            wake_timeout_retry_fired: state.wake_timeout_retry_fired,
+            missed_wakes: state.missed_wakes,
+        }
+    }
+}
+
+/// Wall-clock time of a monotonic instant, rounded to the second so
+/// unchanged timers produce identical snapshots.
+fn instant_to_epoch_ms(clock: &dyn Clock, at: Instant) -> i64 {
+    let now = clock.instant();
+    let now_ms = clock.now_ms();
+    let epoch_ms = match at.checked_duration_since(now) {
+        Some(ahead) => now_ms + ahead.as_millis() as i64,
+        None => now_ms - now.duration_since(at).as_millis() as i64,
+    };
+    (epoch_ms + 500).div_euclid(1_000) * 1_000
+}
+
+/// Inverse of `instant_to_epoch_ms`. Times before the monotonic clock's
+/// origin (e.g. before a reboot) clamp to now.
+fn epoch_ms_to_instant(clock: &dyn Clock, epoch_ms: i64) -> Instant {
+    let now = clock.instant();
+    let delta_ms = epoch_ms - clock.now_ms();
+    if delta_ms >= 0 {
+        now + Duration::from_millis(delta_ms as u64)
+    } else {
+        now.checked_sub(Duration::from_millis(delta_ms.unsigned_abs()))
+            .unwrap_or(now)
+    }
+}
+
+/// What the state snapshot reads from a running slot.
+#[derive(Clone)]
+struct PersistedSlot {
+    schedule: Arc<RwLock<SlotSchedule>>,
+    config_rx: watch::Receiver<KeySlotConfig>,
+}
+
+type SlotRegistry = Arc<RwLock<HashMap<usize, PersistedSlot>>>;
+
+/// Background task keeping `scheduler-state.json` current.
+struct StatePersister {
+    host: HostHandle,
+    runtime_status: Arc<RwLock<RuntimeStatus>>,
+    handle: JoinHandle<()>,
 }
 
 /// Controls for a single slot's tasks (wake + poll)
@@ -86,16 +195,102 @@ struct SlotTaskControl {
     poll_handle: JoinHandle<()>,
 }
 
+/// Everything a slot's wake and poll tasks share.
+#[derive(Clone)]
+struct SlotTaskContext {
+    slot_id: usize,
+    host: HostHandle,
+    config_rx: watch::Receiver<KeySlotConfig>,
+    app_config_rx: watch::Receiver<AppConfig>,
+    schedule: Arc<RwLock<SlotSchedule>>,
+    runtime_status: Arc<RwLock<RuntimeStatus>>,
+    stop_rx: watch::Receiver<bool>,
+    poll_now_tx: watch::Sender<bool>,
+    alerts: Arc<AlertEngine>,
+    clock: ClockHandle,
+}
+
+/// Settings a slot's `ApiClient` is built from.
+#[derive(Clone, PartialEq)]
+struct ClientSettings {
+    debug: bool,
+    mock_url: Option<String>,
+    network: NetworkConfig,
+}
+
+/// A slot task's `ApiClient`, rebuilt whenever the debug, mock or network
+/// settings it was built from change, so proxy, CA bundle and client
+/// certificate edits reach running slots. Retry policy edits apply to the
+/// existing client.
+struct SlotClient {
+    host: HostHandle,
+    clock: ClockHandle,
+    built_for: Option<ClientSettings>,
+    client: Result<ApiClient, String>,
+}
+
+impl SlotClient {
+    fn new(host: HostHandle, clock: ClockHandle) -> Self {
+        Self {
+            host,
+            clock,
+            built_for: None,
+            client: Err(String::new()),
+        }
+    }
+
+    /// The client for the current config. A build error is returned as
+    /// `Err(Some(_))` the first time and `Err(None)` until the settings
+    /// change again, so the caller records it once.
+    fn update(&mut self, app_cfg: &AppConfig, cfg: &KeySlotConfig) -> Result<&ApiClient, Option<String>> {
+        let settings = ClientSettings {
+            debug: app_cfg.debug,
+            mock_url: app_cfg.mock_url.clone(),
+            network: app_cfg.network_for(cfg),
+        };
+        let rebuilt = self.built_for.as_ref() != Some(&settings);
+        if rebuilt {
+            if self.built_for.is_some() {
+                info!("slot {} network settings changed, rebuilding HTTP client", cfg.slot);
+            }
+            self.client = ApiClient::new(Some(self.host.clone()), settings.debug, settings.mock_url.clone(), &settings.network)
+                .map(|client| client.with_clock(self.clock.clone()).with_retry(app_cfg.retry.clone()));
+            self.built_for = Some(settings);
+        }
+        match &mut self.client {
+            Ok(client) => {
+                client.set_retry(&app_cfg.retry);
+                Ok(client)
+            }
+            Err(err) if rebuilt => Err(Some(err.clone())),
+            Err(_) => Err(None),
+        }
+    }
+}
+
 pub struct SchedulerManager {
     slot_tasks: HashMap<usize, SlotTaskControl>,
     running: bool,
+    alerts: Arc<AlertEngine>,
+    persisted: SlotRegistry,
+    persister: Option<StatePersister>,
+    clock: ClockHandle,
 }
 
 impl SchedulerManager {
     pub fn new() -> Self {
+        Self::with_clock(clock::system())
+    }
+
+    /// A manager whose tasks read time from `clock`; tests pass a `FakeClock`.
+    pub fn with_clock(clock: ClockHandle) -> Self {
         Self {
             slot_tasks: HashMap::new(),
             running: false,
+            alerts: Arc::new(AlertEngine::new(clock.clone())),
+            persisted: Arc::new(RwLock::new(HashMap::new())),
+            persister: None,
+            clock,
         }
     }
 
@@ -103,9 +298,14 @@ impl SchedulerManager {
         self.running
     }
 
+    /// The clock the slot tasks read time from.
+    pub fn clock(&self) -> ClockHandle {
+        self.clock.clone()
+    }
+
     pub async fn start(
         &mut self,
-        app: AppHandle,
+        host: HostHandle,
         config: AppConfig,
         runtime_status: Arc<RwLock<RuntimeStatus>>,
     ) {
@@ -113,12 +313,14 @@ impl SchedulerManager {
 
         info!("scheduler starting");
         self.running = true;
+        let saved = scheduler_state::load(&host).await;
         {
             let mut runtime = runtime_status.write().await;
             runtime.monitoring = true;
+            runtime.sync_slots(&config);
             for slot in &mut runtime.slots {
-                slot.auto_disabled = false;
-                slot.wake_auto_disabled = false;
+                slot.quota_circuit.close();
+                slot.wake_circuit.close();
                 slot.enabled = false;
                 slot.last_error = None;
                 slot.consecutive_errors = 0;
@@ -130,22 +332,48 @@ impl SchedulerManager {
                 slot.percentage = None;
                 slot.next_reset_hms = None;
                 slot.last_updated_epoch_ms = None;
+                slot.forecast = None;
+                if let Some(restored) = config
+                    .slot(slot.slot)
+                    .and_then(|cfg| saved.slot(cfg.slot, &cfg.api_key))
+                {
+                    info!("slot {} scheduler state restored", slot.slot);
+                    restored.restore_runtime(slot);
+                }
             }
         }
 
-        for (idx, slot_cfg) in config.slots.iter().enumerate() {
+        for slot_cfg in &config.slots {
             if !slot_cfg.enabled || slot_cfg.api_key.trim().is_empty() {
                 continue;
             }
-            self.spawn_slot_task(idx, slot_cfg.clone(), config.clone(), &app, runtime_status.clone()).await;
+            let restored = saved.slot(slot_cfg.slot, &slot_cfg.api_key).cloned();
+            self.spawn_slot_task(slot_cfg.slot, slot_cfg.clone(), config.clone(), &host, runtime_status.clone(), restored)
+                .await;
         }
 
+        let handle = tokio::spawn(Self::state_persist_task(
+            host.clone(),
+            self.persisted.clone(),
+            runtime_status.clone(),
+            self.clock.clone(),
+        ));
+        self.persister = Some(StatePersister {
+            host: host.clone(),
+            runtime_status: runtime_status.clone(),
+            handle,
+        });
+
         let snapshot = runtime_status.read().await.clone();
         let has_ready_slots = has_enabled_slot(&config);
-        let _ = tray::refresh_tray(&app, snapshot, has_ready_slots);
+        let _ = host.refresh_status(snapshot, has_ready_slots);
     }
 
     pub async fn stop(&mut self) {
+        let persister = self.persister.take();
+        if let Some(persister) = &persister {
+            persister.handle.abort();
+        }
         if self.slot_tasks.is_empty() {
             self.running = false;
             return;
@@ -165,13 +393,44 @@ impl SchedulerManager {
             let _ = task.poll_handle.await;
         }
 
+        // Final snapshot so a restart picks up exactly where this run ended.
+        if let Some(persister) = persister {
+            let slots = snapshot_state(&self.persisted, &persister.runtime_status, &*self.clock).await;
+            if let Err(err) = scheduler_state::save(&persister.host, slots).await {
+                warn!("{err}");
+            }
+        }
+        self.persisted.write().await.clear();
+
         self.running = false;
         info!("scheduler stopped");
     }
 
+    /// Save the state snapshot every few seconds while it changes.
+    async fn state_persist_task(
+        host: HostHandle,
+        registry: SlotRegistry,
+        runtime_status: Arc<RwLock<RuntimeStatus>>,
+        clock: ClockHandle,
+    ) {
+        let mut last_saved: Vec<SlotState> = Vec::new();
+        let mut ticker = time::interval(Duration::from_secs(STATE_SAVE_INTERVAL_SECONDS));
+        loop {
+            ticker.tick().await;
+            let slots = snapshot_state(&registry, &runtime_status, &*clock).await;
+            if slots == last_saved {
+                continue;
+            }
+            match scheduler_state::save(&host, slots.clone()).await {
+                Ok(()) => last_saved = slots,
+                Err(err) => warn!("{err}"),
+            }
+        }
+    }
+
     pub async fn reload_if_running(
         &mut self,
-        app: AppHandle,
+        host: HostHandle,
         config: AppConfig,
         runtime_status: Arc<RwLock<RuntimeStatus>>,
     ) {
@@ -182,64 +441,70 @@ impl SchedulerManager {
         info!("scheduler reloading configuration");
 
         let mut desired_slots: HashMap<usize, KeySlotConfig> = HashMap::new();
-        for (idx, slot_cfg) in config.slots.iter().enumerate() {
+        for slot_cfg in &config.slots {
             if slot_cfg.enabled && !slot_cfg.api_key.trim().is_empty() {
-                desired_slots.insert(idx, slot_cfg.clone());
+                desired_slots.insert(slot_cfg.slot, slot_cfg.clone());
             }
         }
 
-        let running_indices: Vec<usize> = self.slot_tasks.keys().copied().collect();
-        let mut changed_indices: HashSet<usize> = HashSet::new();
+        let running_ids: Vec<usize> = self.slot_tasks.keys().copied().collect();
+        let mut changed_ids: HashSet<usize> = HashSet::new();
 
         // Update existing tasks and stop those that should no longer run.
-        for idx in running_indices {
-            if let Some(slot_cfg) = desired_slots.get(&idx) {
-                if let Some(slot_task) = self.slot_tasks.get(&idx) {
+        for slot_id in running_ids {
+            if let Some(slot_cfg) = desired_slots.get(&slot_id) {
+                if let Some(slot_task) = self.slot_tasks.get(&slot_id) {
                     let _ = slot_task.config_tx.send(slot_cfg.clone());
                     let _ = slot_task.app_config_tx.send(config.clone());
-                    sync_slot_runtime_name(&runtime_status, idx, slot_cfg).await;
-                    info!("slot {} config updated", idx + 1);
-                    changed_indices.insert(idx);
+                    sync_slot_runtime_name(&runtime_status, slot_id, slot_cfg).await;
+                    info!("slot {} config updated", slot_id);
+                    changed_ids.insert(slot_id);
                 }
             } else {
-                if let Some(task) = self.slot_tasks.remove(&idx) {
+                if let Some(task) = self.slot_tasks.remove(&slot_id) {
                     let _ = task.stop_tx.send(true);
                     let _ = task.wake_handle.await;
                     let _ = task.poll_handle.await;
-                    clear_slot_runtime(&runtime_status, idx).await;
-                    info!("slot {} task stopped after config change", idx + 1);
-                    changed_indices.insert(idx);
+                    self.persisted.write().await.remove(&slot_id);
+                    clear_slot_runtime(&runtime_status, slot_id).await;
+                    info!("slot {} task stopped after config change", slot_id);
+                    changed_ids.insert(slot_id);
                 }
             }
         }
 
+        // Drop runtime entries of removed slots and add entries for new ones.
+        runtime_status.write().await.sync_slots(&config);
+
         // Start tasks for slots enabled in config that were not previously running.
-        for (idx, slot_cfg) in desired_slots {
-            if self.slot_tasks.contains_key(&idx) {
+        for (slot_id, slot_cfg) in desired_slots {
+            if self.slot_tasks.contains_key(&slot_id) {
                 continue;
             }
-            self.spawn_slot_task(idx, slot_cfg, config.clone(), &app, runtime_status.clone()).await;
-            changed_indices.insert(idx);
+            self.spawn_slot_task(slot_id, slot_cfg, config.clone(), &host, runtime_status.clone(), None)
+                .await;
+            changed_ids.insert(slot_id);
         }
 
-        if !changed_indices.is_empty() {
+        if !changed_ids.is_empty() {
             let snapshot = runtime_status.read().await.clone();
             let has_ready_slots = has_enabled_slot(&config);
-            let _ = tray::refresh_tray(&app, snapshot, has_ready_slots);
+            let _ = host.refresh_status(snapshot, has_ready_slots);
         }
     }
 
     async fn spawn_slot_task(
         &mut self,
-        idx: usize,
+        slot_id: usize,
         slot_cfg: KeySlotConfig,
         app_config: AppConfig,
-        app: &AppHandle,
+        host: &HostHandle,
         runtime_status: Arc<RwLock<RuntimeStatus>>,
+        restored: Option<SlotState>,
     ) {
         {
             let mut runtime = runtime_status.write().await;
-            if let Some(current) = runtime.slots.get_mut(idx) {
+            if let Some(current) = runtime.slot_mut(slot_id) {
                 current.name = slot_cfg.name.clone();
             }
         }
@@ -249,33 +514,35 @@ impl SchedulerManager {
         let (app_config_tx, app_config_rx) = watch::channel(app_config);
         let (poll_now_tx, poll_now_rx) = watch::channel(false);
 
-        let schedule = Arc::new(RwLock::new(SlotSchedule::default()));
+        let schedule = Arc::new(RwLock::new(match restored.as_ref() {
+            Some(state) => SlotSchedule::from_state(state, &*self.clock),
+            None => SlotSchedule::new(self.clock.instant()),
+        }));
         let runtime_handle = runtime_status.clone();
-
-        let wake_handle = tokio::spawn(Self::wake_scheduler_task(
-            idx,
-            app.clone(),
-            config_rx.clone(),
-            app_config_rx.clone(),
-            schedule.clone(),
-            runtime_handle.clone(),
-            stop_rx.clone(),
-            poll_now_tx.clone(),
-        ));
-
-        let poll_handle = tokio::spawn(Self::quota_poller_task(
-            idx,
-            app.clone(),
+        self.persisted.write().await.insert(
+            slot_id,
+            PersistedSlot {
+                schedule: schedule.clone(),
+                config_rx: config_rx.clone(),
+            },
+        );
+
+        let ctx = SlotTaskContext {
+            slot_id,
+            host: host.clone(),
             config_rx,
             app_config_rx,
             schedule,
-            runtime_handle,
+            runtime_status: runtime_handle,
             stop_rx,
-            poll_now_tx.clone(),
-            poll_now_rx,
-        ));
+            poll_now_tx: poll_now_tx.clone(),
+            alerts: self.alerts.clone(),
+            clock: self.clock.clone(),
+        };
+        let wake_handle = tokio::spawn(Self::wake_scheduler_task(ctx.clone()));
+        let poll_handle = tokio::spawn(Self::quota_poller_task(ctx, poll_now_rx));
 
-        self.slot_tasks.insert(idx, SlotTaskControl {
+        self.slot_tasks.insert(slot_id, SlotTaskControl {
             stop_tx,
             config_tx,
             app_config_tx,
@@ -286,50 +553,33 @@ impl SchedulerManager {
     }
 
     /// Wake scheduler task - runs every minute to check wake conditions
-    async fn wake_scheduler_task(
-        idx: usize,
-        app: AppHandle,
-        mut config_rx: watch::Receiver<KeySlotConfig>,
-        mut app_config_rx: watch::Receiver<AppConfig>,
-        schedule: Arc<RwLock<SlotSchedule>>,
-        runtime_status: Arc<RwLock<RuntimeStatus>>,
-        mut stop_rx: watch::Receiver<bool>,
-        poll_now_tx: watch::Sender<bool>,
-    ) {
-        info!("slot {} wake scheduler started", idx + 1);
+    async fn wake_scheduler_task(ctx: SlotTaskContext) {
+        let SlotTaskContext {
+            slot_id,
+            host,
+            mut config_rx,
+            mut app_config_rx,
+            schedule,
+            runtime_status,
+            mut stop_rx,
+            poll_now_tx,
+            alerts,
+            clock,
+        } = ctx;
+        info!("slot {} wake scheduler started", slot_id);
         let initial_cfg = config_rx.borrow().clone();
-        let initial_app_cfg = app_config_rx.borrow().clone();
         let _ = log_scheduler_event(
-            &app,
+            &host,
             &initial_cfg,
             "wake.scheduler.task-started",
-            json!({"slot": idx + 1}),
+            json!({"slot": slot_id}),
         )
         .await;
 
-        let client = match ApiClient::new(Some(app.clone()), initial_app_cfg.debug, initial_app_cfg.mock_url) {
-            Ok(client) => client,
-            Err(err) => {
-                warn!("slot {} client setup failed: {}", idx + 1, err);
-                let initial_policy = SchedulerPolicy::from(&*app_config_rx.borrow());
-                let _ = record_wake_error(
-                    &runtime_status,
-                    idx,
-                    &err,
-                    initial_policy.max_consecutive_errors,
-                )
-                .await;
-                let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                let _ = tray::refresh_tray(
-                    &app,
-                    runtime_status.read().await.clone(),
-                    has_ready_slots,
-                );
-                return;
-            }
-        };
-
+        let mut clients = SlotClient::new(host.clone(), clock.clone());
         let mut poll_now_signal = false;
+        let mut last_tick_wall_ms = clock.now_ms();
+        let mut last_tick = clock.instant();
 
         loop {
             let current_policy = SchedulerPolicy::from(&*app_config_rx.borrow());
@@ -337,124 +587,147 @@ impl SchedulerManager {
 
             if *stop_rx.borrow() {
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "wake.scheduler.task-stopped",
-                    json!({"slot": idx + 1, "reason": "stop-signal"}),
+                    json!({"slot": slot_id, "reason": "stop-signal"}),
                 )
                 .await;
                 break;
             }
             if !cfg.enabled || cfg.api_key.trim().is_empty() {
-                info!("slot {} disabled config, stopping wake scheduler", idx + 1);
+                info!("slot {} disabled config, stopping wake scheduler", slot_id);
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "wake.scheduler.config-disabled",
-                    json!({"slot": idx + 1}),
+                    json!({"slot": slot_id}),
                 )
                 .await;
-                clear_slot_runtime(&runtime_status, idx).await;
+                clear_slot_runtime(&runtime_status, slot_id).await;
                 let runtime_snapshot = runtime_status.read().await.clone();
                 let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                let _ = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots);
+                let _ = host.refresh_status(runtime_snapshot, has_ready_slots);
                 break;
             }
 
-            {
+            // A client that cannot be built (say, a bad CA path) waits for
+            // the settings to be fixed.
+            let update = clients.update(&app_config_rx.borrow(), &cfg);
+            let client = match update {
+                Ok(client) => client,
+                Err(err) => {
+                    if let Some(err) = err {
+                        warn!("slot {} client setup failed: {}", slot_id, err);
+                        let _ = record_wake_error(&runtime_status, slot_id, &err, current_policy, clock.now_ms()).await;
+                        let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
+                        let _ = host.refresh_status(runtime_status.read().await.clone(), has_ready_slots);
+                    }
+                    if wait_for_config_change(slot_id, &mut stop_rx, &mut config_rx, &mut app_config_rx).await {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            // Hold wakes while either circuit is open. The quota poller
+            // probes its own circuit; an open wake circuit lets one retry
+            // through as its probe once it is due.
+            let (quota_circuit, wake_circuit) = {
                 let runtime = runtime_status.read().await;
-                if runtime
-                    .slots
-                    .get(idx)
-                    .is_some_and(|slot| slot.auto_disabled)
-                {
-                    info!("slot {} auto-disabled, stopping wake scheduler", idx + 1);
-                    let _ = log_scheduler_event(
-                        &app,
-                        &cfg,
-                        "wake.scheduler.auto-disabled",
-                        json!({"slot": idx + 1, "source": "quota-auto-disable"}),
-                    )
-                    .await;
+                runtime
+                    .slot(slot_id)
+                    .map(|slot| (slot.quota_circuit.clone(), slot.wake_circuit.clone()))
+                    .unwrap_or_default()
+            };
+            if !quota_circuit.is_closed() {
+                if wait_for_wake_tick(slot_id, &mut stop_rx, &mut config_rx, &mut app_config_rx).await {
                     break;
                 }
+                continue;
             }
-            {
-                let runtime = runtime_status.read().await;
-                if runtime
-                    .slots
-                    .get(idx)
-                    .is_some_and(|slot| slot.wake_auto_disabled)
-                {
-                    info!("slot {} wake auto-disabled, stopping wake scheduler", idx + 1);
-                    let _ = log_scheduler_event(
-                        &app,
-                        &cfg,
-                        "wake.scheduler.auto-disabled",
-                        json!({"slot": idx + 1, "source": "wake-auto-disable"}),
-                    )
-                    .await;
-                    break;
+            if wake_circuit.state == CircuitState::Open {
+                if !begin_circuit_probe(&runtime_status, slot_id, CircuitEndpoint::Wake, clock.now_ms()).await {
+                    if wait_for_wake_tick(slot_id, &mut stop_rx, &mut config_rx, &mut app_config_rx).await {
+                        break;
+                    }
+                    continue;
                 }
+                info!("slot {} wake circuit half-open, probing", slot_id);
+                let _ = log_scheduler_event(
+                    &host,
+                    &cfg,
+                    "wake.scheduler.circuit-half-open",
+                    json!({"slot": slot_id, "failed_probes": wake_circuit.failed_probes}),
+                )
+                .await;
             }
 
+            // Detect a system sleep since the last tick
+            let now_wall_ms = clock.now_ms();
+            let now_tick = clock.instant();
+            let suspended_ms = (now_wall_ms - last_tick_wall_ms)
+                - now_tick.saturating_duration_since(last_tick).as_millis() as i64;
+            if suspended_ms >= RESUME_GAP_MS {
+                handle_resume(&host, &cfg, &schedule, &*clock, last_tick_wall_ms, now_wall_ms, suspended_ms).await;
+            }
+            last_tick_wall_ms = now_wall_ms;
+            last_tick = now_tick;
+
             // Get current schedule state
             let sched = schedule.read().await.clone();
 
             // Check if we should fire a wake request
-            let schedule_reason = should_fire_wake(&cfg, &sched);
+            let schedule_reason = should_fire_wake(&cfg, &sched, &*clock);
             let wake_error_count = {
                 let runtime = runtime_status.read().await;
                 runtime
-                    .slots
-                    .get(idx)
+                    .slot(slot_id)
                     .map(|slot| slot.wake_consecutive_errors)
                     .unwrap_or(0)
             };
             let wake_pending = {
                 let runtime = runtime_status.read().await;
                 runtime
-                    .slots
-                    .get(idx)
+                    .slot(slot_id)
                     .is_some_and(|slot| slot.wake_pending)
             };
             let should_retry_after_errors = {
                 let runtime = runtime_status.read().await;
                 runtime
-                    .slots
-                    .get(idx)
+                    .slot(slot_id)
                     .is_some_and(|slot| slot.wake_consecutive_errors > 0 && !slot.wake_pending)
             };
-            let wake_window_active = should_retry_quota_while_wake_pending(&schedule, &runtime_status, idx)
+            let wake_window_active = should_retry_quota_while_wake_pending(&schedule, &runtime_status, slot_id, &*clock)
                 .await;
             let wake_retry_due = wake_pending && !wake_window_active && !sched.wake_timeout_retry_fired;
 
             if schedule_reason.is_some() && wake_pending && !wake_retry_due {
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "wake.scheduler.duplicate-suppressed",
                     json!({
-                        "slot": idx + 1,
+                        "slot": slot_id,
                         "reason": schedule_reason.as_deref().unwrap_or("unknown"),
                     }),
                 )
                 .await;
                 let mut sched_mut = schedule.write().await;
                 let old_sched = sched_mut.clone();
-                update_schedule_markers(&cfg, &old_sched, &mut sched_mut);
-                info!("slot {} wake already pending; skipping duplicate wake", idx + 1);
+                update_schedule_markers(&cfg, &old_sched, &mut sched_mut, &*clock);
+                info!("slot {} wake already pending; skipping duplicate wake", slot_id);
             } else if schedule_reason.is_some() || should_retry_after_errors || wake_retry_due {
                 let is_required_now =
-                    is_wake_required(&client, &runtime_status, &cfg, idx).await;
+                    is_wake_required(client, &runtime_status, &cfg, slot_id, &*clock).await;
 
                 if !is_required_now {
                     let _ = log_scheduler_event(
-                        &app,
+                        &host,
                         &cfg,
                         "wake.scheduler.condition-not-ready",
                         json!({
-                            "slot": idx + 1,
+                            "slot": slot_id,
                             "schedule_reason": schedule_reason.clone(),
                             "should_retry_after_errors": should_retry_after_errors,
                             "wake_retry_due": wake_retry_due,
@@ -462,33 +735,33 @@ impl SchedulerManager {
                     )
                     .await;
                     if should_retry_after_errors || wake_retry_due {
-                        clear_wake_state(&runtime_status, idx).await;
+                        clear_wake_state(&runtime_status, slot_id).await;
                         let _ = log_scheduler_event(
-                            &app,
+                            &host,
                             &cfg,
                             "wake.pending-cleared",
                             json!({
-                                "slot": idx + 1,
+                                "slot": slot_id,
                                 "reason": if should_retry_after_errors { "no-longer-requires-wake" } else { "retry-window-expired" },
                             }),
                         )
                         .await;
                     }
                     if let Some(reason) = schedule_reason.as_deref() {
-                        info!("slot {} wake condition not ready ({})", idx + 1, reason);
+                        info!("slot {} wake condition not ready ({})", slot_id, reason);
                     } else if wake_retry_due {
-                        info!("slot {} wake retry window elapsed; wake no longer required", idx + 1);
+                        info!("slot {} wake retry window elapsed; wake no longer required", slot_id);
                     } else {
                         info!(
                             "slot {} retrying wake after failures but reset is not yet active",
-                            idx + 1
+                            slot_id
                         );
                     }
 
                     if schedule_reason.is_some() {
                         let mut sched_mut = schedule.write().await;
                         let old_sched = sched_mut.clone();
-                        update_schedule_markers(&cfg, &old_sched, &mut sched_mut);
+                        update_schedule_markers(&cfg, &old_sched, &mut sched_mut, &*clock);
                     }
                 } else {
                     let reason = schedule_reason
@@ -500,13 +773,13 @@ impl SchedulerManager {
                                 "retrying wake after failures".to_string()
                             }
                         });
-                    info!("slot {} wake condition met: {}", idx + 1, reason);
+                    info!("slot {} wake condition met: {}", slot_id, reason);
                     let _ = log_scheduler_event(
-                        &app,
+                        &host,
                         &cfg,
                         "wake.scheduler.wake-attempt",
                         json!({
-                            "slot": idx + 1,
+                            "slot": slot_id,
                             "reason": reason,
                             "wake_pending": wake_pending,
                             "wake_error_count": wake_error_count,
@@ -517,13 +790,13 @@ impl SchedulerManager {
                     .await;
 
                     if let Err(err) = client.send_wake_request(&cfg).await {
-                        warn!("slot {} scheduled wake failed: {}", idx + 1, err);
+                        warn!("slot {} scheduled wake failed: {}", slot_id, err);
                         let _ = log_scheduler_event(
-                            &app,
+                            &host,
                             &cfg,
                             "wake.scheduler.wake-attempt-failed",
                             json!({
-                                "slot": idx + 1,
+                                "slot": slot_id,
                                 "reason": reason,
                                 "error": err,
                             }),
@@ -531,72 +804,63 @@ impl SchedulerManager {
                         .await;
                         let consecutive_errors = record_wake_error(
                             &runtime_status,
-                            idx,
+                            slot_id,
                             &err,
-                            current_policy.max_consecutive_errors,
+                            current_policy,
+                            clock.now_ms(),
                         )
                         .await;
+                        raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeFailed(err)).await;
                         if consecutive_errors >= current_policy.max_consecutive_errors {
+                            // Only the first trip alerts; failed probes just reopen.
+                            if consecutive_errors == current_policy.max_consecutive_errors {
+                                raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeAutoDisabled).await;
+                            }
+                            let next_probe_epoch_ms = circuit_next_probe(&runtime_status, slot_id, CircuitEndpoint::Wake).await;
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "wake.scheduler.auto-disabled",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "consecutive_errors": consecutive_errors,
+                                    "next_probe_epoch_ms": next_probe_epoch_ms,
                                 }),
                             )
                             .await;
                             let runtime_snapshot = runtime_status.read().await.clone();
                             let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                            let _ = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots);
-                            break;
+                            let _ = host.refresh_status(runtime_snapshot, has_ready_slots);
                         }
                     } else {
-                        info!("slot {} scheduled wake fired", idx + 1);
+                        info!("slot {} scheduled wake fired", slot_id);
                         let _ = log_scheduler_event(
-                            &app,
+                            &host,
                             &cfg,
                             "wake.scheduler.wake-attempt-success",
                             json!({
-                                "slot": idx + 1,
+                                "slot": slot_id,
                                 "reason": reason,
                                 "retrying_after_errors": should_retry_after_errors,
                             }),
                         )
                         .await;
 
-                    let pre_reset_marker = {
-                        let runtime = runtime_status.read().await;
-                        runtime
-                            .slots
-                            .get(idx)
-                                .and_then(|slot| slot.last_updated_epoch_ms)
-                        };
-
-                        // Update schedule markers after successful wake
-                        let mut sched_mut = schedule.write().await;
-                        let old_sched = sched_mut.clone();
-                        update_schedule_markers(&cfg, &old_sched, &mut sched_mut);
-                        if wake_retry_due {
-                            sched_mut.wake_timeout_retry_fired = true;
-                            sched_mut.wake_retry_window_deadline = None;
-                        } else {
-                            sched_mut.wake_retry_window_deadline = Some(
-                                Instant::now()
-                                    + Duration::from_secs(
-                                        current_policy.wake_quota_retry_window_minutes * 60,
-                                    ),
-                            );
-                            sched_mut.wake_timeout_retry_fired = false;
-                        }
-                        mark_wake_attempt(&runtime_status, idx, pre_reset_marker).await;
+                        let pre_reset_marker = arm_wake_confirmation(
+                            &cfg,
+                            &schedule,
+                            &runtime_status,
+                            current_policy,
+                            wake_retry_due,
+                            &*clock,
+                        )
+                        .await;
                         let _ = log_scheduler_event(
-                            &app,
+                            &host,
                             &cfg,
                             "wake.pending-set",
                             json!({
-                                "slot": idx + 1,
+                                "slot": slot_id,
                                 "pre_reset_marker": pre_reset_marker,
                                 "forced_retry": wake_retry_due,
                             }),
@@ -606,124 +870,126 @@ impl SchedulerManager {
                         // Trigger immediate quota poll to verify wake worked
                         poll_now_signal = !poll_now_signal;
                         let _ = poll_now_tx.send(poll_now_signal);
-                        info!("slot {} triggered immediate quota poll", idx + 1);
+                        info!("slot {} triggered immediate quota poll", slot_id);
                     }
                 }
             }
 
-            // Sleep for 60 seconds
-            tokio::select! {
-                _ = stop_rx.changed() => {
-                    if *stop_rx.borrow() {
-                        break;
-                    }
-                }
-                _ = config_rx.changed() => {
-                    info!("slot {} wake scheduler detected config change", idx + 1);
-                }
-                _ = app_config_rx.changed() => {
-                    info!("slot {} wake scheduler detected policy change", idx + 1);
-                }
-                _ = time::sleep(Duration::from_secs(WAKE_RETRY_INTERVAL_SECONDS)) => {}
+            if wait_for_wake_tick(slot_id, &mut stop_rx, &mut config_rx, &mut app_config_rx).await {
+                break;
             }
         }
 
-        info!("slot {} wake scheduler stopped", idx + 1);
+        info!("slot {} wake scheduler stopped", slot_id);
     }
 
     /// Quota poller task - fetches quota at configured intervals
-    async fn quota_poller_task(
-        idx: usize,
-        app: AppHandle,
-        mut config_rx: watch::Receiver<KeySlotConfig>,
-        mut app_config_rx: watch::Receiver<AppConfig>,
-        schedule: Arc<RwLock<SlotSchedule>>,
-        runtime_status: Arc<RwLock<RuntimeStatus>>,
-        mut stop_rx: watch::Receiver<bool>,
-        poll_now_tx: watch::Sender<bool>,
-        mut poll_now_rx: watch::Receiver<bool>,
-    ) {
-        info!("slot {} quota poller started", idx + 1);
+    async fn quota_poller_task(ctx: SlotTaskContext, mut poll_now_rx: watch::Receiver<bool>) {
+        let SlotTaskContext {
+            slot_id,
+            host,
+            mut config_rx,
+            mut app_config_rx,
+            schedule,
+            runtime_status,
+            mut stop_rx,
+            poll_now_tx,
+            alerts,
+            clock,
+        } = ctx;
+        info!("slot {} quota poller started", slot_id);
         let initial_cfg = config_rx.borrow().clone();
-        let initial_app_cfg = app_config_rx.borrow().clone();
         let _ = log_scheduler_event(
-            &app,
+            &host,
             &initial_cfg,
             "quota-poller.task-started",
-            json!({"slot": idx + 1}),
+            json!({"slot": slot_id}),
         )
         .await;
         let mut poll_now_signal = false;
+        let mut burn = BurnTracker::default();
 
-        let client = match ApiClient::new(Some(app.clone()), initial_app_cfg.debug, initial_app_cfg.mock_url) {
-            Ok(client) => client,
-            Err(err) => {
-                warn!("slot {} client setup failed: {}", idx + 1, err);
-                let current_policy = SchedulerPolicy::from(&*app_config_rx.borrow());
-                let _ = record_quota_error(
-                    &runtime_status,
-                    idx,
-                    &err,
-                    current_policy.max_consecutive_errors,
-                )
-                .await;
-                let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                let _ = tray::refresh_tray(
-                    &app,
-                    runtime_status.read().await.clone(),
-                    has_ready_slots,
-                );
-                return;
-            }
-        };
+        let mut clients = SlotClient::new(host.clone(), clock.clone());
 
-        // Send initial wake request
+        // Send initial wake request, unless a restored wake is still awaiting
+        // confirmation or wake was auto-disabled before the restart
         let cfg = config_rx.borrow().clone();
         let initial_policy = SchedulerPolicy::from(&*app_config_rx.borrow());
-        if is_wake_required(&client, &runtime_status, &cfg, idx).await {
+        let restored_hold = {
+            let runtime = runtime_status.read().await;
+            runtime
+                .slot(slot_id)
+                .is_some_and(|slot| slot.wake_pending || slot.wake_auto_disabled())
+        };
+        let update = clients.update(&app_config_rx.borrow(), &cfg);
+        let initial_client = match update {
+            Ok(client) => Some(client),
+            Err(err) => {
+                if let Some(err) = err {
+                    warn!("slot {} client setup failed: {}", slot_id, err);
+                    let _ = record_quota_error(&runtime_status, slot_id, &err, initial_policy, clock.now_ms()).await;
+                    let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
+                    let _ = host.refresh_status(runtime_status.read().await.clone(), has_ready_slots);
+                }
+                None
+            }
+        };
+        let initial_wake = match initial_client {
+            Some(client) if !restored_hold => is_wake_required(client, &runtime_status, &cfg, slot_id, &*clock).await,
+            _ => false,
+        };
+        if restored_hold {
+            info!("slot {} skipping initial wake: restored wake state", slot_id);
+        } else if initial_client.is_none() {
+            info!("slot {} skipping initial wake: no HTTP client", slot_id);
+        } else if let Some(client) = initial_client.filter(|_| initial_wake) {
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "quota-poller.initial-wake-trigger",
-                    json!({"slot": idx + 1}),
+                    json!({"slot": slot_id}),
                 )
                 .await;
                 if let Err(err) = client.send_wake_request(&cfg).await {
-                    warn!("slot {} initial wake failed: {}", idx + 1, err);
+                    warn!("slot {} initial wake failed: {}", slot_id, err);
                     let _ = log_scheduler_event(
-                        &app,
+                        &host,
                         &cfg,
                         "quota-poller.initial-wake-failed",
                         json!({
-                            "slot": idx + 1,
+                            "slot": slot_id,
                             "error": err
                         }),
                     )
                     .await;
-                    let _ = record_wake_error(
+                    let consecutive_errors = record_wake_error(
                         &runtime_status,
-                        idx,
+                        slot_id,
                         &err,
-                        initial_policy.max_consecutive_errors,
+                        initial_policy,
+                        clock.now_ms(),
                     )
                     .await;
+                    raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeFailed(err)).await;
+                    if consecutive_errors >= initial_policy.max_consecutive_errors {
+                        raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeAutoDisabled).await;
+                    }
                     let runtime_snapshot = runtime_status.read().await.clone();
                     let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                    let _ = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots);
+                    let _ = host.refresh_status(runtime_snapshot, has_ready_slots);
                 } else {
                 let pre_reset_marker = {
                     let runtime = runtime_status.read().await;
                     runtime
-                        .slots
-                        .get(idx)
+                        .slot(slot_id)
                         .and_then(|slot| slot.last_updated_epoch_ms)
                     };
-                        mark_wake_attempt(&runtime_status, idx, pre_reset_marker).await;
+                        mark_wake_attempt(&runtime_status, slot_id, pre_reset_marker).await;
                     {
                         let mut sched_mut = schedule.write().await;
                         sched_mut.wake_timeout_retry_fired = false;
                         sched_mut.wake_retry_window_deadline = Some(
-                            Instant::now()
+                            clock.instant()
                                 + Duration::from_secs(
                                     initial_policy.wake_quota_retry_window_minutes * 60,
                                 ),
@@ -732,18 +998,18 @@ impl SchedulerManager {
                     poll_now_signal = !poll_now_signal;
                     let _ = poll_now_tx.send(poll_now_signal);
                     let _ = log_scheduler_event(
-                        &app,
+                        &host,
                         &cfg,
                         "quota-poller.initial-wake-poll",
-                        json!({"slot": idx + 1}),
+                        json!({"slot": slot_id}),
                     )
                     .await;
-                    info!("slot {} triggered initial wake poll", idx + 1);
+                    info!("slot {} triggered initial wake poll", slot_id);
                 }
         } else {
             info!(
                 "slot {} skipping initial wake because wake conditions are not met",
-                idx + 1
+                slot_id
             );
         }
 
@@ -766,28 +1032,27 @@ impl SchedulerManager {
                 // Determine sleep duration — recomputed each iteration from
                 // the last-known retry/wake state.
                 let retry_quota_now_for_sleep = {
-                    should_retry_quota_while_wake_pending(&schedule, &runtime_status, idx).await
+                    should_retry_quota_while_wake_pending(&schedule, &runtime_status, slot_id, &*clock).await
                 };
                 let consecutive_errors_for_sleep = if retry_quota_now_for_sleep {
                     0
                 } else {
                     let runtime = runtime_status.read().await;
                     runtime
-                        .slots
-                        .get(idx)
+                        .slot(slot_id)
                         .map(|slot| slot.quota_consecutive_errors)
                         .unwrap_or(0)
                 };
-                let sleep_minutes = if consecutive_errors_for_sleep == 0 {
-                    if retry_quota_now_for_sleep {
-                        1
-                    } else {
-                        cfg_for_sleep.poll_interval_minutes.max(1)
-                    }
-                } else {
-                    let backoff = cfg_for_sleep.poll_interval_minutes.max(1)
-                        .saturating_mul(1u64 << consecutive_errors_for_sleep.min(6));
-                    backoff.min(current_policy_for_sleep.quota_backoff_cap_minutes)
+                let sleep_minutes = poll_sleep_minutes(
+                    &cfg_for_sleep,
+                    current_policy_for_sleep,
+                    retry_quota_now_for_sleep,
+                    consecutive_errors_for_sleep,
+                );
+                // An open circuit sleeps until its probe instead.
+                let sleep = match circuit_next_probe(&runtime_status, slot_id, CircuitEndpoint::Quota).await {
+                    Some(probe_ms) => Duration::from_millis((probe_ms - clock.now_ms()).max(0) as u64),
+                    None => Duration::from_secs(sleep_minutes * 60),
                 };
 
                 tokio::select! {
@@ -797,15 +1062,15 @@ impl SchedulerManager {
                         }
                     }
                     _ = config_rx.changed() => {
-                        info!("slot {} quota poller detected config change", idx + 1);
+                        info!("slot {} quota poller detected config change", slot_id);
                     }
                     _ = app_config_rx.changed() => {
-                        info!("slot {} quota poller detected policy change", idx + 1);
+                        info!("slot {} quota poller detected policy change", slot_id);
                     }
                     _ = poll_now_rx.changed() => {
-                        info!("slot {} quota poller received immediate poll signal", idx + 1);
+                        info!("slot {} quota poller received immediate poll signal", slot_id);
                     }
-                    _ = time::sleep(Duration::from_secs(sleep_minutes * 60)) => {}
+                    _ = time::sleep(sleep) => {}
                 }
             }
 
@@ -814,55 +1079,78 @@ impl SchedulerManager {
 
             if *stop_rx.borrow() {
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "quota-poller.task-stopped",
-                    json!({"slot": idx + 1, "reason": "stop-signal"}),
+                    json!({"slot": slot_id, "reason": "stop-signal"}),
                 )
                 .await;
                 break;
             }
             if !cfg.enabled || cfg.api_key.trim().is_empty() {
-                info!("slot {} disabled config, stopping quota poller", idx + 1);
+                info!("slot {} disabled config, stopping quota poller", slot_id);
                 let _ = log_scheduler_event(
-                    &app,
+                    &host,
                     &cfg,
                     "quota-poller.config-disabled",
-                    json!({"slot": idx + 1}),
+                    json!({"slot": slot_id}),
                 )
                 .await;
-                clear_slot_runtime(&runtime_status, idx).await;
+                clear_slot_runtime(&runtime_status, slot_id).await;
                 let runtime_snapshot = runtime_status.read().await.clone();
                 let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                let _ = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots);
+                let _ = host.refresh_status(runtime_snapshot, has_ready_slots);
                 break;
             }
 
-            {
-                let runtime = runtime_status.read().await;
-                if let Some(slot) = runtime.slots.get(idx) {
-                    if slot.auto_disabled {
-                        info!("slot {} auto-disabled, stopping quota poller", idx + 1);
-                        let _ = log_scheduler_event(
-                            &app,
-                            &cfg,
-                            "quota-poller.auto-disabled",
-                            json!({"slot": idx + 1}),
-                        )
-                        .await;
+            // Without a client, poll again once the settings change.
+            let update = clients.update(&app_config_rx.borrow(), &cfg);
+            let client = match update {
+                Ok(client) => client,
+                Err(err) => {
+                    if let Some(err) = err {
+                        warn!("slot {} client setup failed: {}", slot_id, err);
+                        let _ = record_quota_error(&runtime_status, slot_id, &err, current_policy, clock.now_ms()).await;
+                        let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
+                        let _ = host.refresh_status(runtime_status.read().await.clone(), has_ready_slots);
+                    }
+                    if wait_for_config_change(slot_id, &mut stop_rx, &mut config_rx, &mut app_config_rx).await {
                         break;
                     }
-                } else {
-                    let _ = log_scheduler_event(
-                        &app,
-                        &cfg,
-                        "quota-poller.slot-missing",
-                        json!({"slot": idx + 1}),
-                    )
-                    .await;
-                    break;
+                    first_iteration = true;
+                    continue;
+                }
+            };
+
+            let quota_circuit = {
+                let runtime = runtime_status.read().await;
+                runtime.slot(slot_id).map(|slot| slot.quota_circuit.clone())
+            };
+            let Some(quota_circuit) = quota_circuit else {
+                let _ = log_scheduler_event(
+                    &host,
+                    &cfg,
+                    "quota-poller.slot-missing",
+                    json!({"slot": slot_id}),
+                )
+                .await;
+                break;
+            };
+            // An open circuit polls only as a probe, once it is due.
+            if quota_circuit.state == CircuitState::Open {
+                if !begin_circuit_probe(&runtime_status, slot_id, CircuitEndpoint::Quota, clock.now_ms()).await {
+                    continue;
                 }
+                info!("slot {} quota circuit half-open, probing", slot_id);
+                let _ = log_scheduler_event(
+                    &host,
+                    &cfg,
+                    "quota-poller.circuit-half-open",
+                    json!({"slot": slot_id, "failed_probes": quota_circuit.failed_probes}),
+                )
+                .await;
             }
+            let probing = quota_circuit.state != CircuitState::Closed;
 
             // Fetch quota
             let retry_quota_now;
@@ -872,22 +1160,21 @@ impl SchedulerManager {
                     let was_wake_pending = {
                         let runtime = runtime_status.read().await;
                         runtime
-                            .slots
-                            .get(idx)
+                            .slot(slot_id)
                             .and_then(|slot| slot.wake_reset_epoch_ms)
                     };
                         let wake_outcome = if runtime_status
                             .read()
                             .await
-                            .slots
-                            .get(idx)
+                            .slot(slot_id)
                             .is_some_and(|slot| slot.wake_pending)
                         {
                             complete_wake_if_advanced(
                                 &runtime_status,
-                                idx,
+                                slot_id,
                                 snapshot.next_reset_epoch_ms,
-                                current_policy.max_consecutive_errors,
+                                current_policy,
+                                clock.now_ms(),
                             )
                             .await
                         } else {
@@ -896,55 +1183,61 @@ impl SchedulerManager {
                     match wake_outcome {
                         WakeConfirmOutcome::Confirmed => {
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "quota-poller.wake-confirmed",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "previous_next_reset_ms": was_wake_pending,
                                     "next_reset_ms": snapshot.next_reset_epoch_ms,
                                 }),
                             )
                             .await;
+                            raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeConfirmed).await;
                         }
                         WakeConfirmOutcome::FailedMissing => {
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "quota-poller.wake-confirmation-failed",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "reason": "missing_next_reset_time",
                                     "previous_next_reset_ms": was_wake_pending,
                                 }),
                             )
                             .await;
+                            let event = AlertEvent::WakeFailed("quota shows no reset time after wake".into());
+                            raise_alert(&alerts, &host, &app_config_rx, &cfg, event).await;
                         }
                         WakeConfirmOutcome::FailedNotAdvanced => {
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "quota-poller.wake-confirmation-failed",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "reason": "next_reset_time_not_advanced",
                                     "previous_next_reset_ms": was_wake_pending,
                                     "next_reset_ms": snapshot.next_reset_epoch_ms,
                                 }),
                             )
                             .await;
+                            let event = AlertEvent::WakeFailed("reset time did not advance after wake".into());
+                            raise_alert(&alerts, &host, &app_config_rx, &cfg, event).await;
                         }
                         WakeConfirmOutcome::AutoDisabled => {
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "wake.scheduler.auto-disabled",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "reason": "auto-disabled_during_confirmation",
                                 }),
                             )
                             .await;
+                            raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::WakeAutoDisabled).await;
                         }
                         WakeConfirmOutcome::NotPending => {}
                     };
@@ -952,16 +1245,17 @@ impl SchedulerManager {
                     wake_window_active = should_retry_quota_while_wake_pending(
                         &schedule,
                         &runtime_status,
-                        idx,
+                        slot_id,
+                        &*clock,
                     )
                     .await;
 
                     let _ = log_scheduler_event(
-                        &app,
+                        &host,
                         &cfg,
                         "quota-poller.quota-success",
                         json!({
-                            "slot": idx + 1,
+                            "slot": slot_id,
                             "next_reset_ms": snapshot.next_reset_epoch_ms,
                             "wake_window_active": wake_window_active,
                         }),
@@ -971,8 +1265,7 @@ impl SchedulerManager {
                     let wake_pending = {
                         let runtime = runtime_status.read().await;
                         runtime
-                            .slots
-                            .get(idx)
+                            .slot(slot_id)
                             .is_some_and(|slot| slot.wake_pending)
                     };
                     if !wake_pending {
@@ -982,31 +1275,30 @@ impl SchedulerManager {
                     let consecutive_errors = {
                         let runtime = runtime_status.read().await;
                         runtime
-                            .slots
-                            .get(idx)
+                            .slot(slot_id)
                             .map(|slot| slot.quota_consecutive_errors)
                             .unwrap_or(0)
                     };
                     if consecutive_errors > 0 {
                         info!(
                             "slot {} recovered after {} consecutive quota error(s)",
-                            idx + 1,
+                            slot_id,
                             consecutive_errors
                         );
                     }
 
                     // Verify next_reset_time is in the future
-                    let now_ms = Local::now().timestamp_millis();
+                    let now_ms = clock.now_ms();
                     if let Some(next_reset) = snapshot.next_reset_epoch_ms {
                         if next_reset <= now_ms {
                             warn!(
                                 "slot {} next_reset_time {} is not in the future (now: {})",
-                                idx + 1, next_reset, now_ms
+                                slot_id, next_reset, now_ms
                             );
                         } else {
                             info!(
                                 "slot {} quota verified: next_reset in {} min",
-                                idx + 1,
+                                slot_id,
                                 (next_reset - now_ms) / 60_000
                             );
                         }
@@ -1025,63 +1317,161 @@ impl SchedulerManager {
                     let (model_calls_5h, tokens_5h) = client
                         .fetch_model_usage_5h(&cfg, snapshot.next_reset_epoch_ms)
                         .await;
-                    let now_iso = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+                    let now_iso = clock.now_local().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+                    let forecast = burn.observe(
+                        clock.now_ms(),
+                        snapshot.percentage,
+                        tokens_5h,
+                        snapshot.next_reset_epoch_ms,
+                    );
 
                         // Update runtime status for UI
-                    clear_quota_error(&runtime_status, idx).await;
-                    {
+                    clear_quota_error(&runtime_status, slot_id).await;
+                    if probing {
+                        info!("slot {} quota probe succeeded, circuit closed", slot_id);
+                        let _ = log_scheduler_event(
+                            &host,
+                            &cfg,
+                            "quota-poller.circuit-closed",
+                            json!({"slot": slot_id, "failed_probes": quota_circuit.failed_probes}),
+                        )
+                        .await;
+                    }
+                    let (previous, was_exhausting) = {
                         let mut runtime = runtime_status.write().await;
-                        if let Some(current) = runtime.slots.get_mut(idx) {
-                            current.slot = idx + 1;
+                        let current = runtime.slot_mut(slot_id);
+                        let previous = current.as_ref().map(|slot| {
+                            (slot.percentage, slot.timer_active, slot.last_updated_epoch_ms)
+                        });
+                        let was_exhausting = current
+                            .as_ref()
+                            .and_then(|slot| slot.forecast.as_ref())
+                            .is_some_and(|f| f.will_exhaust);
+                        if let Some(current) = current {
+                            current.slot = slot_id;
                             current.name = cfg.name.clone();
                             current.enabled = true;
                             current.timer_active = snapshot.timer_active;
                             current.percentage = Some(snapshot.percentage);
                             current.next_reset_hms = snapshot.next_reset_hms.clone();
                             current.last_updated_epoch_ms = snapshot.next_reset_epoch_ms;
-                            current.auto_disabled = false;
                             current.total_model_calls_5h = model_calls_5h;
                             current.total_tokens_5h = tokens_5h;
                             current.quota_last_updated = Some(now_iso.clone());
+                            current.forecast = forecast.clone();
+                        }
+                        (previous, was_exhausting)
+                    };
+
+                    // Percentage is `None` until the first poll after start,
+                    // so nothing counts as a crossing on that poll.
+                    let transition = QuotaTransition {
+                        previous_percentage: previous.and_then(|(pct, _, _)| pct),
+                        percentage: snapshot.percentage,
+                        previous_timer_active: previous
+                            .filter(|(pct, _, _)| pct.is_some())
+                            .map(|(_, active, _)| active),
+                        timer_active: snapshot.timer_active,
+                        previous_reset_ms: previous.and_then(|(_, _, reset)| reset),
+                        reset_ms: snapshot.next_reset_epoch_ms,
+                    };
+                    if let Some(previous_pct) = transition.previous_percentage {
+                        for threshold in webhooks::crossed_thresholds(&host, previous_pct, snapshot.percentage).await {
+                            let _ = log_scheduler_event(
+                                &host,
+                                &cfg,
+                                webhooks::THRESHOLD_EVENT,
+                                json!({
+                                    "slot": slot_id,
+                                    "threshold": threshold,
+                                    "percentage": snapshot.percentage,
+                                    "previous_percentage": previous_pct,
+                                }),
+                            )
+                            .await;
+                        }
+                    }
+                    // A reset happened when the window ended, or rolled straight
+                    // into a new one between two polls.
+                    let rolled_over = transition.window_started() && transition.previous_timer_active == Some(true);
+                    if transition.window_ended() || rolled_over {
+                        let _ = log_scheduler_event(
+                            &host,
+                            &cfg,
+                            webhooks::RESET_EVENT,
+                            json!({
+                                "slot": slot_id,
+                                "previous_reset_ms": transition.previous_reset_ms,
+                                "next_reset_ms": transition.reset_ms,
+                                "timer_active": transition.timer_active,
+                            }),
+                        )
+                        .await;
+                    }
+                    raise_alert(&alerts, &host, &app_config_rx, &cfg, AlertEvent::Quota(transition)).await;
+
+                    if let Some(f) = forecast.as_ref().filter(|f| f.will_exhaust && !was_exhausting) {
+                        info!(
+                            "slot {} forecast: quota exhausted before reset at {:.1}%/h",
+                            slot_id,
+                            f.burn_rate_pct_per_hour
+                        );
+                        let _ = log_scheduler_event(
+                            &host,
+                            &cfg,
+                            "quota-forecast.exhaustion-predicted",
+                            json!({"slot": slot_id, "forecast": f}),
+                        )
+                        .await;
+                        if let Some(at_ms) = f.exhausts_at_epoch_ms {
+                            let event = AlertEvent::ForecastExhaustion { at_ms };
+                            raise_alert(&alerts, &host, &app_config_rx, &cfg, event).await;
                         }
                     }
 
+                    history::record(
+                        &host,
+                        UsageSample::from_poll(slot_id, &snapshot, model_calls_5h, tokens_5h),
+                    )
+                    .await;
+
                     // Emit event to frontend so it can refresh stats
-                    let _ = app.emit("quota-updated", serde_json::json!({
-                        "slot": idx + 1,
+                    host.emit("quota-updated", serde_json::json!({
+                        "slot": slot_id,
                         "percentage": snapshot.percentage,
                         "timer_active": snapshot.timer_active,
                         "next_reset_hms": snapshot.next_reset_hms,
                         "next_reset_epoch_ms": snapshot.next_reset_epoch_ms,
                         "total_model_calls_5h": model_calls_5h,
                         "total_tokens_5h": tokens_5h,
-                        "quota_last_updated": now_iso
+                        "quota_last_updated": now_iso,
+                        "forecast": forecast
                     }));
 
-                    info!("slot {} quota refreshed (next_reset: {:?})", idx + 1, snapshot.next_reset_epoch_ms);
+                    info!("slot {} quota refreshed (next_reset: {:?})", slot_id, snapshot.next_reset_epoch_ms);
                 }
                 Err(err) => {
-                    retry_quota_now = should_retry_quota_while_wake_pending(&schedule, &runtime_status, idx)
+                    retry_quota_now = should_retry_quota_while_wake_pending(&schedule, &runtime_status, slot_id, &*clock)
                         .await;
                     if retry_quota_now {
                         let mut runtime = runtime_status.write().await;
-                        if let Some(current) = runtime.slots.get_mut(idx) {
+                        if let Some(current) = runtime.slot_mut(slot_id) {
                             current.last_error = Some(format!(
                                 "quota request failed during wake verification retry: {err}"
                             ));
                         }
                         warn!(
                             "slot {} poll failed during wake verification (retrying every minute for the next {} minutes): {}",
-                            idx + 1,
+                            slot_id,
                             current_policy.wake_quota_retry_window_minutes,
                             err
                         );
                         let _ = log_scheduler_event(
-                            &app,
+                            &host,
                             &cfg,
                             "quota-poller.retry-in-window",
                             json!({
-                                "slot": idx + 1,
+                                "slot": slot_id,
                                 "error": err,
                                 "window_minutes": current_policy.wake_quota_retry_window_minutes,
                             }),
@@ -1090,43 +1480,38 @@ impl SchedulerManager {
                     } else {
                         let consecutive_errors = record_quota_error(
                             &runtime_status,
-                            idx,
+                            slot_id,
                             &err,
-                            current_policy.max_consecutive_errors,
+                            current_policy,
+                            clock.now_ms(),
                         )
                         .await;
                         warn!(
                             "slot {} poll failed ({}/{} consecutive): {}",
-                            idx + 1,
+                            slot_id,
                             consecutive_errors,
                             current_policy.max_consecutive_errors,
                             err
                         );
 
                         if consecutive_errors >= current_policy.max_consecutive_errors {
+                            let next_probe_epoch_ms =
+                                circuit_next_probe(&runtime_status, slot_id, CircuitEndpoint::Quota).await;
                             let _ = log_scheduler_event(
-                                &app,
+                                &host,
                                 &cfg,
                                 "quota-poller.auto-disabled",
                                 json!({
-                                    "slot": idx + 1,
+                                    "slot": slot_id,
                                     "consecutive_errors": consecutive_errors,
+                                    "next_probe_epoch_ms": next_probe_epoch_ms,
                                 }),
                             )
                             .await;
                             error!(
-                                "slot {} auto-disabled after {} consecutive errors",
-                                idx + 1, consecutive_errors
+                                "slot {} quota circuit open after {} consecutive errors, probing every {} min",
+                                slot_id, consecutive_errors, current_policy.circuit_probe_minutes
                             );
-                            let mut runtime = runtime_status.write().await;
-                            if let Some(current) = runtime.slots.get_mut(idx) {
-                                current.auto_disabled = true;
-                            }
-                            let runtime_snapshot = runtime_status.read().await.clone();
-                            drop(runtime);
-                            let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-                            let _ = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots);
-                            break;
                         }
                     }
                 }
@@ -1135,35 +1520,51 @@ impl SchedulerManager {
             // Refresh tray
             let runtime_snapshot = runtime_status.read().await.clone();
             let has_ready_slots = has_enabled_slot(&app_config_rx.borrow());
-            if let Err(err) = tray::refresh_tray(&app, runtime_snapshot, has_ready_slots) {
-                error!("failed to refresh tray for slot {}: {}", idx + 1, err);
+            if let Err(err) = host.refresh_status(runtime_snapshot, has_ready_slots) {
+                error!("failed to refresh tray for slot {}: {}", slot_id, err);
             }
         }
 
-        info!("slot {} quota poller stopped", idx + 1);
+        info!("slot {} quota poller stopped", slot_id);
     }
 }
 
 /// Check if wake should fire based on current config and schedule state.
 /// Returns Some(reason) if should fire, None otherwise.
 /// Now supports multiple enabled modes - fires if ANY enabled mode triggers.
-fn should_fire_wake(
+pub(crate) fn should_fire_wake(
     slot_cfg: &KeySlotConfig,
     schedule: &SlotSchedule,
+    clock: &dyn Clock,
 ) -> Option<String> {
     // Check if any schedule mode is enabled
     let any_enabled = slot_cfg.schedule_interval_enabled
         || slot_cfg.schedule_times_enabled
-        || slot_cfg.schedule_after_reset_enabled;
+        || slot_cfg.schedule_after_reset_enabled
+        || slot_cfg.schedule_cron_enabled;
 
     if !any_enabled {
         return None;
     }
 
+    if let Some(reason) = scheduled_fire_reason(slot_cfg, schedule, clock) {
+        return Some(reason);
+    }
+
+    // Catch up on wakes missed during a system sleep
+    if schedule.missed_wakes > 0 {
+        return Some(format!("catch-up after resume ({} missed)", schedule.missed_wakes));
+    }
+
+    None
+}
+
+/// The enabled schedule mode due now, leaving out catch-up wakes.
+fn scheduled_fire_reason(slot_cfg: &KeySlotConfig, schedule: &SlotSchedule, clock: &dyn Clock) -> Option<String> {
     // Check interval mode
     if slot_cfg.schedule_interval_enabled {
         let interval = Duration::from_secs(slot_cfg.schedule_interval_minutes.max(1) * 60);
-        if schedule.last_interval_fire.elapsed() >= interval {
+        if clock.instant().saturating_duration_since(schedule.last_interval_fire) >= interval {
             return Some(format!(
                 "interval mode ({} min elapsed)",
                 slot_cfg.schedule_interval_minutes
@@ -1173,13 +1574,19 @@ fn should_fire_wake(
 
     // Check times mode
     if slot_cfg.schedule_times_enabled {
-        let now = Local::now();
-        let current_hm = format!("{:02}:{:02}", now.hour(), now.minute());
-
-        if slot_cfg.schedule_times.iter().any(|value| value == &current_hm) {
-            let marker = format!("{}-{}", now.format("%Y-%m-%d"), current_hm);
+        if let Some((time, marker)) = due_schedule_time(slot_cfg, clock) {
             if schedule.last_times_marker.as_ref() != Some(&marker) {
-                return Some(format!("times mode (matched {})", current_hm));
+                return Some(format!("times mode (matched {})", time));
+            }
+        }
+    }
+
+    // Check cron mode
+    if slot_cfg.schedule_cron_enabled {
+        let now = SlotZone::for_slot(slot_cfg).now(clock);
+        if let Some(expr) = cron_schedule::matching(&slot_cfg.schedule_cron, now) {
+            if schedule.last_cron_marker.as_deref() != Some(cron_schedule::marker(&now).as_str()) {
+                return Some(format!("cron mode (matched '{}')", expr));
             }
         }
     }
@@ -1188,9 +1595,12 @@ fn should_fire_wake(
     if slot_cfg.schedule_after_reset_enabled {
         if let Some(next_reset) = schedule.last_known_reset_epoch_ms {
             let target = next_reset + (slot_cfg.schedule_after_reset_minutes.max(1) as i64 * 60_000);
-            let now_ms = Local::now().timestamp_millis();
+            let now_ms = clock.now_ms();
 
-            if now_ms >= target && schedule.last_reset_marker != Some(next_reset) {
+            if now_ms >= target
+                && after_reset_day_allowed(slot_cfg, target)
+                && schedule.last_reset_marker != Some(next_reset)
+            {
                 return Some(format!(
                     "after-reset mode (reset + {} min)",
                     slot_cfg.schedule_after_reset_minutes
@@ -1202,24 +1612,202 @@ fn should_fire_wake(
     None
 }
 
-/// Update schedule markers after a successful wake.
-/// Now updates markers for all enabled modes since multiple can be active.
-fn update_schedule_markers(
+/// The configured `HH:MM` due in the current minute of the slot's zone and
+/// its dedup marker (`YYYY-MM-DD-HH:MM`, wall clock). Only on days in the
+/// slot's weekday mask; times skipped or repeated by DST resolve as in
+/// `SlotZone::resolve`, so each fires once.
+fn due_schedule_time(slot_cfg: &KeySlotConfig, clock: &dyn Clock) -> Option<(String, String)> {
+    let zone = SlotZone::for_slot(slot_cfg);
+    let now = zone.now(clock);
+    if !slot_zone::date_allowed(slot_cfg.schedule_days, &now) {
+        return None;
+    }
+    let minute = now.timestamp_millis().div_euclid(60_000);
+    slot_cfg
+        .schedule_times
+        .iter()
+        .find(|value| {
+            NaiveTime::parse_from_str(value, "%H:%M")
+                .ok()
+                .and_then(|time| zone.resolve(now.date_naive().and_time(time)))
+                .is_some_and(|at| at.div_euclid(60_000) == minute)
+        })
+        .map(|value| (value.clone(), now.format("%Y-%m-%d-%H:%M").to_string()))
+}
+
+/// Epoch ms of the configured `HH:MM` times that fell strictly between
+/// `from_ms` and `to_ms` on days in the slot's weekday mask.
+fn schedule_times_between(slot_cfg: &KeySlotConfig, zone: SlotZone, from_ms: i64, to_ms: i64) -> Vec<i64> {
+    let (Some(from), Some(to)) = (zone.at(from_ms), zone.at(to_ms)) else {
+        return Vec::new();
+    };
+    let times: Vec<NaiveTime> = slot_cfg
+        .schedule_times
+        .iter()
+        .filter_map(|value| NaiveTime::parse_from_str(value, "%H:%M").ok())
+        .collect();
+    from.date_naive()
+        .iter_days()
+        .take_while(|date| *date <= to.date_naive())
+        .filter(|date| slot_zone::day_allowed(slot_cfg.schedule_days, date.weekday()))
+        .flat_map(|date| times.iter().filter_map(move |time| zone.resolve(date.and_time(*time))))
+        .filter(|at| *at > from_ms && *at < to_ms)
+        .collect()
+}
+
+/// Current schedule and runtime state of every running slot.
+async fn snapshot_state(
+    registry: &SlotRegistry,
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    clock: &dyn Clock,
+) -> Vec<SlotState> {
+    let slots: Vec<(usize, PersistedSlot)> = registry
+        .read()
+        .await
+        .iter()
+        .map(|(slot_id, slot)| (*slot_id, slot.clone()))
+        .collect();
+    let runtime = runtime_status.read().await.clone();
+
+    let mut states = Vec::with_capacity(slots.len());
+    for (slot_id, slot) in slots {
+        let mut state = slot.schedule.read().await.to_state(clock);
+        state.slot = slot_id;
+        state.key_fingerprint = scheduler_state::key_fingerprint(&slot.config_rx.borrow().api_key);
+        if let Some(current) = runtime.slot(slot_id) {
+            state.capture_runtime(current);
+        }
+        states.push(state);
+    }
+    states.sort_by_key(|state| state.slot);
+    states
+}
+
+/// Called when a tick finds the wall clock ran ahead of the monotonic one:
+/// counts the interval, times and cron wakes that fell inside the sleep,
+/// queues catch-up wakes per the slot's `missed_fire_policy`, and moves the
+/// monotonic timers so the sleep does not delay the next regular fire.
+async fn handle_resume(
+    host: &HostHandle,
+    cfg: &KeySlotConfig,
+    schedule: &Arc<RwLock<SlotSchedule>>,
+    clock: &dyn Clock,
+    last_tick_ms: i64,
+    now_ms: i64,
+    suspended_ms: i64,
+) {
+    let zone = SlotZone::for_slot(cfg);
+    let suspended = Duration::from_millis(suspended_ms.max(0) as u64);
+    // The minutes of both ticks were evaluated normally.
+    let from_ms = (last_tick_ms.div_euclid(60_000) + 1) * 60_000 - 1;
+    let to_ms = now_ms.div_euclid(60_000) * 60_000;
+
+    let mut missed = 0;
+    if cfg.schedule_times_enabled {
+        missed += schedule_times_between(cfg, zone, from_ms, to_ms).len();
+    }
+    if cfg.schedule_cron_enabled {
+        missed += cron_schedule::fires_between(zone, &cfg.schedule_cron, from_ms, to_ms, MAX_CATCH_UP_WAKES).len();
+    }
+
+    let mut sched = schedule.write().await;
+    if cfg.schedule_interval_enabled {
+        let interval_secs = cfg.schedule_interval_minutes.max(1) * 60;
+        let now = clock.instant();
+        let since_fire = now.saturating_duration_since(sched.last_interval_fire) + suspended;
+        missed += (since_fire.as_secs() / interval_secs) as usize;
+        // Keep the cadence: the next regular fire lands where it would have
+        // without the sleep.
+        let phase = Duration::from_secs(since_fire.as_secs() % interval_secs);
+        sched.last_interval_fire = now.checked_sub(phase).unwrap_or(now);
+    }
+    if let Some(deadline) = sched.wake_retry_window_deadline {
+        sched.wake_retry_window_deadline = Some(deadline.checked_sub(suspended).unwrap_or_else(|| clock.instant()));
+    }
+
+    let catch_up = match cfg.missed_fire_policy {
+        MissedFirePolicy::FireOnce => missed.min(1),
+        MissedFirePolicy::FireAll => missed.min(MAX_CATCH_UP_WAKES),
+        MissedFirePolicy::Skip => {
+            // After-reset wakes run on wall-clock time and would still fire.
+            if let Some(reset) = sched.last_known_reset_epoch_ms {
+                let target = reset + cfg.schedule_after_reset_minutes.max(1) as i64 * 60_000;
+                if cfg.schedule_after_reset_enabled && target > last_tick_ms && target <= now_ms {
+                    sched.last_reset_marker = Some(reset);
+                }
+            }
+            0
+        }
+    };
+    // Wakes still owed from an earlier resume stay owed.
+    let owed = sched.missed_wakes.saturating_add(catch_up as u32);
+    sched.missed_wakes = match cfg.missed_fire_policy {
+        MissedFirePolicy::FireOnce => owed.min(1),
+        _ => owed.min(MAX_CATCH_UP_WAKES as u32),
+    };
+    let owed = sched.missed_wakes;
+    drop(sched);
+
+    info!(
+        "slot {} resumed after {} s asleep: {} missed wake(s), {} to catch up, {} owed",
+        cfg.slot,
+        suspended_ms / 1_000,
+        missed,
+        catch_up,
+        owed
+    );
+    let _ = log_scheduler_event(
+        host,
+        cfg,
+        "scheduler.resume-detected",
+        json!({
+            "slot": cfg.slot,
+            "suspended_seconds": suspended_ms / 1_000,
+            "last_tick_epoch_ms": last_tick_ms,
+            "missed": missed,
+            "policy": cfg.missed_fire_policy,
+            "catch_up": catch_up,
+            "owed": owed,
+        }),
+    )
+    .await;
+}
+
+/// After-reset wakes are skipped when their target lands on a day outside
+/// the slot's weekday mask.
+fn after_reset_day_allowed(slot_cfg: &KeySlotConfig, target_ms: i64) -> bool {
+    SlotZone::for_slot(slot_cfg)
+        .at(target_ms)
+        .is_none_or(|at| slot_zone::date_allowed(slot_cfg.schedule_days, &at))
+}
+
+/// Update schedule markers after a wake fired, or was skipped as a duplicate
+/// or not needed. Now updates markers for all enabled modes since multiple
+/// can be active. Owed catch-up wakes are left alone: only a wake actually
+/// sent settles one.
+pub(crate) fn update_schedule_markers(
     slot_cfg: &KeySlotConfig,
     old_schedule: &SlotSchedule,
     new_schedule: &mut SlotSchedule,
+    clock: &dyn Clock,
 ) {
     // Always update interval marker if enabled
     if slot_cfg.schedule_interval_enabled {
-        new_schedule.last_interval_fire = Instant::now();
+        new_schedule.last_interval_fire = clock.instant();
     }
 
     // Update times marker if enabled and matched
     if slot_cfg.schedule_times_enabled {
-        let now = Local::now();
-        let current_hm = format!("{:02}:{:02}", now.hour(), now.minute());
-        if slot_cfg.schedule_times.iter().any(|value| value == &current_hm) {
-            new_schedule.last_times_marker = Some(format!("{}-{}", now.format("%Y-%m-%d"), current_hm));
+        if let Some((_, marker)) = due_schedule_time(slot_cfg, clock) {
+            new_schedule.last_times_marker = Some(marker);
+        }
+    }
+
+    // Update cron marker if enabled and matched
+    if slot_cfg.schedule_cron_enabled {
+        let now = SlotZone::for_slot(slot_cfg).now(clock);
+        if cron_schedule::matching(&slot_cfg.schedule_cron, now).is_some() {
+            new_schedule.last_cron_marker = Some(cron_schedule::marker(&now));
         }
     }
 
@@ -1227,25 +1815,34 @@ fn update_schedule_markers(
     if slot_cfg.schedule_after_reset_enabled {
         if let Some(next_reset) = old_schedule.last_known_reset_epoch_ms {
             let target = next_reset + (slot_cfg.schedule_after_reset_minutes.max(1) as i64 * 60_000);
-            let now_ms = Local::now().timestamp_millis();
-            if now_ms >= target {
+            if clock.now_ms() >= target {
                 new_schedule.last_reset_marker = Some(next_reset);
             }
         }
     }
 }
 
-async fn should_retry_quota_while_wake_pending(
+/// Update markers after a wake was sent. A wake no mode was due for was a
+/// catch-up; it settles one owed wake.
+fn record_wake_sent(slot_cfg: &KeySlotConfig, schedule: &mut SlotSchedule, clock: &dyn Clock) {
+    let old_schedule = schedule.clone();
+    update_schedule_markers(slot_cfg, &old_schedule, schedule, clock);
+    if scheduled_fire_reason(slot_cfg, &old_schedule, clock).is_none() {
+        schedule.missed_wakes = old_schedule.missed_wakes.saturating_sub(1);
+    }
+}
+
+pub(crate) async fn should_retry_quota_while_wake_pending(
     schedule: &Arc<RwLock<SlotSchedule>>,
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
+    clock: &dyn Clock,
 ) -> bool {
     let wake_pending = {
         let runtime = runtime_status.read().await;
         runtime
-            .slots
-            .get(idx)
-            .is_some_and(|slot| slot.wake_pending && !slot.wake_auto_disabled)
+            .slot(slot_id)
+            .is_some_and(|slot| slot.wake_pending && !slot.wake_auto_disabled())
     };
     if !wake_pending {
         return false;
@@ -1256,44 +1853,115 @@ async fn should_retry_quota_while_wake_pending(
         sched.wake_retry_window_deadline
     };
 
-    deadline.is_some_and(|deadline| Instant::now() < deadline)
+    deadline.is_some_and(|deadline| clock.instant() < deadline)
 }
 
-async fn clear_wake_quota_retry_window(schedule: &Arc<RwLock<SlotSchedule>>) {
+pub(crate) async fn clear_wake_quota_retry_window(schedule: &Arc<RwLock<SlotSchedule>>) {
     let mut sched = schedule.write().await;
     sched.wake_retry_window_deadline = None;
     sched.wake_timeout_retry_fired = false;
 }
 
-async fn is_wake_required(
-    client: &ApiClient,
-    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+/// After a scheduled wake was sent: record it on the schedule, open the
+/// confirmation retry window (or note the one forced retry after it timed
+/// out) and mark the wake pending. Returns the reset time the confirmation
+/// must see advance.
+pub(crate) async fn arm_wake_confirmation(
     cfg: &KeySlotConfig,
-    idx: usize,
-) -> bool {
-    let now_ms = Local::now().timestamp_millis();
-
-    let cached_state = {
+    schedule: &Arc<RwLock<SlotSchedule>>,
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    policy: SchedulerPolicy,
+    forced_retry: bool,
+    clock: &dyn Clock,
+) -> Option<i64> {
+    let pre_reset_marker = {
         let runtime = runtime_status.read().await;
         runtime
-            .slots
-            .get(idx)
+            .slot(cfg.slot)
             .and_then(|slot| slot.last_updated_epoch_ms)
     };
 
-    if let Some(next_reset_ms) = cached_state {
-        // If the cached window is still active, trust local state.
-        if next_reset_ms > now_ms {
-            return false;
+    let mut sched = schedule.write().await;
+    record_wake_sent(cfg, &mut sched, clock);
+    if forced_retry {
+        sched.wake_timeout_retry_fired = true;
+        sched.wake_retry_window_deadline = None;
+    } else {
+        sched.wake_retry_window_deadline =
+            Some(clock.instant() + Duration::from_secs(policy.wake_quota_retry_window_minutes * 60));
+        sched.wake_timeout_retry_fired = false;
+    }
+    drop(sched);
+
+    mark_wake_attempt(runtime_status, cfg.slot, pre_reset_marker).await;
+    pre_reset_marker
+}
+
+/// Minutes until the next quota poll: every minute while a wake awaits
+/// confirmation, the slot's interval normally, and exponential backoff
+/// (capped) after errors.
+pub(crate) fn poll_sleep_minutes(
+    cfg: &KeySlotConfig,
+    policy: SchedulerPolicy,
+    wake_window_active: bool,
+    consecutive_errors: u32,
+) -> u64 {
+    if consecutive_errors == 0 {
+        if wake_window_active {
+            1
+        } else {
+            cfg.poll_interval_minutes.max(1)
         }
-        // Expired/missing window state should be revalidated with live quota
-        // so the app can observe externally triggered activity correctly.
+    } else {
+        let backoff = cfg
+            .poll_interval_minutes
+            .max(1)
+            .saturating_mul(1u64 << consecutive_errors.min(6));
+        backoff.min(policy.quota_backoff_cap_minutes)
+    }
+}
+
+async fn is_wake_required(
+    client: &ApiClient,
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    cfg: &KeySlotConfig,
+    slot_id: usize,
+    clock: &dyn Clock,
+) -> bool {
+    let now_ms = clock.now_ms();
+    if cached_window_active(runtime_status, slot_id, now_ms).await {
+        return false;
     }
+    let quota = client.fetch_quota(cfg, "wake-precheck").await;
+    apply_wake_precheck(runtime_status, slot_id, quota, now_ms).await
+}
+
+/// If the cached window is still active, trust local state. Expired or
+/// missing window state is revalidated with live quota so the app can
+/// observe externally triggered activity correctly.
+pub(crate) async fn cached_window_active(
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    slot_id: usize,
+    now_ms: i64,
+) -> bool {
+    let runtime = runtime_status.read().await;
+    runtime
+        .slot(slot_id)
+        .and_then(|slot| slot.last_updated_epoch_ms)
+        .is_some_and(|next_reset_ms| next_reset_ms > now_ms)
+}
 
-    match client.fetch_quota(cfg, "wake-precheck").await {
+/// Record the pre-check's quota answer and decide whether a wake is needed.
+pub(crate) async fn apply_wake_precheck(
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    slot_id: usize,
+    quota: Result<QuotaSnapshot, String>,
+    now_ms: i64,
+) -> bool {
+    match quota {
         Ok(snapshot) => {
             let mut runtime = runtime_status.write().await;
-            if let Some(current) = runtime.slots.get_mut(idx) {
+            if let Some(current) = runtime.slot_mut(slot_id) {
                 current.percentage = Some(snapshot.percentage);
                 current.timer_active = snapshot.timer_active;
                 current.next_reset_hms = snapshot.next_reset_hms;
@@ -1302,12 +1970,12 @@ async fn is_wake_required(
 
             snapshot
                 .next_reset_epoch_ms
-                .map_or(true, |next_reset_ms| next_reset_ms <= now_ms)
+                .is_none_or(|next_reset_ms| next_reset_ms <= now_ms)
         }
         Err(err) => {
             warn!(
                 "slot {} wake pre-check failed (quota fetch failed), attempting wake: {}",
-                idx + 1,
+                slot_id,
                 err
             );
             // If the quota API is unavailable, prefer to attempt the wake attempt
@@ -1318,26 +1986,27 @@ async fn is_wake_required(
     }
 }
 
-async fn mark_wake_attempt(
+pub(crate) async fn mark_wake_attempt(
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
     reset_marker: Option<i64>,
 ) {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
+    if let Some(current) = runtime.slot_mut(slot_id) {
         current.wake_pending = true;
         current.wake_reset_epoch_ms = reset_marker;
     }
 }
 
-async fn complete_wake_if_advanced(
+pub(crate) async fn complete_wake_if_advanced(
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
     next_reset_epoch_ms: Option<i64>,
-    max_consecutive_errors: u32,
+    policy: SchedulerPolicy,
+    now_ms: i64,
 ) -> WakeConfirmOutcome {
     let mut runtime = runtime_status.write().await;
-    let Some(current) = runtime.slots.get_mut(idx) else {
+    let Some(current) = runtime.slot_mut(slot_id) else {
         return WakeConfirmOutcome::NotPending;
     };
 
@@ -1348,14 +2017,8 @@ async fn complete_wake_if_advanced(
     let Some(next_reset) = next_reset_epoch_ms else {
         current.wake_consecutive_errors = current.wake_consecutive_errors.saturating_add(1);
         current.last_error = Some("wake confirmation pending: quota reset timestamp is missing".to_string());
-        if current.wake_consecutive_errors >= max_consecutive_errors {
-            current.wake_pending = false;
-            current.wake_reset_epoch_ms = None;
-            current.wake_auto_disabled = true;
-            current.last_error = Some(format!(
-                "wake disabled after {} consecutive wake failures",
-                current.wake_consecutive_errors
-            ));
+        if current.wake_consecutive_errors >= policy.max_consecutive_errors {
+            open_wake_circuit(current, policy, now_ms);
             return WakeConfirmOutcome::AutoDisabled;
         }
         return WakeConfirmOutcome::FailedMissing;
@@ -1366,7 +2029,7 @@ async fn complete_wake_if_advanced(
             current.wake_pending = false;
             current.wake_reset_epoch_ms = None;
             current.wake_consecutive_errors = 0;
-            current.wake_auto_disabled = false;
+            current.wake_circuit.close();
             if current.quota_consecutive_errors == 0 {
                 current.last_error = None;
             }
@@ -1378,14 +2041,8 @@ async fn complete_wake_if_advanced(
                 current.last_error = Some(
                     "wake confirmation failed: quota reset timestamp did not advance".to_string(),
                 );
-                if current.wake_consecutive_errors >= max_consecutive_errors {
-                    current.wake_pending = false;
-                    current.wake_reset_epoch_ms = None;
-                    current.wake_auto_disabled = true;
-                    current.last_error = Some(format!(
-                        "wake disabled after {} consecutive wake failures",
-                        current.wake_consecutive_errors
-                    ));
+                if current.wake_consecutive_errors >= policy.max_consecutive_errors {
+                    open_wake_circuit(current, policy, now_ms);
                     return WakeConfirmOutcome::AutoDisabled;
                 }
                 return WakeConfirmOutcome::FailedNotAdvanced;
@@ -1393,7 +2050,7 @@ async fn complete_wake_if_advanced(
             current.wake_pending = false;
             current.wake_reset_epoch_ms = None;
             current.wake_consecutive_errors = 0;
-            current.wake_auto_disabled = false;
+            current.wake_circuit.close();
             if current.quota_consecutive_errors == 0 {
                 current.last_error = None;
             }
@@ -1402,24 +2059,32 @@ async fn complete_wake_if_advanced(
     }
 }
 
-async fn record_wake_error(
+/// Open (or, after a failed probe, reopen) the slot's wake circuit.
+fn open_wake_circuit(current: &mut SlotRuntimeStatus, policy: SchedulerPolicy, now_ms: i64) {
+    current.wake_pending = false;
+    current.wake_reset_epoch_ms = None;
+    current.wake_circuit.open(now_ms, policy.circuit_probe_minutes);
+    current.last_error = Some(format!(
+        "wake circuit open after {} consecutive wake failures; probing every {} min",
+        current.wake_consecutive_errors, policy.circuit_probe_minutes
+    ));
+}
+
+pub(crate) async fn record_wake_error(
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
     message: &str,
-    max_consecutive_errors: u32,
+    policy: SchedulerPolicy,
+    now_ms: i64,
 ) -> u32 {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
-        current.slot = idx + 1;
+    if let Some(current) = runtime.slot_mut(slot_id) {
+        current.slot = slot_id;
         current.enabled = true;
         current.last_error = Some(format!("wake request failed: {message}"));
         current.wake_consecutive_errors = current.wake_consecutive_errors.saturating_add(1);
-        if current.wake_consecutive_errors >= max_consecutive_errors {
-            current.wake_auto_disabled = true;
-            current.last_error = Some(format!(
-                "wake disabled after {} consecutive wake failures",
-                current.wake_consecutive_errors
-            ));
+        if current.wake_consecutive_errors >= policy.max_consecutive_errors {
+            open_wake_circuit(current, policy, now_ms);
         }
         return current.wake_consecutive_errors;
     }
@@ -1427,24 +2092,25 @@ async fn record_wake_error(
     0
 }
 
-async fn record_quota_error(
+pub(crate) async fn record_quota_error(
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
     message: &str,
-    max_consecutive_errors: u32,
+    policy: SchedulerPolicy,
+    now_ms: i64,
 ) -> u32 {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
-        current.slot = idx + 1;
+    if let Some(current) = runtime.slot_mut(slot_id) {
+        current.slot = slot_id;
         current.enabled = true;
         current.last_error = Some(format!("quota request failed: {message}"));
         current.quota_consecutive_errors = current.quota_consecutive_errors.saturating_add(1);
         current.consecutive_errors = current.quota_consecutive_errors;
-        if current.quota_consecutive_errors >= max_consecutive_errors {
-            current.auto_disabled = true;
+        if current.quota_consecutive_errors >= policy.max_consecutive_errors {
+            current.quota_circuit.open(now_ms, policy.circuit_probe_minutes);
             current.last_error = Some(format!(
-                "quota polling disabled after {} consecutive quota failures",
-                current.quota_consecutive_errors
+                "quota circuit open after {} consecutive quota failures; probing every {} min",
+                current.quota_consecutive_errors, policy.circuit_probe_minutes
             ));
         }
         return current.quota_consecutive_errors;
@@ -1453,10 +2119,47 @@ async fn record_quota_error(
     0
 }
 
-async fn clear_slot_runtime(runtime_status: &Arc<RwLock<RuntimeStatus>>, idx: usize) {
+/// When the slot's open `endpoint` circuit sends its next probe.
+async fn circuit_next_probe(
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    slot_id: usize,
+    endpoint: CircuitEndpoint,
+) -> Option<i64> {
+    let runtime = runtime_status.read().await;
+    let slot = runtime.slot(slot_id)?;
+    match endpoint {
+        CircuitEndpoint::Quota => slot.quota_circuit.next_probe_epoch_ms,
+        CircuitEndpoint::Wake => slot.wake_circuit.next_probe_epoch_ms,
+    }
+}
+
+/// Move an open circuit whose probe is due to half-open. Returns whether
+/// the caller should send the probe now.
+pub(crate) async fn begin_circuit_probe(
+    runtime_status: &Arc<RwLock<RuntimeStatus>>,
+    slot_id: usize,
+    endpoint: CircuitEndpoint,
+    now_ms: i64,
+) -> bool {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
-        current.slot = idx + 1;
+    let Some(current) = runtime.slot_mut(slot_id) else {
+        return false;
+    };
+    let circuit = match endpoint {
+        CircuitEndpoint::Quota => &mut current.quota_circuit,
+        CircuitEndpoint::Wake => &mut current.wake_circuit,
+    };
+    if !circuit.probe_due(now_ms) {
+        return false;
+    }
+    circuit.half_open();
+    true
+}
+
+async fn clear_slot_runtime(runtime_status: &Arc<RwLock<RuntimeStatus>>, slot_id: usize) {
+    let mut runtime = runtime_status.write().await;
+    if let Some(current) = runtime.slot_mut(slot_id) {
+        current.slot = slot_id;
         current.enabled = false;
         current.timer_active = false;
         current.name.clear();
@@ -1467,55 +2170,110 @@ async fn clear_slot_runtime(runtime_status: &Arc<RwLock<RuntimeStatus>>, idx: us
         current.consecutive_errors = 0;
         current.quota_consecutive_errors = 0;
         current.wake_consecutive_errors = 0;
-        current.auto_disabled = false;
-        current.wake_auto_disabled = false;
+        current.quota_circuit.close();
+        current.wake_circuit.close();
         current.wake_pending = false;
         current.wake_reset_epoch_ms = None;
+        current.forecast = None;
     }
 }
 
 async fn sync_slot_runtime_name(
     runtime_status: &Arc<RwLock<RuntimeStatus>>,
-    idx: usize,
+    slot_id: usize,
     slot_cfg: &KeySlotConfig,
 ) {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
+    if let Some(current) = runtime.slot_mut(slot_id) {
         current.name = slot_cfg.name.clone();
     }
 }
 
-async fn clear_quota_error(runtime_status: &Arc<RwLock<RuntimeStatus>>, idx: usize) {
+pub(crate) async fn clear_quota_error(runtime_status: &Arc<RwLock<RuntimeStatus>>, slot_id: usize) {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
+    if let Some(current) = runtime.slot_mut(slot_id) {
         current.quota_consecutive_errors = 0;
         current.consecutive_errors = 0;
-        current.auto_disabled = false;
+        current.quota_circuit.close();
         if current.wake_consecutive_errors == 0 {
             current.last_error = None;
         }
     }
 }
 
-async fn clear_wake_state(runtime_status: &Arc<RwLock<RuntimeStatus>>, idx: usize) {
+pub(crate) async fn clear_wake_state(runtime_status: &Arc<RwLock<RuntimeStatus>>, slot_id: usize) {
     let mut runtime = runtime_status.write().await;
-    if let Some(current) = runtime.slots.get_mut(idx) {
+    if let Some(current) = runtime.slot_mut(slot_id) {
         current.wake_pending = false;
         current.wake_reset_epoch_ms = None;
         current.wake_consecutive_errors = 0;
-        current.wake_auto_disabled = false;
+        current.wake_circuit.close();
         if current.quota_consecutive_errors == 0 {
             current.last_error = None;
         }
     }
 }
 
-async fn log_scheduler_event(app: &AppHandle, cfg: &KeySlotConfig, action: &str, details: serde_json::Value) {
+/// Sleep until the wake scheduler's next tick, cut short by config changes.
+/// Returns whether the task was asked to stop.
+async fn wait_for_wake_tick(
+    slot_id: usize,
+    stop_rx: &mut watch::Receiver<bool>,
+    config_rx: &mut watch::Receiver<KeySlotConfig>,
+    app_config_rx: &mut watch::Receiver<AppConfig>,
+) -> bool {
+    tokio::select! {
+        _ = stop_rx.changed() => *stop_rx.borrow(),
+        _ = config_rx.changed() => {
+            info!("slot {} wake scheduler detected config change", slot_id);
+            false
+        }
+        _ = app_config_rx.changed() => {
+            info!("slot {} wake scheduler detected policy change", slot_id);
+            false
+        }
+        _ = time::sleep(Duration::from_secs(WAKE_RETRY_INTERVAL_SECONDS)) => false,
+    }
+}
+
+/// Wait for a slot or app config change; returns whether the task should stop.
+async fn wait_for_config_change(
+    slot_id: usize,
+    stop_rx: &mut watch::Receiver<bool>,
+    config_rx: &mut watch::Receiver<KeySlotConfig>,
+    app_config_rx: &mut watch::Receiver<AppConfig>,
+) -> bool {
+    tokio::select! {
+        _ = stop_rx.changed() => *stop_rx.borrow(),
+        _ = config_rx.changed() => {
+            info!("slot {} detected config change", slot_id);
+            false
+        }
+        _ = app_config_rx.changed() => {
+            info!("slot {} detected settings change", slot_id);
+            false
+        }
+    }
+}
+
+async fn raise_alert(
+    alerts: &Arc<AlertEngine>,
+    host: &HostHandle,
+    app_config_rx: &watch::Receiver<AppConfig>,
+    cfg: &KeySlotConfig,
+    event: AlertEvent,
+) {
+    let settings = app_config_rx.borrow().alerts.clone();
+    alerts.evaluate(host, &settings, cfg, &event).await;
+}
+
+async fn log_scheduler_event(host: &HostHandle, cfg: &KeySlotConfig, action: &str, details: serde_json::Value) {
+    webhooks::dispatch(host, cfg, action, &details).await;
     if !cfg.logging {
         return;
     }
     let _ = file_logger::append(
-        app,
+        host,
         file_logger::event_entry(cfg.slot, action, Some(details)),
     )
     .await;
@@ -1524,10 +2282,628 @@ async fn log_scheduler_event(app: &AppHandle, cfg: &KeySlotConfig, action: &str,
 pub async fn reset_runtime(runtime_status: &Arc<RwLock<RuntimeStatus>>) {
     let mut runtime = runtime_status.write().await;
     runtime.monitoring = false;
-    for idx in 0..runtime.slots.len() {
-        runtime.slots[idx] = SlotRuntimeStatus {
-            slot: idx + 1,
+    for slot in runtime.slots.iter_mut() {
+        *slot = SlotRuntimeStatus {
+            slot: slot.slot,
+            proxy: std::mem::take(&mut slot.proxy),
             ..Default::default()
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FakeClock;
+    use crate::host::HeadlessHost;
+    use crate::models::{NetworkConfig, RetryPolicy};
+    use chrono::{DateTime, TimeZone, Utc};
+    use glm_mock_server::{Endpoint, Fault, FaultRule, MockConfig, MockServer};
+    use tempfile::TempDir;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+    const HOUR: Duration = Duration::from_secs(3600);
+    const MAX_ERRORS: u32 = 3;
+
+    fn policy() -> SchedulerPolicy {
+        SchedulerPolicy::from(&AppConfig {
+            max_consecutive_errors: MAX_ERRORS,
+            ..AppConfig::default()
+        })
+    }
+
+    /// Monday 2026-03-02 08:00 UTC.
+    fn clock() -> Arc<FakeClock> {
+        FakeClock::new(Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap())
+    }
+
+    /// Minutes in which `due_schedule_time` fires, stepping a fake clock
+    /// from `start` for `hours`.
+    fn due_minutes(slot_cfg: &KeySlotConfig, start: DateTime<Utc>, hours: u64) -> Vec<String> {
+        let clock = FakeClock::new(start);
+        let zone = SlotZone::for_slot(slot_cfg);
+        let mut fired = Vec::new();
+        for _ in 0..hours * 60 {
+            if due_schedule_time(slot_cfg, &*clock).is_some() {
+                fired.push(zone.format(clock.now_ms(), "%m-%d %H:%M %z").unwrap());
+            }
+            clock.advance(MINUTE);
+        }
+        fired
+    }
+
+    fn slot_config() -> KeySlotConfig {
+        KeySlotConfig {
+            slot: 1,
+            enabled: true,
+            api_key: "test-key".to_string(),
+            schedule_timezone: "UTC".to_string(),
+            ..KeySlotConfig::default()
+        }
+    }
+
+    fn runtime() -> Arc<RwLock<RuntimeStatus>> {
+        Arc::new(RwLock::new(RuntimeStatus {
+            monitoring: true,
+            slots: vec![SlotRuntimeStatus {
+                slot: 1,
+                enabled: true,
+                ..Default::default()
+            }],
+        }))
+    }
+
+    /// Mock API whose reset windows follow `clock`.
+    async fn mock_server(clock: &Arc<FakeClock>) -> (MockServer, ApiClient) {
+        let now = clock.clone();
+        let server = MockServer::start(MockConfig {
+            expiry: 5 * HOUR,
+            now: Arc::new(move || now.now_ms()),
+            ..MockConfig::default()
+        })
+        .await
+        .unwrap();
+        let client = ApiClient::new(None, true, Some(server.url()), &NetworkConfig::default())
+            .unwrap()
+            .with_clock(clock.clone())
+            .with_retry(RetryPolicy::none());
+        (server, client)
+    }
+
+    #[test]
+    fn interval_mode_fires_once_per_interval() {
+        let clock = clock();
+        let cfg = KeySlotConfig {
+            schedule_interval_enabled: true,
+            schedule_interval_minutes: 60,
+            ..slot_config()
+        };
+        let mut schedule = SlotSchedule::new(clock.instant());
+
+        clock.advance(59 * MINUTE);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+        clock.advance(MINUTE);
+        let reason = should_fire_wake(&cfg, &schedule, &*clock).unwrap();
+        assert!(reason.starts_with("interval mode"), "{reason}");
+
+        record_wake_sent(&cfg, &mut schedule, &*clock);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+        clock.advance(HOUR);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_some());
+    }
+
+    #[test]
+    fn times_mode_fires_once_per_day_in_mask() {
+        let clock = clock();
+        let cfg = KeySlotConfig {
+            schedule_times_enabled: true,
+            schedule_times: vec!["09:30".to_string()],
+            schedule_days: 0b0000_0001, // Mondays
+            ..slot_config()
+        };
+        let mut schedule = SlotSchedule::new(clock.instant());
+
+        clock.advance(89 * MINUTE);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+        clock.advance(MINUTE);
+        assert_eq!(
+            should_fire_wake(&cfg, &schedule, &*clock).as_deref(),
+            Some("times mode (matched 09:30)")
+        );
+
+        record_wake_sent(&cfg, &mut schedule, &*clock);
+        clock.advance(Duration::from_secs(30));
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+
+        // Tuesday is outside the mask; the next Monday is not.
+        clock.advance(24 * HOUR - Duration::from_secs(30));
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+        clock.advance(6 * 24 * HOUR);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_some());
+    }
+
+    #[test]
+    fn after_reset_mode_waits_for_offset_and_fires_once_per_reset() {
+        let clock = clock();
+        let cfg = KeySlotConfig {
+            schedule_after_reset_enabled: true,
+            schedule_after_reset_minutes: 10,
+            ..slot_config()
+        };
+        let mut schedule = SlotSchedule::new(clock.instant());
+        schedule.last_known_reset_epoch_ms = Some(clock.now_ms() + HOUR.as_millis() as i64);
+
+        clock.advance(HOUR + 9 * MINUTE);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+        clock.advance(MINUTE);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_some());
+
+        record_wake_sent(&cfg, &mut schedule, &*clock);
+        clock.advance(HOUR);
+        assert!(should_fire_wake(&cfg, &schedule, &*clock).is_none());
+    }
+
+    #[test]
+    fn schedule_times_fire_once_across_dst_changes() {
+        let cfg = KeySlotConfig {
+            schedule_timezone: "America/New_York".to_string(),
+            schedule_times: vec!["02:30".to_string(), "01:30".to_string()],
+            ..slot_config()
+        };
+        // Spring forward: 02:30 does not exist and fires at 03:00 instead.
+        let spring = Utc.with_ymd_and_hms(2026, 3, 8, 4, 0, 0).unwrap();
+        assert_eq!(due_minutes(&cfg, spring, 4), ["03-08 01:30 -0500", "03-08 03:00 -0400"]);
+        // Fall back: 01:30 happens twice and fires at the first one only.
+        let fall = Utc.with_ymd_and_hms(2026, 11, 1, 4, 0, 0).unwrap();
+        assert_eq!(due_minutes(&cfg, fall, 4), ["11-01 01:30 -0400", "11-01 02:30 -0500"]);
+        // Sunday is outside a weekdays-only mask.
+        let weekdays = KeySlotConfig { schedule_days: 0x1f, ..cfg };
+        assert!(due_minutes(&weekdays, fall, 4).is_empty());
+    }
+
+    #[tokio::test]
+    async fn wake_is_confirmed_when_reset_advances() {
+        let clock = clock();
+        let runtime = runtime();
+        let schedule = Arc::new(RwLock::new(SlotSchedule::new(clock.instant())));
+        let reset = clock.now_ms() + HOUR.as_millis() as i64;
+
+        mark_wake_attempt(&runtime, 1, Some(reset)).await;
+        schedule.write().await.wake_retry_window_deadline = Some(clock.instant() + 15 * MINUTE);
+        assert!(should_retry_quota_while_wake_pending(&schedule, &runtime, 1, &*clock).await);
+
+        let next_reset = Some(reset + 5 * HOUR.as_millis() as i64);
+        assert!(matches!(
+            complete_wake_if_advanced(&runtime, 1, next_reset, policy(), clock.now_ms()).await,
+            WakeConfirmOutcome::Confirmed
+        ));
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(!slot.wake_pending);
+        assert_eq!(slot.wake_consecutive_errors, 0);
+        assert!(!should_retry_quota_while_wake_pending(&schedule, &runtime, 1, &*clock).await);
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_wake_retries_until_auto_disabled() {
+        let clock = clock();
+        let runtime = runtime();
+        let schedule = Arc::new(RwLock::new(SlotSchedule::new(clock.instant())));
+        let reset = Some(clock.now_ms() + HOUR.as_millis() as i64);
+
+        mark_wake_attempt(&runtime, 1, reset).await;
+        schedule.write().await.wake_retry_window_deadline = Some(clock.instant() + 15 * MINUTE);
+
+        // Quota polls keep retrying inside the window, then stop.
+        clock.advance(14 * MINUTE);
+        assert!(should_retry_quota_while_wake_pending(&schedule, &runtime, 1, &*clock).await);
+        clock.advance(MINUTE);
+        assert!(!should_retry_quota_while_wake_pending(&schedule, &runtime, 1, &*clock).await);
+
+        for attempt in 1..MAX_ERRORS {
+            assert!(matches!(
+                complete_wake_if_advanced(&runtime, 1, reset, policy(), clock.now_ms()).await,
+                WakeConfirmOutcome::FailedNotAdvanced
+            ));
+            let slot = runtime.read().await.slot(1).cloned().unwrap();
+            assert!(slot.wake_pending);
+            assert_eq!(slot.wake_consecutive_errors, attempt);
+        }
+        assert!(matches!(
+            complete_wake_if_advanced(&runtime, 1, None, policy(), clock.now_ms()).await,
+            WakeConfirmOutcome::AutoDisabled
+        ));
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(slot.wake_auto_disabled());
+        assert!(!slot.wake_pending);
+        assert!(matches!(
+            complete_wake_if_advanced(&runtime, 1, reset, policy(), clock.now_ms()).await,
+            WakeConfirmOutcome::NotPending
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_wake_requests_auto_disable_and_clear() {
+        let clock = clock();
+        let runtime = runtime();
+        for attempt in 1..=MAX_ERRORS {
+            assert_eq!(record_wake_error(&runtime, 1, "HTTP 500", policy(), clock.now_ms()).await, attempt);
+        }
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(slot.wake_auto_disabled());
+        assert_eq!(slot.wake_circuit.state, CircuitState::Open);
+
+        clear_wake_state(&runtime, 1).await;
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(!slot.wake_auto_disabled());
+        assert!(slot.wake_circuit.is_closed());
+        assert_eq!(slot.wake_consecutive_errors, 0);
+        assert!(slot.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn wake_precheck_trusts_an_active_window() {
+        let clock = clock();
+        let runtime = runtime();
+        let cfg = slot_config();
+        // Nothing listens here, so any live check fails and asks for a wake.
+        let client = ApiClient::new(None, true, Some("http://127.0.0.1:9".to_string()), &NetworkConfig::default())
+            .unwrap()
+            .with_clock(clock.clone())
+            .with_retry(RetryPolicy::none());
+        runtime.write().await.slot_mut(1).unwrap().last_updated_epoch_ms =
+            Some(clock.now_ms() + 2 * HOUR.as_millis() as i64);
+
+        assert!(!is_wake_required(&client, &runtime, &cfg, 1, &*clock).await);
+        clock.advance(2 * HOUR);
+        assert!(is_wake_required(&client, &runtime, &cfg, 1, &*clock).await);
+    }
+
+    #[tokio::test]
+    async fn resume_queues_missed_wakes_per_policy() {
+        let clock = clock();
+        let config = Arc::new(RwLock::new(AppConfig::default()));
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), config));
+        let mut cfg = KeySlotConfig {
+            schedule_interval_enabled: true,
+            schedule_interval_minutes: 60,
+            schedule_times_enabled: true,
+            schedule_times: vec!["10:00".to_string()],
+            missed_fire_policy: MissedFirePolicy::FireAll,
+            ..slot_config()
+        };
+        let schedule = Arc::new(RwLock::new(SlotSchedule::new(clock.instant())));
+
+        // 08:00 → 11:30 asleep: three interval fires and 10:00.
+        let last_tick_ms = clock.now_ms();
+        let suspended = 3 * HOUR + 30 * MINUTE;
+        clock.suspend(suspended);
+        let suspended_ms = suspended.as_millis() as i64;
+        handle_resume(&host, &cfg, &schedule, &*clock, last_tick_ms, clock.now_ms(), suspended_ms).await;
+        let mut sched = schedule.read().await.clone();
+        assert_eq!(sched.missed_wakes, 4);
+        // The interval keeps its phase: the next regular fire is at 12:00.
+        assert_eq!(clock.instant().saturating_duration_since(sched.last_interval_fire), 30 * MINUTE);
+
+        let reason = should_fire_wake(&cfg, &sched, &*clock).unwrap();
+        assert_eq!(reason, "catch-up after resume (4 missed)");
+        record_wake_sent(&cfg, &mut sched, &*clock);
+        assert_eq!(sched.missed_wakes, 3);
+
+        // A catch-up skipped as a duplicate or not needed stays owed.
+        let old = sched.clone();
+        update_schedule_markers(&cfg, &old, &mut sched, &*clock);
+        assert_eq!(sched.missed_wakes, 3);
+
+        // A regular fire while catch-ups are owed leaves them owed.
+        clock.advance(HOUR);
+        let reason = should_fire_wake(&cfg, &sched, &*clock).unwrap();
+        assert!(reason.starts_with("interval mode"), "{reason}");
+        record_wake_sent(&cfg, &mut sched, &*clock);
+        assert_eq!(sched.missed_wakes, 3);
+        assert_eq!(
+            should_fire_wake(&cfg, &sched, &*clock).as_deref(),
+            Some("catch-up after resume (3 missed)")
+        );
+
+        // Another sleep adds to the wakes still owed.
+        *schedule.write().await = sched;
+        let last_tick_ms = clock.now_ms();
+        clock.suspend(HOUR);
+        handle_resume(&host, &cfg, &schedule, &*clock, last_tick_ms, clock.now_ms(), HOUR.as_millis() as i64).await;
+        assert_eq!(schedule.read().await.missed_wakes, 4);
+
+        cfg.missed_fire_policy = MissedFirePolicy::FireOnce;
+        let last_tick_ms = clock.now_ms();
+        clock.suspend(suspended);
+        handle_resume(&host, &cfg, &schedule, &*clock, last_tick_ms, clock.now_ms(), suspended_ms).await;
+        assert_eq!(schedule.read().await.missed_wakes, 1);
+    }
+
+    #[test]
+    fn state_snapshot_round_trips_timers() {
+        let clock = clock();
+        let mut schedule = SlotSchedule::new(clock.instant());
+        clock.advance(10 * MINUTE);
+        schedule.wake_retry_window_deadline = Some(clock.instant() + 5 * MINUTE);
+
+        let restored = SlotSchedule::from_state(&schedule.to_state(&*clock), &*clock);
+        let now = clock.instant();
+        assert_eq!(now.saturating_duration_since(restored.last_interval_fire), 10 * MINUTE);
+        assert_eq!(restored.wake_retry_window_deadline.unwrap().saturating_duration_since(now), 5 * MINUTE);
+    }
+
+    #[tokio::test]
+    async fn wake_cycle_against_mock_server() {
+        let clock = clock();
+        let (server, client) = mock_server(&clock).await;
+        let runtime = runtime();
+        let cfg = slot_config();
+
+        // A cold key needs a wake; the quota then shows the new window.
+        assert!(is_wake_required(&client, &runtime, &cfg, 1, &*clock).await);
+        client.send_wake_request(&cfg).await.unwrap();
+        mark_wake_attempt(&runtime, 1, None).await;
+        let first = client.fetch_quota(&cfg, "test").await.unwrap().next_reset_epoch_ms;
+        assert_eq!(first, Some(clock.now_ms() + 5 * HOUR.as_millis() as i64));
+        assert!(matches!(
+            complete_wake_if_advanced(&runtime, 1, first, policy(), clock.now_ms()).await,
+            WakeConfirmOutcome::Confirmed
+        ));
+        assert!(!is_wake_required(&client, &runtime, &cfg, 1, &*clock).await);
+
+        // Once the window closes the next wake must move the reset forward.
+        clock.advance(5 * HOUR);
+        assert!(is_wake_required(&client, &runtime, &cfg, 1, &*clock).await);
+        mark_wake_attempt(&runtime, 1, first).await;
+        client.send_wake_request(&cfg).await.unwrap();
+        let second = client.fetch_quota(&cfg, "test").await.unwrap().next_reset_epoch_ms;
+        assert!(second > first);
+        assert!(matches!(
+            complete_wake_if_advanced(&runtime, 1, second, policy(), clock.now_ms()).await,
+            WakeConfirmOutcome::Confirmed
+        ));
+        assert_eq!(server.key(&cfg.api_key).unwrap().wake_count, 2);
+    }
+
+    #[tokio::test]
+    async fn mock_server_faults_reach_error_paths() {
+        let clock = clock();
+        let (server, client) = mock_server(&clock).await;
+        let runtime = runtime();
+        let cfg = slot_config();
+
+        server.inject(FaultRule::once(Endpoint::Quota, Fault::Http { status: 500 }));
+        server.inject(FaultRule::once(Endpoint::Quota, Fault::ApiCode { code: 1302 }));
+        server.inject(FaultRule::once(Endpoint::Quota, Fault::MalformedJson));
+        for _ in 0..3 {
+            assert!(client.fetch_quota(&cfg, "test").await.is_err());
+        }
+        assert!(client.fetch_quota(&cfg, "test").await.is_ok());
+
+        // The wake lands but the quota never reports a reset time.
+        server.inject(FaultRule::always(Endpoint::Quota, Fault::MissingResetTime));
+        client.send_wake_request(&cfg).await.unwrap();
+        mark_wake_attempt(&runtime, 1, None).await;
+        for attempt in 1..=MAX_ERRORS {
+            let snapshot = client.fetch_quota(&cfg, "test").await.unwrap();
+            assert!(snapshot.next_reset_epoch_ms.is_none());
+            let outcome = complete_wake_if_advanced(&runtime, 1, snapshot.next_reset_epoch_ms, policy(), clock.now_ms()).await;
+            if attempt < MAX_ERRORS {
+                assert!(matches!(outcome, WakeConfirmOutcome::FailedMissing));
+            } else {
+                assert!(matches!(outcome, WakeConfirmOutcome::AutoDisabled));
+            }
+        }
+        server.clear_faults();
+        clear_wake_state(&runtime, 1).await;
+
+        // Wake requests failing outright disable wakes too.
+        server.inject(FaultRule::always(Endpoint::ChatCompletions, Fault::Http { status: 503 }));
+        for attempt in 1..=MAX_ERRORS {
+            let err = client.send_wake_request(&cfg).await.unwrap_err();
+            assert_eq!(record_wake_error(&runtime, 1, &err, policy(), clock.now_ms()).await, attempt);
+        }
+        assert!(runtime.read().await.slot(1).unwrap().wake_auto_disabled());
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 1 + MAX_ERRORS as u64);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_within_one_call() {
+        let clock = clock();
+        let (server, client) = mock_server(&clock).await;
+        let client = client.with_retry(RetryPolicy {
+            base_delay_ms: 1,
+            ..RetryPolicy::default()
+        });
+        let runtime = runtime();
+        let cfg = slot_config();
+
+        server.inject(FaultRule::times(Endpoint::Quota, Fault::Http { status: 503 }, 2));
+        assert!(client.fetch_quota(&cfg, "test").await.is_ok());
+        assert_eq!(server.requests(Endpoint::Quota), 3);
+
+        // A status outside `retry_statuses` fails on the first try.
+        server.inject(FaultRule::once(Endpoint::Quota, Fault::Http { status: 401 }));
+        assert!(client.fetch_quota(&cfg, "test").await.is_err());
+        assert_eq!(server.requests(Endpoint::Quota), 4);
+
+        // Only an exhausted call counts as one scheduler error.
+        server.inject(FaultRule::times(Endpoint::ChatCompletions, Fault::Http { status: 502 }, 3));
+        let err = client.send_wake_request(&cfg).await.unwrap_err();
+        assert_eq!(record_wake_error(&runtime, 1, &err, policy(), clock.now_ms()).await, 1);
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 3);
+        client.send_wake_request(&cfg).await.unwrap();
+
+        // A rate-limited or exhausted key: quota polls retry the 429, wakes don't.
+        server.inject(FaultRule::once(Endpoint::Quota, Fault::Http { status: 429 }));
+        assert!(client.fetch_quota(&cfg, "test").await.is_ok());
+        assert_eq!(server.requests(Endpoint::Quota), 6);
+        server.inject(FaultRule::always(Endpoint::ChatCompletions, Fault::Http { status: 429 }));
+        assert!(client.send_wake_request(&cfg).await.is_err());
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 5);
+    }
+
+    /// Let the slot task loops run for `minutes`, moving `clock` with them.
+    async fn run_for(clock: &FakeClock, minutes: u32) {
+        for _ in 0..minutes {
+            clock.advance(MINUTE);
+            time::sleep(MINUTE).await;
+        }
+    }
+
+    /// Start a manager on `clock` for one slot talking to `server`, over a
+    /// config directory removed when the returned guard drops.
+    async fn start_manager(
+        clock: &Arc<FakeClock>,
+        server: &MockServer,
+        slot: KeySlotConfig,
+    ) -> (SchedulerManager, Arc<RwLock<RuntimeStatus>>, TempDir) {
+        let config = AppConfig {
+            debug: true,
+            mock_url: Some(server.url()),
+            max_consecutive_errors: MAX_ERRORS,
+            retry: RetryPolicy::none(),
+            slots: vec![slot],
+            ..AppConfig::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::new(RwLock::new(config.clone()))));
+        let runtime = runtime();
+        let mut manager = SchedulerManager::with_clock(clock.clone());
+        manager.start(host, config, runtime.clone()).await;
+        (manager, runtime, dir)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fire_all_sends_every_missed_wake_after_resume() {
+        let clock = clock();
+        // Keys stay warm for the default 3 minutes after a wake.
+        let now = clock.clone();
+        let server = MockServer::start(MockConfig {
+            now: Arc::new(move || now.now_ms()),
+            ..MockConfig::default()
+        })
+        .await
+        .unwrap();
+        let slot = KeySlotConfig {
+            schedule_interval_enabled: true,
+            schedule_interval_minutes: 60,
+            missed_fire_policy: MissedFirePolicy::FireAll,
+            ..slot_config()
+        };
+        let (mut manager, _, _dir) = start_manager(&clock, &server, slot).await;
+
+        // The poller's initial wake for the cold key.
+        run_for(&clock, 1).await;
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 1);
+
+        // 08:01 → 11:31 asleep misses the 09:00, 10:00 and 11:00 fires. Each
+        // catch-up goes out once the previous wake's window has closed.
+        clock.suspend(3 * HOUR + 30 * MINUTE);
+        run_for(&clock, 15).await;
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 1 + 3);
+
+        manager.stop().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failing_wakes_open_the_circuit_until_a_probe_succeeds() {
+        let clock = clock();
+        let (server, _) = mock_server(&clock).await;
+        server.inject(FaultRule::always(Endpoint::ChatCompletions, Fault::Http { status: 503 }));
+        let (mut manager, runtime, _dir) = start_manager(&clock, &server, slot_config()).await;
+
+        // The initial wake fails, then the wake loop retries once a minute.
+        run_for(&clock, 10).await;
+        assert_eq!(server.requests(Endpoint::ChatCompletions), MAX_ERRORS as u64);
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(slot.wake_auto_disabled());
+        assert_eq!(slot.wake_consecutive_errors, MAX_ERRORS);
+        assert_eq!(slot.wake_circuit.state, CircuitState::Open);
+        let next_probe = slot.wake_circuit.next_probe_epoch_ms.unwrap();
+
+        // Nothing goes out until the probe is due; a successful one closes it.
+        server.clear_faults();
+        run_for(&clock, ((next_probe - clock.now_ms()) / 60_000) as u32 - 1).await;
+        assert_eq!(server.requests(Endpoint::ChatCompletions), MAX_ERRORS as u64);
+        run_for(&clock, 2).await;
+        assert_eq!(server.requests(Endpoint::ChatCompletions), MAX_ERRORS as u64 + 1);
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(!slot.wake_auto_disabled());
+        assert!(slot.wake_circuit.is_closed());
+        assert_eq!(slot.wake_consecutive_errors, 0);
+
+        manager.stop().await;
+    }
+
+    #[tokio::test]
+    async fn slot_clients_follow_retry_policy_edits() {
+        let clock = clock();
+        let (server, _) = mock_server(&clock).await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig {
+            debug: true,
+            mock_url: Some(server.url()),
+            retry: RetryPolicy::none(),
+            ..AppConfig::default()
+        };
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::new(RwLock::new(config.clone()))));
+        let mut clients = SlotClient::new(host, clock.clone());
+        let cfg = slot_config();
+
+        server.inject(FaultRule::times(Endpoint::Quota, Fault::Http { status: 503 }, 2));
+        assert!(clients.update(&config, &cfg).unwrap().fetch_quota(&cfg, "test").await.is_err());
+
+        let config = AppConfig {
+            retry: RetryPolicy {
+                base_delay_ms: 1,
+                ..RetryPolicy::default()
+            },
+            ..config
+        };
+        assert!(clients.update(&config, &cfg).unwrap().fetch_quota(&cfg, "test").await.is_ok());
+        assert_eq!(server.requests(Endpoint::Quota), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn client_build_errors_wait_for_a_network_fix() {
+        let clock = clock();
+        let (server, _) = mock_server(&clock).await;
+        let broken = AppConfig {
+            debug: true,
+            mock_url: Some(server.url()),
+            max_consecutive_errors: MAX_ERRORS,
+            retry: RetryPolicy::none(),
+            network: NetworkConfig {
+                ca_bundle_path: "/nonexistent/glm-tray-ca.pem".to_string(),
+                ..NetworkConfig::default()
+            },
+            slots: vec![slot_config()],
+            ..AppConfig::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let host: HostHandle = Arc::new(HeadlessHost::new(dir.path().to_path_buf(), Arc::new(RwLock::new(broken.clone()))));
+        let runtime = runtime();
+        let mut manager = SchedulerManager::with_clock(clock.clone());
+        manager.start(host.clone(), broken.clone(), runtime.clone()).await;
+
+        // Without a client nothing goes out, but the slot keeps running.
+        run_for(&clock, 10).await;
+        assert_eq!(server.requests(Endpoint::Quota), 0);
+        assert_eq!(server.requests(Endpoint::ChatCompletions), 0);
+        let slot = runtime.read().await.slot(1).cloned().unwrap();
+        assert!(slot.last_error.as_deref().unwrap_or_default().contains("CA bundle"), "{:?}", slot.last_error);
+
+        // Fixing the network settings rebuilds the client in place.
+        let fixed = AppConfig {
+            network: NetworkConfig::default(),
+            ..broken
+        };
+        manager.reload_if_running(host, fixed, runtime.clone()).await;
+        run_for(&clock, 2).await;
+        assert!(server.requests(Endpoint::Quota) > 0);
+
+        manager.stop().await;
+    }
+}
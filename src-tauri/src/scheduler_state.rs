@@ -0,0 +1,172 @@
+//! Scheduler state snapshot (`scheduler-state.json` in the config dir).
+//! Schedule markers and wake/quota error state are saved while monitoring
+//! runs and restored when it starts, so interval cadence, dedupe markers,
+//! pending wake confirmations and open circuits survive a restart
+//! or crash. Stopping monitoring from the UI, tray or CLI discards it.
+
+use std::path::PathBuf;
+
+use chrono::Local;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::host::HostHandle;
+use crate::models::{CircuitState, CircuitStatus, SlotRuntimeStatus};
+
+const STATE_FILE: &str = "scheduler-state.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerState {
+    pub saved_at_epoch_ms: i64,
+    pub slots: Vec<SlotState>,
+}
+
+/// One slot's schedule and runtime state. Monotonic timers are stored as
+/// epoch ms.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlotState {
+    pub slot: usize,
+    /// State saved for a different API key is not restored.
+    pub key_fingerprint: String,
+
+    pub next_reset_epoch_ms: Option<i64>,
+    pub last_known_reset_epoch_ms: Option<i64>,
+    pub last_times_marker: Option<String>,
+    pub last_cron_marker: Option<String>,
+    pub last_reset_marker: Option<i64>,
+    pub last_interval_fire_epoch_ms: Option<i64>,
+    pub wake_retry_window_deadline_epoch_ms: Option<i64>,
+    pub wake_timeout_retry_fired: bool,
+    pub missed_wakes: u32,
+
+    pub last_updated_epoch_ms: Option<i64>,
+    pub last_error: Option<String>,
+    pub wake_consecutive_errors: u32,
+    pub quota_consecutive_errors: u32,
+    pub consecutive_errors: u32,
+    pub wake_pending: bool,
+    pub wake_reset_epoch_ms: Option<i64>,
+    pub wake_auto_disabled: bool,
+    pub auto_disabled: bool,
+    pub quota_circuit: CircuitStatus,
+    pub wake_circuit: CircuitStatus,
+}
+
+impl SchedulerState {
+    /// Saved state for `slot`, if it belongs to `api_key`.
+    pub fn slot(&self, slot: usize, api_key: &str) -> Option<&SlotState> {
+        let fingerprint = key_fingerprint(api_key);
+        self.slots
+            .iter()
+            .find(|s| s.slot == slot && s.key_fingerprint == fingerprint)
+    }
+}
+
+impl SlotState {
+    pub fn capture_runtime(&mut self, runtime: &SlotRuntimeStatus) {
+        self.last_updated_epoch_ms = runtime.last_updated_epoch_ms;
+        self.last_error = runtime.last_error.clone();
+        self.wake_consecutive_errors = runtime.wake_consecutive_errors;
+        self.quota_consecutive_errors = runtime.quota_consecutive_errors;
+        self.consecutive_errors = runtime.consecutive_errors;
+        self.wake_pending = runtime.wake_pending;
+        self.wake_reset_epoch_ms = runtime.wake_reset_epoch_ms;
+        self.wake_auto_disabled = runtime.wake_auto_disabled();
+        self.auto_disabled = runtime.auto_disabled();
+        self.quota_circuit = runtime.quota_circuit.clone();
+        self.wake_circuit = runtime.wake_circuit.clone();
+    }
+
+    pub fn restore_runtime(&self, runtime: &mut SlotRuntimeStatus) {
+        runtime.last_updated_epoch_ms = self.last_updated_epoch_ms;
+        runtime.last_error = self.last_error.clone();
+        runtime.wake_consecutive_errors = self.wake_consecutive_errors;
+        runtime.quota_consecutive_errors = self.quota_consecutive_errors;
+        runtime.consecutive_errors = self.consecutive_errors;
+        runtime.wake_pending = self.wake_pending;
+        runtime.wake_reset_epoch_ms = self.wake_reset_epoch_ms;
+        runtime.quota_circuit = restored_circuit(&self.quota_circuit, self.auto_disabled);
+        runtime.wake_circuit = restored_circuit(&self.wake_circuit, self.wake_auto_disabled);
+    }
+}
+
+/// A probe cut short by the restart, or an auto-disable saved before
+/// circuits existed, comes back open with its probe due at once.
+fn restored_circuit(circuit: &CircuitStatus, auto_disabled: bool) -> CircuitStatus {
+    let mut circuit = circuit.clone();
+    if circuit.state == CircuitState::HalfOpen || (auto_disabled && circuit.is_closed()) {
+        circuit.state = CircuitState::Open;
+        circuit.next_probe_epoch_ms = None;
+    }
+    circuit
+}
+
+/// Short SHA-256 prefix of the key; enough to tell keys apart without
+/// storing them.
+pub fn key_fingerprint(api_key: &str) -> String {
+    Sha256::digest(api_key.trim().as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn state_path(host: &HostHandle) -> Result<PathBuf, String> {
+    Ok(host.config_dir()?.join(STATE_FILE))
+}
+
+/// Saved state, or an empty one when there is none or it can't be read.
+pub async fn load(host: &HostHandle) -> SchedulerState {
+    let Ok(path) = state_path(host) else {
+        return SchedulerState::default();
+    };
+    let content = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return SchedulerState::default(),
+        Err(err) => {
+            warn!("failed to read scheduler state: {err}");
+            return SchedulerState::default();
+        }
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        warn!("ignoring unreadable scheduler state: {err}");
+        SchedulerState::default()
+    })
+}
+
+/// Write the state via a temp file so a crash mid-write keeps the old one.
+pub async fn save(host: &HostHandle, slots: Vec<SlotState>) -> Result<(), String> {
+    let path = state_path(host)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("failed to create config directory: {err}"))?;
+    }
+    let state = SchedulerState {
+        saved_at_epoch_ms: Local::now().timestamp_millis(),
+        slots,
+    };
+    let serialized = serde_json::to_string_pretty(&state)
+        .map_err(|err| format!("failed to serialize scheduler state: {err}"))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serialized)
+        .await
+        .map_err(|err| format!("failed to write scheduler state: {err}"))?;
+    fs::rename(&tmp, &path)
+        .await
+        .map_err(|err| format!("failed to replace scheduler state: {err}"))
+}
+
+pub async fn clear(host: &HostHandle) {
+    if let Ok(path) = state_path(host) {
+        if let Err(err) = fs::remove_file(&path).await {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                warn!("failed to remove scheduler state: {err}");
+            }
+        }
+    }
+}
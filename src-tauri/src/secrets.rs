@@ -0,0 +1,288 @@
+//! API keys at rest. `settings.json` stores each slot's key sealed with
+//! ChaCha20-Poly1305 as `enc:v1:<base64 nonce + ciphertext>`; the cipher key
+//! is the machine-bound `settings.key` next to it, stretched with PBKDF2
+//! through `$GLM_TRAY_PASSPHRASE` when one is set. Keys are only ever
+//! plaintext in memory, and the settings UI sees them masked.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use log::info;
+use sha2::Sha256;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::host::HostHandle;
+use crate::models::AppConfig;
+
+/// Environment variable holding the optional passphrase.
+pub const PASSPHRASE_ENV: &str = "GLM_TRAY_PASSPHRASE";
+
+const KEY_FILE_NAME: &str = "settings.key";
+const SECRET_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 100_000;
+const SEALED_PREFIX: &str = "enc:v1:";
+const MASK: &str = "••••";
+
+pub struct KeyVault {
+    cipher: ChaCha20Poly1305,
+}
+
+impl KeyVault {
+    /// Vault for the config dir, creating `settings.key` on first use.
+    pub async fn open(host: &HostHandle) -> Result<Self, String> {
+        let secret = load_or_create_secret(&host.config_dir()?.join(KEY_FILE_NAME)).await?;
+        let passphrase = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty());
+        Ok(Self::from_secret(&secret, passphrase.as_deref()))
+    }
+
+    fn from_secret(secret: &[u8; SECRET_LEN], passphrase: Option<&str>) -> Self {
+        let mut key = *secret;
+        if let Some(passphrase) = passphrase {
+            pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), secret, PBKDF2_ROUNDS, &mut key);
+        }
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        }
+    }
+
+    /// `api_key` sealed for storage; a blank key stays blank.
+    pub fn seal(&self, api_key: &str) -> String {
+        if api_key.is_empty() {
+            return String::new();
+        }
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, api_key.as_bytes())
+            .expect("ChaCha20-Poly1305 encryption of a short key cannot fail");
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        format!("{SEALED_PREFIX}{}", BASE64.encode(sealed))
+    }
+
+    /// The key behind a stored value; values that were never sealed pass through.
+    pub fn unseal(&self, stored: &str) -> Result<String, String> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let sealed = BASE64
+            .decode(encoded)
+            .map_err(|err| format!("malformed encrypted key: {err}"))?;
+        if sealed.len() <= NONCE_LEN {
+            return Err("malformed encrypted key: too short".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| format!("wrong {KEY_FILE_NAME} or ${PASSPHRASE_ENV}"))?;
+        String::from_utf8(plaintext).map_err(|_| "encrypted key is not UTF-8".to_string())
+    }
+
+    /// Seal every slot's key in place. Keys that still match their
+    /// `previous` sealed value keep it, so an unchanged config serialises
+    /// the same way twice.
+    pub fn seal_keys(&self, cfg: &mut AppConfig, previous: &HashMap<usize, String>) {
+        for slot in cfg.slots.iter_mut() {
+            let reused = previous
+                .get(&slot.slot)
+                .filter(|sealed| self.unseal(sealed).is_ok_and(|key| key == slot.api_key));
+            slot.api_key = match reused {
+                Some(sealed) => sealed.clone(),
+                None => self.seal(&slot.api_key),
+            };
+        }
+    }
+
+    /// Unseal every slot's key in place; returns whether any key was stored
+    /// in plaintext and still needs sealing.
+    pub fn unseal_keys(&self, cfg: &mut AppConfig) -> Result<bool, String> {
+        let mut plaintext = false;
+        for slot in cfg.slots.iter_mut() {
+            plaintext |= !slot.api_key.is_empty() && !is_sealed(&slot.api_key);
+            slot.api_key = self
+                .unseal(&slot.api_key)
+                .map_err(|err| format!("cannot decrypt the API key of slot {}: {err}", slot.slot))?;
+        }
+        Ok(plaintext)
+    }
+}
+
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(SEALED_PREFIX)
+}
+
+/// Sealed keys of a config as read from disk, by slot ID.
+pub fn sealed_keys(cfg: &AppConfig) -> HashMap<usize, String> {
+    cfg.slots
+        .iter()
+        .filter(|slot| is_sealed(&slot.api_key))
+        .map(|slot| (slot.slot, slot.api_key.clone()))
+        .collect()
+}
+
+/// `••••` and the last four characters, or just `••••` for keys too short
+/// to give any away. A blank key stays blank.
+pub fn mask_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.trim().chars().collect();
+    match chars.len() {
+        0 => String::new(),
+        1..=8 => MASK.to_string(),
+        n => format!("{MASK}{}", chars[n - 4..].iter().collect::<String>()),
+    }
+}
+
+/// `cfg` as the settings UI may see it.
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub fn masked(cfg: &AppConfig) -> AppConfig {
+    let mut masked = cfg.clone();
+    for slot in masked.slots.iter_mut() {
+        slot.api_key = mask_key(&slot.api_key);
+    }
+    masked
+}
+
+/// Put the real keys of `current` back into a config edited by the UI,
+/// which only ever held masked ones. New slots start without a key. A key
+/// that is neither blank nor masked is an error: keys are only replaced
+/// through their own command.
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub fn restore_keys(edited: &mut AppConfig, current: &AppConfig) -> Result<(), String> {
+    if let Some(slot) = edited
+        .slots
+        .iter()
+        .find(|slot| !slot.api_key.trim().is_empty() && !slot.api_key.starts_with(MASK))
+    {
+        return Err(format!("slot {} API key must be masked; replace it with set_slot_api_key", slot.slot));
+    }
+    for slot in edited.slots.iter_mut() {
+        slot.api_key = current
+            .slot(slot.slot)
+            .map(|existing| existing.api_key.clone())
+            .unwrap_or_default();
+    }
+    Ok(())
+}
+
+async fn load_or_create_secret(path: &Path) -> Result<[u8; SECRET_LEN], String> {
+    match fs::read(path).await {
+        Ok(bytes) => return parse_secret(path, bytes),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(format!("failed to read {}: {err}", path.display())),
+    }
+
+    let mut secret = [0u8; SECRET_LEN];
+    OsRng.fill_bytes(&mut secret);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("failed to create config directory: {err}"))?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = match options.open(path).await {
+        Ok(file) => file,
+        // Another process created it first; use theirs.
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            let bytes = fs::read(path)
+                .await
+                .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+            return parse_secret(path, bytes);
+        }
+        Err(err) => return Err(format!("failed to create {}: {err}", path.display())),
+    };
+    file.write_all(&secret)
+        .await
+        .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+    info!("created key file {}", path.display());
+    Ok(secret)
+}
+
+fn parse_secret(path: &Path, bytes: Vec<u8>) -> Result<[u8; SECRET_LEN], String> {
+    bytes
+        .try_into()
+        .map_err(|_| format!("{} is corrupt: expected {SECRET_LEN} bytes", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::KeySlotConfig;
+
+    fn config(keys: &[(usize, &str)]) -> AppConfig {
+        AppConfig {
+            slots: keys
+                .iter()
+                .map(|(slot, key)| KeySlotConfig {
+                    slot: *slot,
+                    api_key: key.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sealed_keys_round_trip_only_with_the_same_secret_and_passphrase() {
+        let secret = [7u8; SECRET_LEN];
+        let vault = KeyVault::from_secret(&secret, Some("hunter2"));
+        let sealed = vault.seal("sk-live-0123456789abcdef");
+
+        assert!(is_sealed(&sealed) && !sealed.contains("0123456789abcdef"));
+        assert_ne!(sealed, vault.seal("sk-live-0123456789abcdef"));
+        assert_eq!(vault.unseal(&sealed).unwrap(), "sk-live-0123456789abcdef");
+        assert_eq!(vault.unseal("plain-key").unwrap(), "plain-key");
+        assert_eq!(vault.seal(""), "");
+
+        assert!(KeyVault::from_secret(&secret, None).unseal(&sealed).is_err());
+        assert!(KeyVault::from_secret(&[8u8; SECRET_LEN], Some("hunter2")).unseal(&sealed).is_err());
+    }
+
+    #[test]
+    fn unchanged_keys_keep_their_sealed_value() {
+        let vault = KeyVault::from_secret(&[1u8; SECRET_LEN], None);
+        let mut cfg = config(&[(1, "key-one"), (2, "key-two")]);
+        vault.seal_keys(&mut cfg, &HashMap::new());
+        let previous = sealed_keys(&cfg);
+        assert_eq!(previous.len(), 2);
+
+        let mut edited = config(&[(1, "key-one"), (2, "key-replaced")]);
+        vault.seal_keys(&mut edited, &previous);
+        assert_eq!(edited.slots[0].api_key, previous[&1]);
+        assert_ne!(edited.slots[1].api_key, previous[&2]);
+
+        assert!(!vault.unseal_keys(&mut edited).unwrap());
+        assert_eq!(edited.slots[1].api_key, "key-replaced");
+        assert!(vault.unseal_keys(&mut config(&[(3, "plain")])).unwrap());
+    }
+
+    #[test]
+    fn the_ui_sees_masked_keys_and_cannot_change_them() {
+        assert_eq!(mask_key("sk-0123456789abcdef"), "••••cdef");
+        assert_eq!(mask_key("short"), "••••");
+        assert_eq!(mask_key(" "), "");
+
+        let current = config(&[(1, "sk-0123456789abcdef")]);
+        let mut edited = masked(&current);
+        assert_eq!(edited.slots[0].api_key, "••••cdef");
+        edited.slots.push(KeySlotConfig { slot: 2, api_key: "••••cdef".to_string(), ..Default::default() });
+
+        restore_keys(&mut edited, &current).unwrap();
+        assert_eq!(edited.slots[0].api_key, "sk-0123456789abcdef");
+        assert_eq!(edited.slots[1].api_key, "");
+
+        let mut plaintext = masked(&current);
+        plaintext.slots[0].api_key = "sk-replaced-0123456789".to_string();
+        assert!(restore_keys(&mut plaintext, &current).is_err());
+    }
+}
@@ -0,0 +1,623 @@
+//! Schedule dry run. Replays a slot's wake schedule over a simulated span
+//! (a day by default) on a `FakeClock`, against scripted quota answers
+//! instead of the API, using the scheduler's own wake, confirmation, retry
+//! and circuit breaker helpers. Nothing is sent and no live state is touched.
+
+use std::sync::Arc;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::clock::{Clock, FakeClock};
+use crate::models::{AppConfig, CircuitState, KeySlotConfig, QuotaSnapshot, RuntimeStatus, SlotRuntimeStatus};
+use crate::scheduler::{self, CircuitEndpoint, SchedulerPolicy, SlotSchedule, WakeConfirmOutcome};
+use crate::slot_zone::SlotZone;
+
+pub const DEFAULT_HOURS: u32 = 24;
+pub const MAX_HOURS: u32 = 72;
+
+/// Length of the quota window a simulated wake opens.
+const WINDOW_MS: i64 = 5 * 3_600_000;
+
+const MINUTE_MS: i64 = 60_000;
+
+/// What the quota API answers from `at_minute` (minutes after the start)
+/// until the next step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaStep {
+    pub at_minute: u32,
+    pub percentage: u8,
+    /// Minute the current window resets at; `None` means no active window.
+    pub reset_at_minute: Option<u32>,
+    /// Quota requests fail with this error.
+    pub error: Option<String>,
+    /// Wake requests fail with this error.
+    pub wake_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationInput {
+    /// Defaults to now.
+    pub start_epoch_ms: Option<i64>,
+    pub hours: u32,
+    pub quota: Vec<QuotaStep>,
+    /// Whether a successful wake with no active window opens a 5h one, as
+    /// the real API does. Off, every wake goes unconfirmed.
+    pub wake_starts_window: bool,
+}
+
+impl Default for SimulationInput {
+    fn default() -> Self {
+        Self {
+            start_epoch_ms: None,
+            hours: DEFAULT_HOURS,
+            quota: Vec::new(),
+            wake_starts_window: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SimulationEventKind {
+    Wake,
+    WakeSkipped,
+    WakeSuppressed,
+    WakeFailed,
+    WakeConfirmed,
+    WakeUnconfirmed,
+    WakeAutoDisabled,
+    WakeProbe,
+    Poll,
+    PollRetry,
+    PollFailed,
+    PollAutoDisabled,
+    PollProbe,
+}
+
+impl SimulationEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Wake => "wake",
+            Self::WakeSkipped => "wake-skipped",
+            Self::WakeSuppressed => "wake-suppressed",
+            Self::WakeFailed => "wake-failed",
+            Self::WakeConfirmed => "wake-confirmed",
+            Self::WakeUnconfirmed => "wake-unconfirmed",
+            Self::WakeAutoDisabled => "wake-auto-disabled",
+            Self::WakeProbe => "wake-probe",
+            Self::Poll => "poll",
+            Self::PollRetry => "poll-retry",
+            Self::PollFailed => "poll-failed",
+            Self::PollAutoDisabled => "poll-auto-disabled",
+            Self::PollProbe => "poll-probe",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationEvent {
+    pub epoch_ms: i64,
+    /// `epoch_ms` in the slot's zone, e.g. `Mon 09:00`.
+    pub local: String,
+    pub kind: SimulationEventKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub slot: usize,
+    pub start_epoch_ms: i64,
+    pub end_epoch_ms: i64,
+    pub wakes: u32,
+    pub polls: u32,
+    pub confirmed: u32,
+    /// The wake circuit opened at some point.
+    pub wake_auto_disabled: bool,
+    /// The quota circuit opened at some point.
+    pub quota_auto_disabled: bool,
+    pub events: Vec<SimulationEvent>,
+}
+
+/// Scripted stand-in for the quota and wake endpoints.
+struct SimulatedApi {
+    start_ms: i64,
+    steps: Vec<QuotaStep>,
+    wake_starts_window: bool,
+    /// Window opened by the last simulated wake: (woken at, resets at).
+    woken: Option<(i64, i64)>,
+}
+
+impl SimulatedApi {
+    fn at(&self, minute: u32) -> i64 {
+        self.start_ms + i64::from(minute) * MINUTE_MS
+    }
+
+    fn step(&self, now_ms: i64) -> Option<&QuotaStep> {
+        self.steps.iter().rev().find(|step| self.at(step.at_minute) <= now_ms)
+    }
+
+    /// The active window's reset time. A wake newer than the current step
+    /// overrides the step's window.
+    fn reset(&self, now_ms: i64) -> Option<i64> {
+        let step = self.step(now_ms);
+        let step_since = step.map_or(i64::MIN, |step| self.at(step.at_minute));
+        let reset = match self.woken {
+            Some((woken_at, reset)) if woken_at >= step_since => Some(reset),
+            _ => step.and_then(|step| step.reset_at_minute).map(|minute| self.at(minute)),
+        };
+        reset.filter(|reset| *reset > now_ms)
+    }
+
+    fn quota(&self, now_ms: i64, zone: SlotZone) -> Result<QuotaSnapshot, String> {
+        let step = self.step(now_ms);
+        if let Some(err) = step.and_then(|step| step.error.clone()) {
+            return Err(err);
+        }
+        let reset = self.reset(now_ms);
+        Ok(QuotaSnapshot {
+            percentage: if reset.is_some() { step.map_or(0, |step| step.percentage) } else { 0 },
+            timer_active: reset.is_some(),
+            next_reset_hms: reset.and_then(|ms| zone.format(ms, "%H:%M:%S")),
+            next_reset_epoch_ms: reset,
+        })
+    }
+
+    fn wake(&mut self, now_ms: i64) -> Result<(), String> {
+        if let Some(err) = self.step(now_ms).and_then(|step| step.wake_error.clone()) {
+            return Err(err);
+        }
+        if self.wake_starts_window && self.reset(now_ms).is_none() {
+            self.woken = Some((now_ms, now_ms + WINDOW_MS));
+        }
+        Ok(())
+    }
+}
+
+struct Simulation {
+    cfg: KeySlotConfig,
+    policy: SchedulerPolicy,
+    zone: SlotZone,
+    clock: Arc<FakeClock>,
+    api: SimulatedApi,
+    schedule: Arc<RwLock<SlotSchedule>>,
+    runtime: Arc<RwLock<RuntimeStatus>>,
+    report: SimulationReport,
+    next_poll_ms: i64,
+    /// Detail of the last poll event; unchanged answers are not repeated.
+    last_poll: Option<String>,
+}
+
+/// Run `slot` (already validated) through `input` under `app`'s policy.
+pub async fn simulate(app: &AppConfig, slot: KeySlotConfig, input: SimulationInput) -> Result<SimulationReport, String> {
+    let start_ms = input
+        .start_epoch_ms
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let start = DateTime::from_timestamp_millis(start_ms).ok_or("invalid start time")?;
+    let hours = input.hours.clamp(1, MAX_HOURS);
+    let end_ms = start_ms + i64::from(hours) * 3_600_000;
+
+    let mut steps = input.quota;
+    steps.sort_by_key(|step| step.at_minute);
+    let clock = FakeClock::new(start);
+    let runtime = RuntimeStatus {
+        monitoring: true,
+        slots: vec![SlotRuntimeStatus {
+            slot: slot.slot,
+            name: slot.name.clone(),
+            enabled: true,
+            ..SlotRuntimeStatus::default()
+        }],
+    };
+    let mut sim = Simulation {
+        policy: SchedulerPolicy::from(app),
+        zone: SlotZone::for_slot(&slot),
+        schedule: Arc::new(RwLock::new(SlotSchedule::new(clock.instant()))),
+        runtime: Arc::new(RwLock::new(runtime)),
+        api: SimulatedApi {
+            start_ms,
+            steps,
+            wake_starts_window: input.wake_starts_window,
+            woken: None,
+        },
+        report: SimulationReport {
+            slot: slot.slot,
+            start_epoch_ms: start_ms,
+            end_epoch_ms: end_ms,
+            ..SimulationReport::default()
+        },
+        cfg: slot,
+        clock,
+        next_poll_ms: start_ms,
+        last_poll: None,
+    };
+
+    sim.initial_wake().await;
+    while sim.clock.now_ms() < end_ms {
+        if sim.wake_tick().await {
+            sim.next_poll_ms = sim.clock.now_ms();
+        }
+        if sim.clock.now_ms() >= sim.next_poll_ms {
+            sim.poll().await;
+        }
+        sim.clock.advance(std::time::Duration::from_millis(MINUTE_MS as u64));
+    }
+    Ok(sim.report)
+}
+
+impl Simulation {
+    fn push(&mut self, kind: SimulationEventKind, detail: impl Into<String>) {
+        let epoch_ms = self.clock.now_ms();
+        self.report.events.push(SimulationEvent {
+            epoch_ms,
+            local: self.zone.format(epoch_ms, "%a %H:%M").unwrap_or_default(),
+            kind,
+            detail: detail.into(),
+        });
+    }
+
+    fn describe_reset(&self, reset_ms: Option<i64>) -> String {
+        reset_ms
+            .and_then(|ms| self.zone.format(ms, "%a %H:%M"))
+            .map_or_else(|| "no active window".to_string(), |at| format!("resets {at}"))
+    }
+
+    /// The pre-check the scheduler runs before a wake: cached window first,
+    /// then a (simulated) quota request.
+    async fn wake_required(&self) -> bool {
+        let now_ms = self.clock.now_ms();
+        if scheduler::cached_window_active(&self.runtime, self.cfg.slot, now_ms).await {
+            return false;
+        }
+        let quota = self.api.quota(now_ms, self.zone);
+        scheduler::apply_wake_precheck(&self.runtime, self.cfg.slot, quota, now_ms).await
+    }
+
+    /// Record a failed wake; enough of them in a row open the wake circuit.
+    async fn wake_failed(&mut self, reason: &str, err: String) {
+        self.push(SimulationEventKind::WakeFailed, format!("{reason}: {err}"));
+        let errors = scheduler::record_wake_error(
+            &self.runtime,
+            self.cfg.slot,
+            &err,
+            self.policy,
+            self.clock.now_ms(),
+        )
+        .await;
+        if errors >= self.policy.max_consecutive_errors {
+            let detail = format!("after {errors} consecutive wake failures; {}", self.describe_probe(CircuitEndpoint::Wake).await);
+            self.push(SimulationEventKind::WakeAutoDisabled, detail);
+            self.report.wake_auto_disabled = true;
+        }
+    }
+
+    /// When the open `endpoint` circuit probes next, e.g. `probe Mon 09:15`.
+    async fn describe_probe(&self, endpoint: CircuitEndpoint) -> String {
+        let runtime = self.runtime.read().await;
+        let circuit = runtime.slot(self.cfg.slot).map(|slot| match endpoint {
+            CircuitEndpoint::Quota => &slot.quota_circuit,
+            CircuitEndpoint::Wake => &slot.wake_circuit,
+        });
+        circuit
+            .and_then(|circuit| circuit.next_probe_epoch_ms)
+            .and_then(|ms| self.zone.format(ms, "%a %H:%M"))
+            .map_or_else(|| "circuit open".to_string(), |at| format!("circuit open, probe {at}"))
+    }
+
+    /// Mirrors the quota poller's start-up wake.
+    async fn initial_wake(&mut self) {
+        if !self.wake_required().await {
+            self.push(SimulationEventKind::WakeSkipped, "initial wake: reset window already active");
+            return;
+        }
+        let now_ms = self.clock.now_ms();
+        match self.api.wake(now_ms) {
+            Err(err) => self.wake_failed("initial wake", err).await,
+            Ok(()) => {
+                self.report.wakes += 1;
+                self.push(SimulationEventKind::Wake, "initial wake");
+                let pre_reset_marker = self
+                    .runtime
+                    .read()
+                    .await
+                    .slot(self.cfg.slot)
+                    .and_then(|slot| slot.last_updated_epoch_ms);
+                scheduler::mark_wake_attempt(&self.runtime, self.cfg.slot, pre_reset_marker).await;
+                let mut sched = self.schedule.write().await;
+                sched.wake_timeout_retry_fired = false;
+                sched.wake_retry_window_deadline = Some(
+                    self.clock.instant()
+                        + std::time::Duration::from_secs(self.policy.wake_quota_retry_window_minutes * 60),
+                );
+            }
+        }
+    }
+
+    /// One minute of the wake scheduler. Returns whether a wake was sent
+    /// (which triggers an immediate poll).
+    async fn wake_tick(&mut self) -> bool {
+        let slot_id = self.cfg.slot;
+        let (quota_closed, wake_state) = self
+            .runtime
+            .read()
+            .await
+            .slot(slot_id)
+            .map_or((true, CircuitState::Closed), |slot| (slot.quota_circuit.is_closed(), slot.wake_circuit.state));
+        if !quota_closed {
+            return false;
+        }
+        if wake_state == CircuitState::Open {
+            let now_ms = self.clock.now_ms();
+            if !scheduler::begin_circuit_probe(&self.runtime, slot_id, CircuitEndpoint::Wake, now_ms).await {
+                return false;
+            }
+            self.push(SimulationEventKind::WakeProbe, "wake circuit half-open");
+        }
+        let clock = &*self.clock;
+        let sched = self.schedule.read().await.clone();
+        let schedule_reason = scheduler::should_fire_wake(&self.cfg, &sched, clock);
+        let (wake_pending, should_retry_after_errors) = {
+            let runtime = self.runtime.read().await;
+            let slot = runtime.slot(slot_id);
+            (
+                slot.is_some_and(|slot| slot.wake_pending),
+                slot.is_some_and(|slot| slot.wake_consecutive_errors > 0 && !slot.wake_pending),
+            )
+        };
+        let wake_window_active =
+            scheduler::should_retry_quota_while_wake_pending(&self.schedule, &self.runtime, slot_id, clock).await;
+        let wake_retry_due = wake_pending && !wake_window_active && !sched.wake_timeout_retry_fired;
+
+        if schedule_reason.is_some() && wake_pending && !wake_retry_due {
+            let mut sched_mut = self.schedule.write().await;
+            scheduler::update_schedule_markers(&self.cfg, &sched, &mut sched_mut, clock);
+            drop(sched_mut);
+            let reason = schedule_reason.unwrap_or_default();
+            self.push(SimulationEventKind::WakeSuppressed, format!("{reason}: previous wake still pending"));
+            return false;
+        }
+        if schedule_reason.is_none() && !should_retry_after_errors && !wake_retry_due {
+            return false;
+        }
+
+        if !self.wake_required().await {
+            if should_retry_after_errors || wake_retry_due {
+                scheduler::clear_wake_state(&self.runtime, slot_id).await;
+            }
+            if schedule_reason.is_some() {
+                let mut sched_mut = self.schedule.write().await;
+                scheduler::update_schedule_markers(&self.cfg, &sched, &mut sched_mut, clock);
+            }
+            let reason = schedule_reason.unwrap_or_else(|| "retry".to_string());
+            self.push(SimulationEventKind::WakeSkipped, format!("{reason}: reset window still active"));
+            return false;
+        }
+
+        let reason = schedule_reason.unwrap_or_else(|| {
+            if wake_retry_due {
+                "forced wake retry after confirmation timeout".to_string()
+            } else {
+                "retrying wake after failures".to_string()
+            }
+        });
+        let now_ms = self.clock.now_ms();
+        match self.api.wake(now_ms) {
+            Err(err) => {
+                self.wake_failed(&reason, err).await;
+                false
+            }
+            Ok(()) => {
+                self.report.wakes += 1;
+                self.push(SimulationEventKind::Wake, reason);
+                scheduler::arm_wake_confirmation(
+                    &self.cfg,
+                    &self.schedule,
+                    &self.runtime,
+                    self.policy,
+                    wake_retry_due,
+                    &*self.clock,
+                )
+                .await;
+                true
+            }
+        }
+    }
+
+    /// One quota poll, then schedule the next one as the poller would.
+    async fn poll(&mut self) {
+        let slot_id = self.cfg.slot;
+        let now_ms = self.clock.now_ms();
+        if scheduler::begin_circuit_probe(&self.runtime, slot_id, CircuitEndpoint::Quota, now_ms).await {
+            self.push(SimulationEventKind::PollProbe, "quota circuit half-open");
+        }
+        match self.api.quota(now_ms, self.zone) {
+            Ok(snapshot) => {
+                self.report.polls += 1;
+                let detail = format!("{}%, {}", snapshot.percentage, self.describe_reset(snapshot.next_reset_epoch_ms));
+                if self.last_poll.as_ref() != Some(&detail) {
+                    self.last_poll = Some(detail.clone());
+                    self.push(SimulationEventKind::Poll, detail);
+                }
+                let outcome = scheduler::complete_wake_if_advanced(
+                    &self.runtime,
+                    slot_id,
+                    snapshot.next_reset_epoch_ms,
+                    self.policy,
+                    now_ms,
+                )
+                .await;
+                match outcome {
+                    WakeConfirmOutcome::Confirmed => {
+                        self.report.confirmed += 1;
+                        let detail = self.describe_reset(snapshot.next_reset_epoch_ms);
+                        self.push(SimulationEventKind::WakeConfirmed, detail);
+                    }
+                    WakeConfirmOutcome::FailedMissing => {
+                        self.push(SimulationEventKind::WakeUnconfirmed, "quota shows no reset time after wake");
+                    }
+                    WakeConfirmOutcome::FailedNotAdvanced => {
+                        self.push(SimulationEventKind::WakeUnconfirmed, "reset time did not advance after wake");
+                    }
+                    WakeConfirmOutcome::AutoDisabled => {
+                        let detail = format!("wake never confirmed; {}", self.describe_probe(CircuitEndpoint::Wake).await);
+                        self.push(SimulationEventKind::WakeAutoDisabled, detail);
+                        self.report.wake_auto_disabled = true;
+                    }
+                    WakeConfirmOutcome::NotPending => {}
+                }
+
+                let wake_pending = self.runtime.read().await.slot(slot_id).is_some_and(|slot| slot.wake_pending);
+                if !wake_pending {
+                    scheduler::clear_wake_quota_retry_window(&self.schedule).await;
+                }
+                {
+                    let mut sched = self.schedule.write().await;
+                    sched.next_reset_epoch_ms = snapshot.next_reset_epoch_ms;
+                    if let Some(reset_ms) = snapshot.next_reset_epoch_ms {
+                        sched.last_known_reset_epoch_ms = Some(reset_ms);
+                    }
+                }
+                scheduler::clear_quota_error(&self.runtime, slot_id).await;
+                let mut runtime = self.runtime.write().await;
+                if let Some(current) = runtime.slot_mut(slot_id) {
+                    current.timer_active = snapshot.timer_active;
+                    current.percentage = Some(snapshot.percentage);
+                    current.next_reset_hms = snapshot.next_reset_hms;
+                    current.last_updated_epoch_ms = snapshot.next_reset_epoch_ms;
+                }
+            }
+            Err(err) => {
+                self.last_poll = None;
+                let clock = &*self.clock;
+                if scheduler::should_retry_quota_while_wake_pending(&self.schedule, &self.runtime, slot_id, clock).await {
+                    self.push(SimulationEventKind::PollRetry, err);
+                } else {
+                    let errors = scheduler::record_quota_error(&self.runtime, slot_id, &err, self.policy, now_ms).await;
+                    self.push(SimulationEventKind::PollFailed, err);
+                    if errors >= self.policy.max_consecutive_errors {
+                        let detail = format!(
+                            "after {errors} consecutive quota failures; {}",
+                            self.describe_probe(CircuitEndpoint::Quota).await
+                        );
+                        self.push(SimulationEventKind::PollAutoDisabled, detail);
+                        self.report.quota_auto_disabled = true;
+                        // Wakes hold until the probe closes the circuit.
+                        self.next_poll_ms = self.runtime.read().await.slot(slot_id).map_or(now_ms, |slot| {
+                            slot.quota_circuit.next_probe_epoch_ms.unwrap_or(now_ms)
+                        });
+                        return;
+                    }
+                }
+            }
+        }
+
+        let window_active =
+            scheduler::should_retry_quota_while_wake_pending(&self.schedule, &self.runtime, slot_id, &*self.clock)
+                .await;
+        let errors = if window_active {
+            0
+        } else {
+            self.runtime
+                .read()
+                .await
+                .slot(slot_id)
+                .map_or(0, |slot| slot.quota_consecutive_errors)
+        };
+        let minutes = scheduler::poll_sleep_minutes(&self.cfg, self.policy, window_active, errors);
+        self.next_poll_ms = now_ms + minutes as i64 * MINUTE_MS;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn start_ms() -> i64 {
+        Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap().timestamp_millis()
+    }
+
+    fn slot_config() -> KeySlotConfig {
+        KeySlotConfig {
+            slot: 1,
+            enabled: true,
+            schedule_timezone: "UTC".to_string(),
+            ..KeySlotConfig::default()
+        }
+    }
+
+    fn kinds(report: &SimulationReport, kind: SimulationEventKind) -> Vec<&str> {
+        report
+            .events
+            .iter()
+            .filter(|event| event.kind == kind)
+            .map(|event| event.local.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn times_schedule_wakes_once_per_window() {
+        let cfg = KeySlotConfig {
+            schedule_times_enabled: true,
+            schedule_times: vec!["09:00".to_string(), "10:00".to_string(), "15:00".to_string()],
+            ..slot_config()
+        };
+        let input = SimulationInput {
+            start_epoch_ms: Some(start_ms()),
+            hours: 12,
+            ..SimulationInput::default()
+        };
+        let report = simulate(&AppConfig::default(), cfg, input).await.unwrap();
+
+        // 08:00 opens a window until 13:00, so 09:00 and 10:00 find it active.
+        assert_eq!(kinds(&report, SimulationEventKind::Wake), ["Mon 08:00", "Mon 15:00"]);
+        assert_eq!(kinds(&report, SimulationEventKind::WakeSkipped), ["Mon 09:00", "Mon 10:00"]);
+        assert_eq!(report.confirmed, 2);
+        assert!(!report.wake_auto_disabled && !report.quota_auto_disabled);
+    }
+
+    #[tokio::test]
+    async fn scripted_failures_open_the_quota_circuit_until_a_probe_succeeds() {
+        let app = AppConfig {
+            max_consecutive_errors: 3,
+            circuit_probe_minutes: 30,
+            ..AppConfig::default()
+        };
+        let input = SimulationInput {
+            start_epoch_ms: Some(start_ms()),
+            hours: 8,
+            quota: vec![
+                QuotaStep {
+                    at_minute: 0,
+                    percentage: 40,
+                    reset_at_minute: Some(30),
+                    ..QuotaStep::default()
+                },
+                QuotaStep {
+                    at_minute: 60,
+                    error: Some("HTTP 500".to_string()),
+                    ..QuotaStep::default()
+                },
+                QuotaStep {
+                    at_minute: 300,
+                    ..QuotaStep::default()
+                },
+            ],
+            ..SimulationInput::default()
+        };
+        let report = simulate(&app, slot_config(), input).await.unwrap();
+
+        // Three failures open the circuit; probes then go out every 30 minutes.
+        assert_eq!(kinds(&report, SimulationEventKind::PollFailed), ["Mon 09:00", "Mon 10:00", "Mon 12:00", "Mon 12:30"]);
+        assert_eq!(kinds(&report, SimulationEventKind::PollAutoDisabled), ["Mon 12:00", "Mon 12:30"]);
+        assert_eq!(kinds(&report, SimulationEventKind::PollProbe), ["Mon 12:30", "Mon 13:00"]);
+        assert!(report.quota_auto_disabled);
+        // The 13:00 probe succeeds and polling resumes.
+        assert_eq!(kinds(&report, SimulationEventKind::Poll).last(), Some(&"Mon 13:00"));
+        assert_eq!(report.events.last().unwrap().kind, SimulationEventKind::Poll);
+    }
+}
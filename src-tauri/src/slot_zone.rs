@@ -0,0 +1,149 @@
+//! Per-slot wall clock. Times mode, after-reset mode, cron mode and the
+//! displayed reset time are evaluated in the slot's IANA time zone (or the
+//! system zone when none is set), limited to the days in its weekday mask.
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, LocalResult, NaiveDateTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+
+use crate::clock::Clock;
+use crate::models::KeySlotConfig;
+
+/// Weekday mask with every day selected (bit 0 = Monday … bit 6 = Sunday).
+pub const ALL_DAYS: u8 = 0x7f;
+
+/// Longest DST jump searched past when a wall-clock time does not exist.
+const MAX_GAP_MINUTES: i64 = 180;
+
+#[derive(Debug, Clone, Copy)]
+pub enum SlotZone {
+    Local,
+    Iana(Tz),
+}
+
+impl SlotZone {
+    /// An empty name means the system zone.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Ok(Self::Local);
+        }
+        name.parse::<Tz>()
+            .map(Self::Iana)
+            .map_err(|_| format!("unknown time zone '{name}'"))
+    }
+
+    /// The slot's zone; `config::validate` has already dropped unknown names.
+    pub fn for_slot(cfg: &KeySlotConfig) -> Self {
+        Self::parse(&cfg.schedule_timezone).unwrap_or(Self::Local)
+    }
+
+    pub fn now(&self, clock: &dyn Clock) -> DateTime<FixedOffset> {
+        self.at(clock.now_ms())
+            .unwrap_or_else(|| clock.now_local().fixed_offset())
+    }
+
+    /// `epoch_ms` as a wall-clock time in this zone.
+    pub fn at(&self, epoch_ms: i64) -> Option<DateTime<FixedOffset>> {
+        let utc = DateTime::from_timestamp_millis(epoch_ms)?;
+        Some(match self {
+            Self::Local => utc.with_timezone(&Local).fixed_offset(),
+            Self::Iana(tz) => utc.with_timezone(tz).fixed_offset(),
+        })
+    }
+
+    pub fn format(&self, epoch_ms: i64, fmt: &str) -> Option<String> {
+        self.at(epoch_ms).map(|dt| dt.format(fmt).to_string())
+    }
+
+    /// Epoch ms at which wall-clock `local` happens. An ambiguous time (DST
+    /// fall-back) resolves to its first occurrence; a skipped one (spring
+    /// forward) to the first valid minute after the gap.
+    pub fn resolve(&self, local: NaiveDateTime) -> Option<i64> {
+        match self {
+            Self::Local => resolve_in(&Local, local),
+            Self::Iana(tz) => resolve_in(tz, local),
+        }
+    }
+}
+
+fn resolve_in<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> Option<i64> {
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Some(dt.timestamp_millis()),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.timestamp_millis()),
+        LocalResult::None => (1..=MAX_GAP_MINUTES).find_map(|minutes| {
+            zone.from_local_datetime(&(local + chrono::Duration::minutes(minutes)))
+                .earliest()
+                .map(|dt| dt.timestamp_millis())
+        }),
+    }
+}
+
+/// Whether `day` is selected in `mask`.
+pub fn day_allowed(mask: u8, day: Weekday) -> bool {
+    mask & (1 << day.num_days_from_monday()) != 0
+}
+
+/// Whether the wall-clock date of `at` is selected in `mask`.
+pub fn date_allowed(mask: u8, at: &DateTime<FixedOffset>) -> bool {
+    day_allowed(mask, at.weekday())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn new_york() -> SlotZone {
+        SlotZone::parse("America/New_York").unwrap()
+    }
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    fn utc_ms(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().timestamp_millis()
+    }
+
+    #[test]
+    fn skipped_time_moves_to_the_end_of_the_gap() {
+        // 2026-03-08 02:00 EST jumps to 03:00 EDT; 02:30 never happens.
+        let zone = new_york();
+        let at = zone.resolve(local(2026, 3, 8, 2, 30)).unwrap();
+        assert_eq!(at, utc_ms(2026, 3, 8, 7, 0));
+        assert_eq!(zone.format(at, "%H:%M %z").unwrap(), "03:00 -0400");
+        // Either side of the gap is unaffected.
+        assert_eq!(zone.resolve(local(2026, 3, 8, 1, 30)), Some(utc_ms(2026, 3, 8, 6, 30)));
+        assert_eq!(zone.resolve(local(2026, 3, 8, 3, 30)), Some(utc_ms(2026, 3, 8, 7, 30)));
+    }
+
+    #[test]
+    fn repeated_time_resolves_to_its_first_occurrence() {
+        // 2026-11-01 02:00 EDT falls back to 01:00 EST; 01:30 happens twice.
+        let zone = new_york();
+        let at = zone.resolve(local(2026, 11, 1, 1, 30)).unwrap();
+        assert_eq!(at, utc_ms(2026, 11, 1, 5, 30));
+        assert_eq!(zone.format(at, "%H:%M %z").unwrap(), "01:30 -0400");
+        // The second 01:30 (EST) maps back to the same wall-clock time.
+        assert_eq!(zone.format(utc_ms(2026, 11, 1, 6, 30), "%H:%M %z").unwrap(), "01:30 -0500");
+    }
+
+    #[test]
+    fn weekday_mask_filters_days_in_the_slot_zone() {
+        let weekdays = 0x1f;
+        assert!(day_allowed(weekdays, Weekday::Mon));
+        assert!(day_allowed(weekdays, Weekday::Fri));
+        assert!(!day_allowed(weekdays, Weekday::Sat));
+        assert!(!day_allowed(weekdays, Weekday::Sun));
+        assert!(day_allowed(ALL_DAYS, Weekday::Sun));
+        assert!(!day_allowed(0, Weekday::Wed));
+
+        // 03:00 UTC on Monday 2026-03-09 is still Sunday evening in New York.
+        let zone = new_york();
+        let at = zone.at(utc_ms(2026, 3, 9, 3, 0)).unwrap();
+        assert_eq!(at.weekday(), Weekday::Sun);
+        assert!(!date_allowed(weekdays, &at));
+        let at = zone.at(utc_ms(2026, 3, 9, 13, 0)).unwrap();
+        assert!(date_allowed(weekdays, &at));
+    }
+}
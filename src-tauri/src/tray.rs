@@ -4,11 +4,13 @@ use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Manager, Wry};
 
-use crate::models::RuntimeStatus;
+use crate::models::{CircuitState, RuntimeStatus, SlotRuntimeStatus};
 
 pub const TRAY_ID: &str = "quota_tray";
 const NORMAL_ICON_BYTES: &[u8] = include_bytes!("../icons/tray-normal.png");
 const ALERT_ICON_BYTES: &[u8] = include_bytes!("../icons/tray-alert.png");
+/// Windows tooltip limit is ~63 chars (NOTIFYICONDATA V1).
+const TOOLTIP_MAX_CHARS: usize = 63;
 
 fn build_tray_menu(
     app: &AppHandle,
@@ -60,19 +62,19 @@ pub fn setup_tray(app: &AppHandle, has_ready_slot: bool) -> Result<(), String> {
                 "start_monitoring" => {
                     info!("tray menu: start monitoring");
                     tauri::async_runtime::spawn(async move {
-                        let _ = crate::start_monitoring_internal(app_handle).await;
+                        let _ = crate::gui::start_monitoring_internal(app_handle).await;
                     });
                 }
                 "stop_monitoring" => {
                     info!("tray menu: stop monitoring");
                     tauri::async_runtime::spawn(async move {
-                        let _ = crate::stop_monitoring_internal(app_handle).await;
+                        let _ = crate::gui::stop_monitoring_internal(app_handle).await;
                     });
                 }
                 "warmup_all" => {
                     info!("tray menu: warmup all keys");
                     tauri::async_runtime::spawn(async move {
-                        let _ = crate::warmup_all_internal(app_handle).await;
+                        let _ = crate::gui::warmup_all_internal(app_handle).await;
                     });
                 }
                 "quit" => {
@@ -127,20 +129,36 @@ pub fn refresh_tray(app: &AppHandle, runtime: RuntimeStatus, has_ready_slot: boo
 
     let enabled_slots: Vec<_> = runtime.slots.iter().filter(|s| s.enabled).collect();
 
-    // Windows tooltip limit is ~63 chars (NOTIFYICONDATA V1).
     // Use ultra-compact single-line format: k1:85% | k2:72% | k3:n/a e1 | k4:DIS
+    // (quota circuit open, `PRB` while probing; `WKE`/`WPR` for the wake
+    // circuit) and fall back to an aggregate summary when too many slots are
+    // enabled.
     let mut parts = Vec::new();
     for slot in &enabled_slots {
         let tag = format!("k{}", slot.slot);
 
-        if slot.auto_disabled {
-            parts.push(format!("{}:DIS", tag));
-            continue;
+        match slot.quota_circuit.state {
+            CircuitState::Open => {
+                parts.push(format!("{}:DIS", tag));
+                continue;
+            }
+            CircuitState::HalfOpen => {
+                parts.push(format!("{}:PRB", tag));
+                continue;
+            }
+            CircuitState::Closed => {}
         }
 
-        if slot.wake_auto_disabled {
-            parts.push(format!("{}:WKE", tag));
-            continue;
+        match slot.wake_circuit.state {
+            CircuitState::Open => {
+                parts.push(format!("{}:WKE", tag));
+                continue;
+            }
+            CircuitState::HalfOpen => {
+                parts.push(format!("{}:WPR", tag));
+                continue;
+            }
+            CircuitState::Closed => {}
         }
 
         let pct_text = slot
@@ -158,7 +176,12 @@ pub fn refresh_tray(app: &AppHandle, runtime: RuntimeStatus, has_ready_slot: boo
     let tooltip = if parts.is_empty() {
         "Quota monitor idle".to_string()
     } else {
-        parts.join(" | ")
+        let joined = parts.join(" | ");
+        if joined.chars().count() <= TOOLTIP_MAX_CHARS {
+            joined
+        } else {
+            summary_tooltip(&enabled_slots)
+        }
     };
 
     debug!("tray tooltip: {}", tooltip);
@@ -166,8 +189,8 @@ pub fn refresh_tray(app: &AppHandle, runtime: RuntimeStatus, has_ready_slot: boo
     tray.set_tooltip(Some(&tooltip))
         .map_err(|err| format!("failed to set tray tooltip: {err}"))?;
 
-    // Red icon when no keys are configured/enabled, or any slot auto-disabled
-    let any_auto_disabled = enabled_slots.iter().any(|s| s.auto_disabled || s.wake_auto_disabled);
+    // Red icon when no keys are configured/enabled, or any slot's circuit is open
+    let any_auto_disabled = enabled_slots.iter().any(|s| s.auto_disabled() || s.wake_auto_disabled());
     let use_alert = (enabled_slots.is_empty() && !runtime.monitoring) || any_auto_disabled;
     let icon_bytes = if use_alert {
         ALERT_ICON_BYTES
@@ -188,3 +211,34 @@ pub fn refresh_tray(app: &AppHandle, runtime: RuntimeStatus, has_ready_slot: boo
 
     Ok(())
 }
+
+/// Aggregate tooltip for when the per-slot list does not fit, e.g.
+/// `12 keys | max k7:91% | 1 DIS | 2 err`, where `DIS` counts slots with an
+/// open (or probing) quota or wake circuit.
+fn summary_tooltip(slots: &[&SlotRuntimeStatus]) -> String {
+    let mut parts = vec![format!("{} keys", slots.len())];
+
+    if let Some(busiest) = slots
+        .iter()
+        .filter(|s| !s.auto_disabled() && !s.wake_auto_disabled())
+        .filter_map(|s| s.percentage.map(|p| (s.slot, p)))
+        .max_by_key(|(_, p)| *p)
+    {
+        parts.push(format!("max k{}:{}%", busiest.0, busiest.1));
+    }
+
+    let disabled = slots
+        .iter()
+        .filter(|s| s.auto_disabled() || s.wake_auto_disabled())
+        .count();
+    if disabled > 0 {
+        parts.push(format!("{disabled} DIS"));
+    }
+
+    let erroring = slots.iter().filter(|s| s.consecutive_errors > 0).count();
+    if erroring > 0 {
+        parts.push(format!("{erroring} err"));
+    }
+
+    parts.join(" | ")
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::NetworkConfig;
+use crate::network;
+
 const GITHUB_API_URL: &str = "https://api.github.com/repos/kiwina/glm-tray/releases/latest";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const UPDATER_JSON_URL: &str = "https://github.com/kiwina/glm-tray/releases/latest/download/updater.json";
@@ -34,9 +37,11 @@ struct UpdaterJson {
 /// Check for updates with improved strategy:
 /// 1. Check updater.json (Source of Truth for Auto-Update)
 /// 2. Fallback to GitHub API (Informational)
-pub async fn check_for_updates() -> Result<UpdateInfo, String> {
+pub async fn check_for_updates(network: &NetworkConfig) -> Result<UpdateInfo, String> {
+    let client = create_client(network)?;
+
     // 1. Try updater.json first (Critical for functional Auto-Update)
-    match check_updater_json().await {
+    match check_updater_json(&client).await {
         Ok(info) => return Ok(info),
         Err(e) => {
             log::warn!("updater.json check failed: {}. Trying fallbacks...", e);
@@ -44,7 +49,7 @@ pub async fn check_for_updates() -> Result<UpdateInfo, String> {
     }
 
     // 2. Try GitHub API
-    match check_github_api().await {
+    match check_github_api(&client).await {
         Ok(info) => return Ok(info),
         Err(e) => {
             log::warn!("GitHub API check failed: {}", e);
@@ -53,16 +58,15 @@ pub async fn check_for_updates() -> Result<UpdateInfo, String> {
     }
 }
 
-async fn create_client() -> Result<reqwest::Client, String> {
-    reqwest::Client::builder()
+fn create_client(network: &NetworkConfig) -> Result<reqwest::Client, String> {
+    network::client_builder(network)?
         .user_agent("glm-tray")
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))
 }
 
-async fn check_updater_json() -> Result<UpdateInfo, String> {
-    let client = create_client().await?;
+async fn check_updater_json(client: &reqwest::Client) -> Result<UpdateInfo, String> {
     log::info!("Checking for updates via updater.json...");
 
     let response = client
@@ -118,9 +122,7 @@ async fn check_updater_json() -> Result<UpdateInfo, String> {
     })
 }
 
-async fn check_github_api() -> Result<UpdateInfo, String> {
-    let client = create_client().await?;
-
+async fn check_github_api(client: &reqwest::Client) -> Result<UpdateInfo, String> {
     log::info!("Checking for updates via GitHub API...");
 
     let response = client
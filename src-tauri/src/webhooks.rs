@@ -0,0 +1,345 @@
+//! Outbound webhooks. Every scheduler event (see `log_scheduler_event`) is
+//! offered to the configured targets; matching ones get a JSON POST, signed
+//! with HMAC-SHA256 when a secret is set and retried with backoff. Each
+//! attempt is recorded in the JSONL log.
+
+use std::time::Instant;
+
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use log::warn;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use tokio::time::{self, Duration};
+
+use crate::api_client;
+use crate::clock::SystemClock;
+use crate::file_logger;
+use crate::host::HostHandle;
+use crate::models::{KeySlotConfig, NetworkConfig, WebhookTarget};
+use crate::network;
+
+/// Event logged when the `TOKENS_LIMIT` percentage crosses one of the
+/// targets' `quota_thresholds` upwards.
+pub const THRESHOLD_EVENT: &str = "quota.threshold-crossed";
+
+/// Event logged when a slot's reset window ends or rolls over.
+pub const RESET_EVENT: &str = "quota.reset-occurred";
+
+const FIRST_RETRY_DELAY_SECS: u64 = 2;
+const MAX_RETRY_DELAY_SECS: u64 = 300;
+
+/// Send `event` to every enabled target subscribed to it. Deliveries run in
+/// the background so a slow endpoint never holds up the scheduler.
+pub async fn dispatch(host: &HostHandle, slot_cfg: &KeySlotConfig, event: &str, details: &Value) {
+    let Some(config) = host.shared_config() else {
+        return;
+    };
+    let (targets, network) = {
+        let config = config.read().await;
+        let targets: Vec<WebhookTarget> = config
+            .webhooks
+            .iter()
+            .filter(|t| t.matches(slot_cfg.slot, event, details))
+            .cloned()
+            .collect();
+        (targets, config.network.clone())
+    };
+    if targets.is_empty() {
+        return;
+    }
+
+    let context = json!({
+        "event": event,
+        "slot": slot_cfg.slot,
+        "slot_name": if slot_cfg.name.is_empty() { format!("Key {}", slot_cfg.slot) } else { slot_cfg.name.clone() },
+        "timestamp": Local::now().to_rfc3339(),
+        "timestamp_ms": Local::now().timestamp_millis(),
+        "details": details,
+    });
+    for target in targets {
+        let host = host.clone();
+        let body = match &target.template {
+            Some(template) => render(template, &context),
+            None => context.clone(),
+        };
+        let event = event.to_string();
+        let slot = slot_cfg.slot;
+        let network = network.clone();
+        tokio::spawn(async move { deliver(&host, slot, &target, &network, &event, &body).await });
+    }
+}
+
+/// Thresholds any enabled target watches that `previous → current` crossed.
+pub async fn crossed_thresholds(host: &HostHandle, previous: u8, current: u8) -> Vec<u8> {
+    let Some(config) = host.shared_config() else {
+        return Vec::new();
+    };
+    let mut crossed: Vec<u8> = config
+        .read()
+        .await
+        .webhooks
+        .iter()
+        .filter(|t| t.enabled)
+        .flat_map(|t| t.quota_thresholds.iter().copied())
+        .filter(|threshold| previous < *threshold && current >= *threshold)
+        .collect();
+    crossed.sort_unstable();
+    crossed.dedup();
+    crossed
+}
+
+impl WebhookTarget {
+    fn matches(&self, slot: usize, event: &str, details: &Value) -> bool {
+        if !self.enabled || (!self.slots.is_empty() && !self.slots.contains(&slot)) {
+            return false;
+        }
+        if event == THRESHOLD_EVENT {
+            let threshold = details["threshold"].as_u64().unwrap_or(0);
+            if !self.quota_thresholds.iter().any(|t| u64::from(*t) == threshold) {
+                return false;
+            }
+        }
+        self.events.is_empty()
+            || self.events.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => event.starts_with(prefix),
+                None => pattern == event,
+            })
+    }
+}
+
+/// Replace `{{name}}` placeholders in string leaves of `template`. A string
+/// that is exactly one placeholder takes the value as-is (numbers stay
+/// numbers); otherwise values are interpolated as text.
+fn render(template: &Value, context: &Value) -> Value {
+    match template {
+        Value::String(text) => {
+            let trimmed = text.trim();
+            if let Some(name) = trimmed
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .filter(|name| !name.contains("{{"))
+            {
+                return lookup(context, name.trim()).cloned().unwrap_or(Value::Null);
+            }
+            Value::String(interpolate(text, context))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| render(item, context)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), render(value, context)))
+                .collect::<Map<_, _>>(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn interpolate(text: &str, context: &Value) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = rest[start + 2..start + end].trim();
+        match lookup(context, name) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(Value::Null) | None => {}
+            Some(value) => out.push_str(&value.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Dotted path lookup, e.g. `details.percentage`.
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(context, |value, key| value.get(key))
+}
+
+/// Scheme, host and port of a target URL. Slack, Discord and Teams webhook
+/// URLs carry their secret in the path, so only this much is logged.
+fn origin(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| "invalid URL".to_string())
+}
+
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> Option<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    Some(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+async fn deliver(
+    host: &HostHandle,
+    slot: usize,
+    target: &WebhookTarget,
+    network: &NetworkConfig,
+    event: &str,
+    payload: &Value,
+) {
+    let client = match network::client_builder(network).and_then(|builder| {
+        builder
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .map_err(|err| err.to_string())
+    }) {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("webhook '{}': failed to create HTTP client: {}", target.name, err);
+            return;
+        }
+    };
+    let body = payload.to_string();
+    let delivery_id = api_client::next_flow_id(&SystemClock, slot, "webhook");
+    let attempts = target.max_retries + 1;
+
+    for attempt in 1..=attempts {
+        let timestamp = Local::now().timestamp();
+        let mut request = client
+            .post(&target.url)
+            .header("content-type", "application/json")
+            .header("user-agent", concat!("glm-tray/", env!("CARGO_PKG_VERSION")))
+            .header("x-glm-tray-event", event)
+            .header("x-glm-tray-delivery", &delivery_id)
+            .header("x-glm-tray-timestamp", timestamp.to_string())
+            .body(body.clone());
+        if let Some(signature) = target
+            .secret
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .and_then(|secret| sign(secret, timestamp, body.as_bytes()))
+        {
+            request = request.header("x-glm-tray-signature", format!("sha256={signature}"));
+        }
+        for (name, value) in &target.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        let start = Instant::now();
+        let result = request.send().await;
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let (status, error, retryable) = match &result {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None, false),
+            Ok(response) => {
+                let status = response.status();
+                let retryable = status.is_server_error() || status.as_u16() == 429;
+                (Some(status.as_u16()), Some(format!("HTTP {status}")), retryable)
+            }
+            // reqwest errors name the URL, and with it any secret in the path.
+            Err(err) => (None, Some(err.to_string().replace(&target.url, &origin(&target.url))), true),
+        };
+        let will_retry = retryable && attempt < attempts;
+
+        let _ = file_logger::append(
+            host,
+            file_logger::webhook_entry(
+                slot,
+                &origin(&target.url),
+                status,
+                error.clone(),
+                json!({
+                    "target": target.name,
+                    "event": event,
+                    "attempt": attempt,
+                    "max_attempts": attempts,
+                    "will_retry": will_retry,
+                }),
+                duration_ms,
+                delivery_id.clone(),
+            ),
+        )
+        .await;
+
+        let Some(error) = error else {
+            return;
+        };
+        if !will_retry {
+            warn!(
+                "webhook '{}' delivery of {} failed after {} attempt(s): {}",
+                target.name, event, attempt, error
+            );
+            return;
+        }
+        let delay = (FIRST_RETRY_DELAY_SECS << (attempt - 1).min(8)).min(MAX_RETRY_DELAY_SECS);
+        time::sleep(Duration::from_secs(delay)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_sha256_over_timestamp_and_body() {
+        let body = br#"{"event":"quota.reset-occurred","slot":1}"#;
+        assert_eq!(
+            sign("whsec_test", 1_767_225_600, body).as_deref(),
+            Some("bed66751c59289da1453945a3d99e332f4f2c800717c203c129591679b6b85ce")
+        );
+        assert_ne!(sign("whsec_test", 1_767_225_601, body), sign("whsec_test", 1_767_225_600, body));
+    }
+
+    #[test]
+    fn logged_urls_keep_only_the_origin() {
+        assert_eq!(origin("https://hooks.slack.com/services/T000/B000/XXXXXXXX"), "https://hooks.slack.com");
+        assert_eq!(origin("http://127.0.0.1:9000/hook?token=abc"), "http://127.0.0.1:9000");
+        assert_eq!(origin("not a url"), "invalid URL");
+    }
+
+    #[test]
+    fn template_placeholders_keep_types_or_interpolate_text() {
+        let context = json!({
+            "event": THRESHOLD_EVENT,
+            "slot": 2,
+            "slot_name": "Work",
+            "details": { "percentage": 96, "threshold": 95 },
+        });
+        let template = json!({
+            "text": "{{slot_name}} is at {{details.percentage}}% ({{event}})",
+            "slot": "{{slot}}",
+            "details": " {{ details }} ",
+            "fields": ["{{details.threshold}}", "{{missing}}", "left {{missing}} right", 7],
+            "unclosed": "{{slot",
+        });
+        assert_eq!(
+            render(&template, &context),
+            json!({
+                "text": "Work is at 96% (quota.threshold-crossed)",
+                "slot": 2,
+                "details": { "percentage": 96, "threshold": 95 },
+                "fields": [95, null, "left  right", 7],
+                "unclosed": "{{slot",
+            })
+        );
+    }
+
+    #[test]
+    fn targets_filter_by_slot_event_pattern_and_threshold() {
+        let details = json!({});
+        let target = WebhookTarget {
+            events: vec!["quota-poller.*".to_string(), RESET_EVENT.to_string()],
+            slots: vec![1, 3],
+            ..WebhookTarget::default()
+        };
+        assert!(target.matches(1, "quota-poller.wake-confirmed", &details));
+        assert!(target.matches(3, RESET_EVENT, &details));
+        assert!(!target.matches(2, RESET_EVENT, &details));
+        assert!(!target.matches(1, "wake.scheduler.fired", &details));
+        assert!(!WebhookTarget { enabled: false, ..target.clone() }.matches(1, RESET_EVENT, &details));
+
+        // No event or slot filter: everything, but only the watched thresholds.
+        let all = WebhookTarget { quota_thresholds: vec![80], ..WebhookTarget::default() };
+        assert!(all.matches(7, "wake.scheduler.fired", &details));
+        assert!(all.matches(7, THRESHOLD_EVENT, &json!({ "threshold": 80 })));
+        assert!(!all.matches(7, THRESHOLD_EVENT, &json!({ "threshold": 95 })));
+    }
+}
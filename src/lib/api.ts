@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { AppConfig, RuntimeStatus } from "./types";
-import { STORAGE_KEY, isTauriRuntime, KEY_RANGE, PLATFORMS } from "./constants";
+import type { AlertKind, AlertRule, AlertSettings, AppConfig, CircuitStatus, LogRedaction, NetworkConfig, RetryPolicy, RuntimeStatus, WakeRequestConfig } from "./types";
+import { STORAGE_KEY, isTauriRuntime, PLATFORMS, defaultWakeRequest, detectProvider } from "./constants";
 
 export function logUiAction(action: string, slot?: number, details?: Record<string, unknown>): void {
   if (!isTauriRuntime) return;
@@ -12,6 +12,14 @@ export function hasSlotWithKey(config: AppConfig | null): boolean {
   return config.slots.some((slot) => slot.api_key.trim().length > 0);
 }
 
+/** Same masking the backend applies before keys reach the UI. */
+export function maskKey(apiKey: string): string {
+  const chars = [...apiKey.trim()];
+  if (chars.length === 0) return "";
+  if (chars.length <= 8) return "••••";
+  return `••••${chars.slice(-4).join("")}`;
+}
+
 // Keep old name as alias for backwards compat
 export const hasEnabledSlotWithKey = hasSlotWithKey;
 
@@ -21,40 +29,155 @@ function defaultSlot(slot: number) {
     name: "",
     enabled: false,
     api_key: "",
+    provider: "zai",
     quota_url: PLATFORMS.zai.quota,
     request_url: PLATFORMS.zai.request,
+    protocol: "openai",
+    proxy_url: null,
     schedule_interval_enabled: false,
     schedule_times_enabled: false,
     schedule_after_reset_enabled: false,
+    schedule_cron_enabled: false,
     schedule_interval_minutes: 60,
     schedule_times: [] as string[],
     schedule_after_reset_minutes: 1,
+    schedule_cron: [] as string[],
+    schedule_timezone: "",
+    schedule_days: 0x7f,
+    missed_fire_policy: "fire_once",
     poll_interval_minutes: 30,
     logging: false,
+    wake_request: defaultWakeRequest(),
   };
 }
 
 function defaultConfig(): AppConfig {
   return {
-    slots: KEY_RANGE.map((s) => defaultSlot(s)),
+    slots: [],
     theme: "glm",
     global_quota_url: PLATFORMS.zai.quota,
     global_request_url: PLATFORMS.zai.request,
     max_log_days: 7,
+    max_log_file_mb: 20,
+    max_log_total_mb: 200,
+    compress_logs: true,
+    history_retention_days: 90,
     wake_quota_retry_window_minutes: 15,
     max_consecutive_errors: 10,
     quota_poll_backoff_cap_minutes: 480,
+    circuit_probe_minutes: 15,
     debug: false,
     mock_url: null,
     auto_update: true,
+    next_slot_id: 1,
+    proxy_enabled: false,
+    proxy_port: 8484,
+    proxy_token: null,
+    alerts: defaultAlertSettings(),
+    webhooks: [],
+    network: normalizeNetwork(undefined),
+    retry: normalizeRetry(undefined),
+    log_redaction: normalizeLogRedaction(undefined),
+  };
+}
+
+export function normalizeNetwork(network: Partial<NetworkConfig> | undefined): NetworkConfig {
+  return {
+    proxy_url: network?.proxy_url?.trim() ?? "",
+    no_proxy: network?.no_proxy?.trim() ?? "",
+    ca_bundle_path: network?.ca_bundle_path?.trim() ?? "",
+    client_cert_path: network?.client_cert_path?.trim() ?? "",
+    client_key_path: network?.client_key_path?.trim() ?? "",
+  };
+}
+
+export function normalizeRetry(retry: Partial<RetryPolicy> | undefined): RetryPolicy {
+  const whole = (value: number | undefined, min: number, max: number, fallback: number) =>
+    Number.isFinite(value) ? Math.min(max, Math.max(min, Math.floor(value as number))) : fallback;
+  const max_delay_ms = whole(retry?.max_delay_ms, 1, 60_000, 10_000);
+  const statuses = (retry?.retry_statuses ?? [408, 429, 500, 502, 503, 504])
+    .filter((status) => Number.isInteger(status) && status >= 400 && status <= 599);
+  return {
+    max_attempts: whole(retry?.max_attempts, 1, 10, 3),
+    base_delay_ms: whole(retry?.base_delay_ms, 1, max_delay_ms, 500),
+    max_delay_ms,
+    jitter_percent: whole(retry?.jitter_percent, 0, 100, 50),
+    retry_statuses: [...new Set(statuses)].sort((a, b) => a - b),
+    respect_retry_after: retry?.respect_retry_after ?? true,
+  };
+}
+
+export function normalizeLogRedaction(redaction: Partial<LogRedaction> | undefined): LogRedaction {
+  const rules = (list: string[] | undefined) => (list ?? []).map((rule) => rule.trim()).filter(Boolean).slice(0, 20);
+  return {
+    enabled: redaction?.enabled ?? true,
+    header_names: rules(redaction?.header_names),
+    json_paths: rules(redaction?.json_paths),
+    patterns: rules(redaction?.patterns),
+  };
+}
+
+export function defaultAlertSettings(): AlertSettings {
+  const rule = (kind: AlertKind, threshold: number, quiet_minutes: number): AlertRule => ({
+    enabled: true,
+    kind,
+    threshold,
+    slots: [],
+    quiet_minutes,
+  });
+  return {
+    enabled: false,
+    debounce_seconds: 30,
+    rules: [
+      rule("quota_threshold", 80, 60),
+      rule("quota_threshold", 95, 60),
+      rule("wake_failed", 0, 30),
+      rule("wake_auto_disabled", 0, 0),
+    ],
+  };
+}
+
+function normalizeAlerts(alerts: AlertSettings | undefined): AlertSettings {
+  if (!alerts) return defaultAlertSettings();
+  return {
+    enabled: alerts.enabled ?? false,
+    debounce_seconds: Math.min(3_600, Math.max(0, Math.floor(Number(alerts.debounce_seconds) || 0))),
+    rules: (alerts.rules ?? []).slice(0, 20).map((rule) => ({
+      enabled: rule.enabled ?? true,
+      kind: rule.kind ?? "quota_threshold",
+      threshold: rule.kind === "quota_threshold"
+        ? Math.min(100, Math.max(1, Math.floor(Number(rule.threshold) || 80)))
+        : Math.floor(Number(rule.threshold) || 0),
+      slots: [...new Set(rule.slots ?? [])].sort((a, b) => a - b),
+      quiet_minutes: Math.min(10_080, Math.max(0, Math.floor(Number(rule.quiet_minutes) || 0))),
+    })),
   };
 }
 
-function defaultRuntimeStatus(): RuntimeStatus {
+function normalizeWakeRequest(wake: WakeRequestConfig | undefined): WakeRequestConfig {
+  if (!wake) return defaultWakeRequest();
+  const messages = (wake.messages ?? [])
+    .filter((m) => ["system", "user", "assistant"].includes(m.role) && m.content?.trim())
+    .slice(0, 10);
+  const maxTokens = Math.floor(Number(wake.max_tokens) || 0);
+  return {
+    model: wake.model?.trim() || defaultWakeRequest().model,
+    messages: messages.some((m) => m.role === "user") ? messages : defaultWakeRequest().messages,
+    max_tokens: maxTokens > 0 ? Math.min(4_096, maxTokens) : null,
+    disable_thinking: wake.disable_thinking ?? false,
+    extra: wake.extra ?? {},
+  };
+}
+
+function closedCircuit(): CircuitStatus {
+  return { state: "closed", opened_epoch_ms: null, next_probe_epoch_ms: null, failed_probes: 0 };
+}
+
+function defaultRuntimeStatus(config: AppConfig): RuntimeStatus {
   return {
     monitoring: false,
-    slots: KEY_RANGE.map((s) => ({
-      slot: s,
+    slots: config.slots.map(({ slot }) => ({
+      slot,
       name: "",
       enabled: false,
       timer_active: false,
@@ -67,11 +190,20 @@ function defaultRuntimeStatus(): RuntimeStatus {
       consecutive_errors: 0,
       wake_pending: false,
       wake_reset_epoch_ms: null,
-      wake_auto_disabled: false,
-      auto_disabled: false,
+      quota_circuit: closedCircuit(),
+      wake_circuit: closedCircuit(),
       total_model_calls_5h: 0,
       total_tokens_5h: 0,
       quota_last_updated: null,
+      forecast: null,
+      proxy: {
+        requests: 0,
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        total_tokens: 0,
+        failovers: 0,
+        last_used: null,
+      },
     })),
   };
 }
@@ -84,6 +216,15 @@ export function normalizeConfig(config: AppConfig): AppConfig {
   const max_log_days = Number.isFinite(config.max_log_days)
     ? Math.min(365, Math.max(1, Math.floor(config.max_log_days)))
     : 7;
+  const max_log_file_mb = Number.isFinite(config.max_log_file_mb)
+    ? Math.min(1_024, Math.max(1, Math.floor(config.max_log_file_mb)))
+    : 20;
+  const max_log_total_mb = Number.isFinite(config.max_log_total_mb)
+    ? Math.min(102_400, Math.max(max_log_file_mb, Math.floor(config.max_log_total_mb)))
+    : 200;
+  const history_retention_days = Number.isFinite(config.history_retention_days)
+    ? Math.min(3_650, Math.max(1, Math.floor(config.history_retention_days)))
+    : 90;
   const wake_quota_retry_window_minutes = Number.isFinite(config.wake_quota_retry_window_minutes)
     ? Math.min(1_440, Math.max(1, Math.floor(config.wake_quota_retry_window_minutes)))
     : 15;
@@ -93,16 +234,29 @@ export function normalizeConfig(config: AppConfig): AppConfig {
   const quota_poll_backoff_cap_minutes = Number.isFinite(config.quota_poll_backoff_cap_minutes)
     ? Math.min(1_440, Math.max(1, Math.floor(config.quota_poll_backoff_cap_minutes)))
     : 480;
+  const circuit_probe_minutes = Number.isFinite(config.circuit_probe_minutes)
+    ? Math.min(1_440, Math.max(1, Math.floor(config.circuit_probe_minutes)))
+    : 15;
+  const proxy_port = Number.isFinite(config.proxy_port) && config.proxy_port >= 1024 && config.proxy_port <= 65_535
+    ? Math.floor(config.proxy_port)
+    : 8484;
 
-  const slots = KEY_RANGE.map((index) => {
-    const current = config.slots.find((s) => s.slot === index) ?? defaultSlot(index);
+  const slots = (config.slots ?? []).map((current) => {
     return {
       ...current,
-      slot: index,
       poll_interval_minutes: Math.max(1, Number(current.poll_interval_minutes) || 30),
       schedule_interval_minutes: Math.max(1, Number(current.schedule_interval_minutes) || 60),
       schedule_after_reset_minutes: Math.max(1, Number(current.schedule_after_reset_minutes) || 1),
       schedule_times: (current.schedule_times ?? []).slice(0, 5),
+      schedule_cron_enabled: current.schedule_cron_enabled ?? false,
+      schedule_cron: (current.schedule_cron ?? []).slice(0, 5),
+      schedule_timezone: current.schedule_timezone?.trim() ?? "",
+      schedule_days: (Number(current.schedule_days) & 0x7f) || 0x7f,
+      missed_fire_policy: current.missed_fire_policy ?? "fire_once",
+      protocol: current.protocol === "anthropic" ? "anthropic" : "openai",
+      provider: current.provider ?? detectProvider(current.quota_url, current.request_url, current.protocol),
+      proxy_url: current.proxy_url?.trim() ?? null,
+      wake_request: normalizeWakeRequest(current.wake_request),
     };
   });
 
@@ -112,12 +266,27 @@ export function normalizeConfig(config: AppConfig): AppConfig {
     global_quota_url: validGlobalQuota ? global_quota_url : PLATFORMS.zai.quota,
     global_request_url: validGlobalRequest ? global_request_url : PLATFORMS.zai.request,
     max_log_days,
+    max_log_file_mb,
+    max_log_total_mb,
+    compress_logs: config.compress_logs ?? true,
+    history_retention_days,
     wake_quota_retry_window_minutes,
     max_consecutive_errors,
     quota_poll_backoff_cap_minutes,
+    circuit_probe_minutes,
     debug: config.debug ?? false,
     mock_url: config.mock_url?.trim() || null,
     auto_update: config.auto_update ?? true,
+    next_slot_id: Math.max(config.next_slot_id ?? 1, ...slots.map((s) => s.slot + 1)),
+    proxy_enabled: config.proxy_enabled ?? false,
+    proxy_port,
+    proxy_token: config.proxy_token?.trim() || null,
+    alerts: normalizeAlerts(config.alerts),
+    // Edited in settings.json only; passed through untouched.
+    webhooks: config.webhooks ?? [],
+    network: normalizeNetwork(config.network),
+    retry: normalizeRetry(config.retry),
+    log_redaction: normalizeLogRedaction(config.log_redaction),
   };
 }
 
@@ -140,9 +309,36 @@ export async function backendInvoke<T>(
       return config as T;
     case "save_settings": {
       const next = normalizeConfig((args?.settings as AppConfig) ?? config);
+      const plaintext = next.slots.find((s) => s.api_key.trim() && !s.api_key.startsWith("••••"));
+      if (plaintext) throw new Error(`slot ${plaintext.slot} API key must be masked; replace it with set_slot_api_key`);
+      localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
+      return next as T;
+    }
+    case "set_slot_api_key": {
+      const id = args?.slot as number;
+      const base = (args?.settings as AppConfig | null) ?? config;
+      if (!base.slots.some((s) => s.slot === id)) throw new Error(`slot ${id} not found`);
+      const apiKey = maskKey((args?.apiKey as string) ?? "");
+      const next = normalizeConfig({ ...base, slots: base.slots.map((s) => (s.slot === id ? { ...s, api_key: apiKey } : s)) });
+      localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
+      return next as T;
+    }
+    case "add_slot": {
+      const id = config.next_slot_id ?? 1;
+      const slot = defaultSlot(id);
+      slot.name = (args?.name as string | null) ?? "";
+      const next = normalizeConfig({ ...config, slots: [...config.slots, slot], next_slot_id: id + 1 });
       localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
       return next as T;
     }
+    case "remove_slot": {
+      const id = args?.slot as number;
+      if (!config.slots.some((s) => s.slot === id)) throw new Error(`slot ${id} not found`);
+      const next = normalizeConfig({ ...config, slots: config.slots.filter((s) => s.slot !== id) });
+      localStorage.setItem(STORAGE_KEY, JSON.stringify(next));
+      previewRuntime.slots = previewRuntime.slots.filter((s) => s.slot !== id);
+      return next as T;
+    }
     case "start_monitoring":
       if (!hasSlotWithKey(config)) return undefined as T;
       previewRuntime = {
@@ -161,16 +357,18 @@ export async function backendInvoke<T>(
           consecutive_errors: 0,
           wake_pending: false,
           wake_reset_epoch_ms: null,
-          wake_auto_disabled: false,
-          auto_disabled: false,
+          quota_circuit: closedCircuit(),
+          wake_circuit: closedCircuit(),
           total_model_calls_5h: 0,
           total_tokens_5h: 0,
           quota_last_updated: null,
+          forecast: null,
+          proxy: { requests: 0, prompt_tokens: 0, completion_tokens: 0, total_tokens: 0, failovers: 0, last_used: null },
         })),
       };
       return undefined as T;
     case "stop_monitoring":
-      previewRuntime = defaultRuntimeStatus();
+      previewRuntime = defaultRuntimeStatus(config);
       return undefined as T;
     case "get_runtime_status":
       return previewRuntime as T;
@@ -200,6 +398,26 @@ export async function backendInvoke<T>(
         total_search_mcp_24h: 0,
       } as T;
     }
+    case "query_usage_history":
+    case "query_reset_cycles":
+    case "preview_cron_schedule":
+      return [] as T;
+    case "query_logs":
+      return { entries: [], total: 0, next_offset: null } as T;
+    case "simulate_schedule": {
+      const now = Date.now();
+      return {
+        slot: (args?.config as { slot?: number })?.slot ?? 1,
+        start_epoch_ms: now,
+        end_epoch_ms: now + 24 * 3600000,
+        wakes: 0,
+        polls: 0,
+        confirmed: 0,
+        wake_auto_disabled: false,
+        quota_auto_disabled: false,
+        events: [],
+      } as T;
+    }
     case "check_for_updates_cmd":
       return { has_update: false, current_version: "preview", latest_version: "preview", download_url: "", release_notes: "", published_at: "" } as T;
     case "log_ui_action":
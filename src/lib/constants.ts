@@ -1,30 +1,41 @@
-import type { Platform, KeySlotConfig } from "./types";
+import type { ApiProtocol, Platform, ProviderKind, KeySlotConfig, WakeRequestConfig } from "./types";
 
 export const STORAGE_KEY = "glm-tray-preview-settings";
 export const isTauriRuntime =
   typeof window !== "undefined" && "__TAURI_INTERNALS__" in window;
 
-export const MAX_KEYS = 4;
-export const KEY_RANGE = Array.from({ length: MAX_KEYS }, (_, i) => i + 1);
-
-export const PLATFORMS: Record<Platform, { label: string; base: string; quota: string; request: string }> = {
+export const PLATFORMS: Record<Platform, { label: string; base: string; quota: string; request: string; anthropic: string }> = {
   zai: {
     label: "Z.ai",
     base: "https://api.z.ai",
     quota: "https://api.z.ai/api/monitor/usage/quota/limit",
     request: "https://api.z.ai/api/coding/paas/v4/chat/completions",
+    anthropic: "https://api.z.ai/api/anthropic/v1/messages",
   },
   bigmodel: {
     label: "BigModel",
     base: "https://open.bigmodel.cn",
     quota: "https://open.bigmodel.cn/api/monitor/usage/quota/limit",
     request: "https://open.bigmodel.cn/api/coding/paas/v4/chat/completions",
+    anthropic: "https://open.bigmodel.cn/api/anthropic/v1/messages",
   },
 };
 
-export function detectPlatform(quotaUrl: string): Platform {
-  if (quotaUrl.includes("bigmodel.cn")) return "bigmodel";
-  return "zai";
+/** Built-in provider whose default endpoints these are, matching the
+ *  backend's config migration; other URLs, even on a built-in host, are
+ *  custom. An unset request URL counts as the default. */
+export function detectProvider(quotaUrl: string, request?: string | null, protocol: ApiProtocol = "openai"): ProviderKind {
+  const match = (Object.keys(PLATFORMS) as Platform[]).find(
+    (platform) => quotaUrl.trim() === PLATFORMS[platform].quota
+      && (request == null || request.trim() === requestUrl(platform, protocol)),
+  );
+  return match ?? "custom";
+}
+
+/** Default wake URL of `platform` for `protocol`. */
+export function requestUrl(platform: Platform, protocol: ApiProtocol): string {
+  const plat = PLATFORMS[platform];
+  return protocol === "anthropic" ? plat.anthropic : plat.request;
 }
 
 export function defaultSlot(slot: number): KeySlotConfig {
@@ -33,15 +44,39 @@ export function defaultSlot(slot: number): KeySlotConfig {
     name: "",
     enabled: false,
     api_key: "",
+    provider: "zai",
     quota_url: PLATFORMS.zai.quota,
     request_url: PLATFORMS.zai.request,
+    protocol: "openai",
+    proxy_url: null,
     schedule_interval_enabled: false,
     schedule_times_enabled: false,
     schedule_after_reset_enabled: false,
+    schedule_cron_enabled: false,
     schedule_interval_minutes: 60,
     schedule_times: [],
     schedule_after_reset_minutes: 1,
+    schedule_cron: [],
+    schedule_timezone: "",
+    schedule_days: 0x7f,
+    missed_fire_policy: "fire_once",
     poll_interval_minutes: 30,
     logging: false,
+    wake_request: defaultWakeRequest(),
+  };
+}
+
+export const DEFAULT_WAKE_MODEL = "glm-5";
+
+export function defaultWakeRequest(): WakeRequestConfig {
+  return {
+    model: DEFAULT_WAKE_MODEL,
+    messages: [
+      { role: "system", content: "You are a helpful assistant." },
+      { role: "user", content: "ping" },
+    ],
+    max_tokens: null,
+    disable_thinking: false,
+    extra: {},
   };
 }
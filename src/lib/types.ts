@@ -1,24 +1,62 @@
-export type View = "dashboard" | "settings" | "1" | "2" | "3" | "4";
+export type View = "dashboard" | "settings" | `${number}`;
 export type KeyTab = "stats" | "schedule" | "settings";
 export type Platform = "zai" | "bigmodel";
+/** A built-in platform, or `custom` for hand-entered URLs. */
+export type ProviderKind = Platform | "custom";
+export type ApiProtocol = "openai" | "anthropic";
+export type MissedFirePolicy = "fire_once" | "fire_all" | "skip";
 
 export interface KeySlotConfig {
+  /** Stable slot ID (never reused after removal). */
   slot: number;
   name: string;
   enabled: boolean;
+  /** Masked (`••••` + last four characters); replaced via `set_slot_api_key`. */
   api_key: string;
+  /** Built-in providers derive both URLs; `custom` uses them as entered. */
+  provider: ProviderKind;
   quota_url: string;
   request_url: string | null;
+  /** Shape and auth headers of wake and warmup requests sent to `request_url`. */
+  protocol: ApiProtocol;
+  /** Proxy for this key's requests; null = the global `network.proxy_url`. */
+  proxy_url: string | null;
   // Schedule modes - can enable multiple simultaneously
   schedule_interval_enabled: boolean;
   schedule_times_enabled: boolean;
   schedule_after_reset_enabled: boolean;
+  schedule_cron_enabled: boolean;
   // Mode-specific settings
   schedule_interval_minutes: number;
   schedule_times: string[];
   schedule_after_reset_minutes: number;
+  /** Five-field cron expressions (max 5), matched in `schedule_timezone`. */
+  schedule_cron: string[];
+  /** IANA zone for times, after-reset and cron modes; "" = system zone. */
+  schedule_timezone: string;
+  /** Days times and after-reset modes may fire on (bit 0 = Monday … bit 6 = Sunday). */
+  schedule_days: number;
+  /** Wakes missed during a system sleep: one catch-up, all of them, or none. */
+  missed_fire_policy: MissedFirePolicy;
   poll_interval_minutes: number;
   logging: boolean;
+  /** Body of the chat request sent by wakes and warmups. */
+  wake_request: WakeRequestConfig;
+}
+
+export interface WakeMessage {
+  role: "system" | "user" | "assistant";
+  /** `{{slot}}`, `{{slot_name}}` and `{{timestamp}}` are filled in per request. */
+  content: string;
+}
+
+export interface WakeRequestConfig {
+  model: string;
+  messages: WakeMessage[];
+  max_tokens: number | null;
+  disable_thinking: boolean;
+  /** Extra top-level body fields, e.g. `temperature`. */
+  extra: Record<string, unknown>;
 }
 
 export interface AppConfig {
@@ -27,13 +65,105 @@ export interface AppConfig {
   global_quota_url: string;
   global_request_url: string;
   max_log_days: number;
+  /** Size in MB at which a day's log rolls over to `YYYY-MM-DD.N.jsonl`. */
+  max_log_file_mb: number;
+  /** Disk budget in MB for all log files; the oldest are deleted first. */
+  max_log_total_mb: number;
+  /** Gzip closed log files. */
+  compress_logs: boolean;
+  history_retention_days: number;
   wake_quota_retry_window_minutes: number;
   max_consecutive_errors: number;
   quota_poll_backoff_cap_minutes: number;
+  /** Minutes between probes while a quota or wake circuit is open. */
+  circuit_probe_minutes: number;
   debug: boolean;
   mock_url?: string | null;
   config_version?: number;
   auto_update: boolean;
+  next_slot_id?: number;
+  proxy_enabled: boolean;
+  proxy_port: number;
+  /** Bearer token proxy clients must send; null serves any local client. */
+  proxy_token: string | null;
+  alerts: AlertSettings;
+  webhooks: WebhookTarget[];
+  network: NetworkConfig;
+  retry: RetryPolicy;
+  log_redaction: LogRedaction;
+}
+
+/** Added to the built-in rules, which always scrub keys and auth headers. */
+export interface LogRedaction {
+  enabled: boolean;
+  /** Header, JSON field and URL query parameter names (case-insensitive). */
+  header_names: string[];
+  /** Dotted paths into a log entry; `*` matches any field or index. */
+  json_paths: string[];
+  /** Regular expressions (Rust syntax). */
+  patterns: string[];
+}
+
+export interface RetryPolicy {
+  /** Tries per request, the first included; 1 = no retries. */
+  max_attempts: number;
+  /** Delay before the first retry, doubled for each later one. */
+  base_delay_ms: number;
+  /** Longest single delay; a longer Retry-After ends the retries. */
+  max_delay_ms: number;
+  /** Up to this share of each delay is randomly taken off. */
+  jitter_percent: number;
+  /** Retryable HTTP statuses; connection errors and timeouts always are. */
+  retry_statuses: number[];
+  respect_retry_after: boolean;
+}
+
+export interface NetworkConfig {
+  /** http(s):// or socks5(h):// proxy; "" = environment, "direct" = no proxy. */
+  proxy_url: string;
+  /** Hosts that skip the proxy, in NO_PROXY syntax. */
+  no_proxy: string;
+  /** PEM file of extra root certificates. */
+  ca_bundle_path: string;
+  /** PEM client certificate for mutual TLS (key inline or in `client_key_path`). */
+  client_cert_path: string;
+  client_key_path: string;
+}
+
+export interface WebhookTarget {
+  enabled: boolean;
+  name: string;
+  url: string;
+  events: string[];
+  slots: number[];
+  quota_thresholds: number[];
+  template: unknown | null;
+  secret: string | null;
+  headers: Record<string, string>;
+  max_retries: number;
+}
+
+export type AlertKind =
+  | "quota_threshold"
+  | "reset_started"
+  | "reset_ended"
+  | "wake_confirmed"
+  | "wake_failed"
+  | "wake_auto_disabled"
+  | "forecast_exhaustion";
+
+export interface AlertRule {
+  enabled: boolean;
+  kind: AlertKind;
+  threshold: number;
+  slots: number[];
+  quiet_minutes: number;
+}
+
+export interface AlertSettings {
+  enabled: boolean;
+  debounce_seconds: number;
+  rules: AlertRule[];
 }
 
 export interface SlotRuntimeStatus {
@@ -50,11 +180,44 @@ export interface SlotRuntimeStatus {
   consecutive_errors: number;
   wake_pending: boolean;
   wake_reset_epoch_ms: number | null;
-  wake_auto_disabled: boolean;
-  auto_disabled: boolean;
+  quota_circuit: CircuitStatus;
+  wake_circuit: CircuitStatus;
   total_model_calls_5h: number;
   total_tokens_5h: number;
   quota_last_updated: string | null;
+  forecast: QuotaForecast | null;
+  proxy: ProxyUsage;
+}
+
+export type CircuitState = "closed" | "open" | "half_open";
+
+/** Per-slot, per-endpoint circuit breaker. */
+export interface CircuitStatus {
+  state: CircuitState;
+  opened_epoch_ms: number | null;
+  /** When the next probe is due while open. */
+  next_probe_epoch_ms: number | null;
+  failed_probes: number;
+}
+
+export interface QuotaForecast {
+  burn_rate_pct_per_hour: number;
+  tokens_per_hour: number;
+  will_exhaust: boolean;
+  exhausts_at_epoch_ms: number | null;
+  projected_percentage_at_reset: number;
+  basis: "trend" | "window-average";
+  samples: number;
+  computed_at_epoch_ms: number;
+}
+
+export interface ProxyUsage {
+  requests: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  total_tokens: number;
+  failovers: number;
+  last_used: string | null;
 }
 
 export interface RuntimeStatus {
@@ -106,4 +269,131 @@ export interface QuotaUpdateEvent {
   total_model_calls_5h: number;
   total_tokens_5h: number;
   quota_last_updated: string | null;
+  forecast?: QuotaForecast | null;
+}
+
+export interface ProxyUsageEvent {
+  slot: number;
+  proxy: ProxyUsage;
+}
+
+export interface UsageSample {
+  ts: number;
+  slot: number;
+  source: "poll" | "stats" | "mixed";
+  percentage?: number;
+  timer_active?: boolean;
+  next_reset_epoch_ms?: number;
+  model_calls_5h?: number;
+  tokens_5h?: number;
+  model_calls_24h?: number;
+  tokens_24h?: number;
+  network_search_24h?: number;
+  web_read_24h?: number;
+  zread_24h?: number;
+  search_mcp_24h?: number;
+}
+
+export interface ResetCycle {
+  reset_epoch_ms: number;
+  first_seen_ms: number;
+  last_seen_ms: number;
+  peak_percentage: number;
+  peak_model_calls_5h: number;
+  peak_tokens_5h: number;
+  samples: number;
+}
+
+/** One line of the daily JSONL logs, as `query_logs` and the `log-entry` event carry it. */
+export interface LogEntry {
+  ts: string;
+  slot: number;
+  action: string;
+  method: string;
+  url: string;
+  flow_id?: string;
+  phase?: string;
+  request_body?: unknown;
+  status?: number;
+  response_body?: unknown;
+  error?: string;
+  details?: unknown;
+  duration_ms?: number;
+}
+
+/** Filters for `query_logs`; `action` may end in `*` to match a prefix such as `wake.*`. */
+export interface LogQuery {
+  from_ms?: number | null;
+  to_ms?: number | null;
+  slot?: number | null;
+  action?: string | null;
+  phase?: string | null;
+  flow_id?: string | null;
+  offset?: number;
+  limit?: number | null;
+}
+
+export interface LogPage {
+  /** Newest first. */
+  entries: LogEntry[];
+  total: number;
+  next_offset: number | null;
+}
+
+/** One upcoming cron fire time from `preview_cron_schedule`. */
+export interface CronFireTime {
+  epoch_ms: number;
+  local: string;
+  expression: string;
+}
+
+/** Scripted quota answer for `simulate_schedule`, from `at_minute` on. */
+export interface QuotaStep {
+  at_minute: number;
+  percentage?: number;
+  reset_at_minute?: number | null;
+  error?: string | null;
+  wake_error?: string | null;
+}
+
+export interface SimulationInput {
+  start_epoch_ms?: number | null;
+  hours?: number;
+  quota?: QuotaStep[];
+  wake_starts_window?: boolean;
+}
+
+export type SimulationEventKind =
+  | "wake"
+  | "wake-skipped"
+  | "wake-suppressed"
+  | "wake-failed"
+  | "wake-confirmed"
+  | "wake-unconfirmed"
+  | "wake-auto-disabled"
+  | "wake-probe"
+  | "poll"
+  | "poll-retry"
+  | "poll-failed"
+  | "poll-auto-disabled"
+  | "poll-probe";
+
+export interface SimulationEvent {
+  epoch_ms: number;
+  local: string;
+  kind: SimulationEventKind;
+  detail: string;
+}
+
+/** Dry-run timeline of a slot's schedule from `simulate_schedule`. */
+export interface SimulationReport {
+  slot: number;
+  start_epoch_ms: number;
+  end_epoch_ms: number;
+  wakes: number;
+  polls: number;
+  confirmed: number;
+  wake_auto_disabled: boolean;
+  quota_auto_disabled: boolean;
+  events: SimulationEvent[];
 }
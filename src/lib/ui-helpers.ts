@@ -1,10 +1,10 @@
-import type { KeySlotConfig } from './types';
+import type { CircuitStatus, KeySlotConfig } from './types';
 
 export function dotClass(
     slot: KeySlotConfig | undefined,
     rt: {
-        auto_disabled?: boolean;
-        wake_auto_disabled?: boolean;
+        quota_circuit?: CircuitStatus;
+        wake_circuit?: CircuitStatus;
         wake_pending?: boolean;
         quota_consecutive_errors?: number;
         wake_consecutive_errors?: number;
@@ -20,8 +20,8 @@ export function dotClass(
     if (!rt) return "bg-base-content/20";
 
     if (
-        rt.auto_disabled ||
-        rt.wake_auto_disabled ||
+        circuitOpen(rt.quota_circuit) ||
+        circuitOpen(rt.wake_circuit) ||
         (rt.consecutive_errors && rt.consecutive_errors > 0) ||
         (rt.quota_consecutive_errors && rt.quota_consecutive_errors > 0) ||
         (rt.wake_consecutive_errors && rt.wake_consecutive_errors > 0)
@@ -33,6 +33,11 @@ export function dotClass(
     return "bg-base-content/20";
 }
 
+/** Open or probing: the slot's quota polling or wakes are paused. */
+export function circuitOpen(circuit: CircuitStatus | undefined): boolean {
+    return !!circuit && circuit.state !== "closed";
+}
+
 // Keep old name as alias
 export const getDotClass = dotClass;
 
@@ -1,6 +1,6 @@
 import { defineStore } from 'pinia';
 import { ref, reactive } from 'vue';
-import type { RuntimeStatus, SlotStats, QuotaUpdateEvent } from '../lib/types';
+import type { RuntimeStatus, SlotStats, QuotaUpdateEvent, ProxyUsageEvent } from '../lib/types';
 import { backendInvoke, logUiAction } from '../lib/api';
 import { isTauriRuntime } from '../lib/constants';
 import { listen } from '@tauri-apps/api/event';
@@ -106,6 +106,7 @@ export const useKeysStore = defineStore('keys', () => {
                     rtSlot.total_model_calls_5h = p.total_model_calls_5h;
                     rtSlot.total_tokens_5h = p.total_tokens_5h;
                     rtSlot.quota_last_updated = p.quota_last_updated;
+                    if (p.forecast !== undefined) rtSlot.forecast = p.forecast;
                 }
 
                 // Update cached stats if they exist (stats tab reads from here)
@@ -123,6 +124,13 @@ export const useKeysStore = defineStore('keys', () => {
                 }
             });
 
+            await listen<ProxyUsageEvent>('proxy-usage', (event) => {
+                const rtSlot = runtime.value.slots.find(s => s.slot === event.payload.slot);
+                if (rtSlot) {
+                    rtSlot.proxy = event.payload.proxy;
+                }
+            });
+
             await listen<boolean>('monitoring-changed', (event) => {
                 const monitoring = event.payload;
                 if (!monitoring) {
@@ -26,5 +26,21 @@ export const useSettingsStore = defineStore('settings', () => {
         }
     }
 
-    return { config, loading, fetchSettings, saveSettings };
+    /** Replace a slot's API key; an empty key removes it. `settings`, when given, are saved in the same write. */
+    async function setApiKey(slot: number, apiKey: string, settings?: AppConfig) {
+        const normalized = settings ? normalizeConfig(settings) : null;
+        config.value = await backendInvoke<AppConfig>('set_slot_api_key', { slot, apiKey, settings: normalized });
+    }
+
+    async function addSlot(name?: string): Promise<number | null> {
+        const before = new Set(config.value?.slots.map(s => s.slot) ?? []);
+        config.value = await backendInvoke<AppConfig>('add_slot', { name: name ?? null });
+        return config.value.slots.find(s => !before.has(s.slot))?.slot ?? null;
+    }
+
+    async function removeSlot(slot: number) {
+        config.value = await backendInvoke<AppConfig>('remove_slot', { slot });
+    }
+
+    return { config, loading, fetchSettings, saveSettings, setApiKey, addSlot, removeSlot };
 });